@@ -0,0 +1,78 @@
+//! Bit-level reader and writer for the compact selector encoding.
+//!
+//! A selector is encoded as one presence bit per field in declaration order,
+//! with the bits of an enabled nested selector following its own bit. Bits are
+//! packed most significant first and the last byte is padded with zeros.
+
+use crate::SelectorDecodeError;
+
+/// Appends bits to a byte buffer.
+#[derive(Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads bits back from a byte buffer written by [`BitWriter`].
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    lenient: bool,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, position: 0, lenient: false }
+    }
+
+    /// A reader that yields zero bits past the end of `bytes` instead of failing.
+    pub fn lenient(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, position: 0, lenient: true }
+    }
+
+    pub fn read(&mut self) -> Result<bool, SelectorDecodeError> {
+        let Some(byte) = self.bytes.get(self.position / 8) else {
+            return if self.lenient {
+                Ok(false)
+            } else {
+                Err(SelectorDecodeError::UnexpectedEnd)
+            };
+        };
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Ok(bit)
+    }
+
+    /// Check that only zero padding is left.
+    pub fn finish(self) -> Result<(), SelectorDecodeError> {
+        let used_bytes = self.position.div_ceil(8);
+        if self.bytes.len() > used_bytes {
+            return Err(SelectorDecodeError::TrailingData);
+        }
+        let padding = used_bytes * 8 - self.position;
+        if padding > 0 && self.bytes[used_bytes - 1] & ((1 << padding) - 1) != 0 {
+            return Err(SelectorDecodeError::TrailingData);
+        }
+        Ok(())
+    }
+}
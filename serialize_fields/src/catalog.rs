@@ -0,0 +1,180 @@
+//! Compile-time construction of field path catalogs.
+//!
+//! The derive macro only sees the fields of the struct it is applied to, so the
+//! full list of dot paths for a struct has to be assembled from the catalogs of
+//! its nested structs. These `const fn`s concatenate them into a single
+//! `&'static [&'static str]` during constant evaluation:
+//!
+//! 1. [`catalog_bytes_len`] and [`catalog_len`] size the buffers,
+//! 2. [`catalog_bytes`] writes every path back to back into one byte array,
+//! 3. [`catalog_paths`] slices that array into the individual paths.
+
+/// One field of a struct, as seen by its catalog.
+pub enum CatalogEntry {
+    /// A plain field, contributing its own name.
+    Leaf(&'static str),
+    /// A nested struct field, contributing `name.path` for every nested path.
+    Nested(&'static str, &'static [&'static str]),
+}
+
+/// Number of paths described by `entries`.
+pub const fn catalog_len(entries: &[CatalogEntry]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < entries.len() {
+        count += match entries[i] {
+            CatalogEntry::Leaf(_) => 1,
+            CatalogEntry::Nested(_, paths) => paths.len(),
+        };
+        i += 1;
+    }
+    count
+}
+
+/// Total length in bytes of all paths described by `entries`.
+pub const fn catalog_bytes_len(entries: &[CatalogEntry]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < entries.len() {
+        match entries[i] {
+            CatalogEntry::Leaf(name) => len += name.len(),
+            CatalogEntry::Nested(name, paths) => {
+                let mut j = 0;
+                while j < paths.len() {
+                    len += name.len() + 1 + paths[j].len();
+                    j += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+    len
+}
+
+/// All paths described by `entries`, concatenated without separators.
+///
+/// `N` must be [`catalog_bytes_len`] of the same entries.
+pub const fn catalog_bytes<const N: usize>(entries: &[CatalogEntry]) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    let mut at = 0;
+    let mut i = 0;
+    while i < entries.len() {
+        match entries[i] {
+            CatalogEntry::Leaf(name) => at = write_str(&mut bytes, at, name),
+            CatalogEntry::Nested(name, paths) => {
+                let mut j = 0;
+                while j < paths.len() {
+                    at = write_str(&mut bytes, at, name);
+                    at = write_str(&mut bytes, at, ".");
+                    at = write_str(&mut bytes, at, paths[j]);
+                    j += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+    bytes
+}
+
+/// Split the output of [`catalog_bytes`] back into individual paths.
+///
+/// `C` must be [`catalog_len`] of the same entries.
+pub const fn catalog_paths<const C: usize>(
+    bytes: &'static [u8],
+    entries: &[CatalogEntry],
+) -> [&'static str; C] {
+    let mut paths = [""; C];
+    let mut at = 0;
+    let mut index = 0;
+    let mut i = 0;
+    while i < entries.len() {
+        match entries[i] {
+            CatalogEntry::Leaf(name) => {
+                paths[index] = read_str(bytes, at, name.len());
+                at += name.len();
+                index += 1;
+            }
+            CatalogEntry::Nested(name, nested) => {
+                let mut j = 0;
+                while j < nested.len() {
+                    let len = name.len() + 1 + nested[j].len();
+                    paths[index] = read_str(bytes, at, len);
+                    at += len;
+                    index += 1;
+                    j += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+    paths
+}
+
+const fn write_str(bytes: &mut [u8], mut at: usize, s: &str) -> usize {
+    let s = s.as_bytes();
+    let mut i = 0;
+    while i < s.len() {
+        bytes[at] = s[i];
+        at += 1;
+        i += 1;
+    }
+    at
+}
+
+const fn read_str(bytes: &'static [u8], at: usize, len: usize) -> &'static str {
+    let (_, rest) = bytes.split_at(at);
+    let (s, _) = rest.split_at(len);
+    match core::str::from_utf8(s) {
+        Ok(s) => s,
+        Err(_) => panic!("field catalog is not valid UTF-8"),
+    }
+}
+
+/// Whether `path` can be enabled from a string given the selectable `paths`:
+/// an entry, a dot prefix of one, or either followed by `.*` or `.**`.
+pub const fn is_selectable_path(paths: &[&str], path: &str) -> bool {
+    let path = path.as_bytes();
+    let mut len = path.len();
+    if ends_with(path, b".**") {
+        len -= 3;
+    } else if ends_with(path, b".*") {
+        len -= 2;
+    } else if is_bytes(path, b"*") || is_bytes(path, b"**") {
+        return true;
+    }
+
+    let mut i = 0;
+    while i < paths.len() {
+        let entry = paths[i].as_bytes();
+        if entry.len() >= len && (entry.len() == len || entry[len] == b'.') {
+            let mut j = 0;
+            while j < len && entry[j] == path[j] {
+                j += 1;
+            }
+            if j == len {
+                return true;
+            }
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn ends_with(bytes: &[u8], suffix: &[u8]) -> bool {
+    if bytes.len() < suffix.len() {
+        return false;
+    }
+    let offset = bytes.len() - suffix.len();
+    let mut i = 0;
+    while i < suffix.len() {
+        if bytes[offset + i] != suffix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn is_bytes(bytes: &[u8], other: &[u8]) -> bool {
+    bytes.len() == other.len() && ends_with(bytes, other)
+}
@@ -0,0 +1,49 @@
+//! Serde helper to (de)serialize a selector as a comma-separated field list.
+//!
+//! Use it with `#[serde(with = "serialize_fields::comma_string")]` to embed a
+//! selector in a query-string struct, so `?fields=id,name,profile.bio` is
+//! parsed straight into the selector. Parsing is strict, as with
+//! [`parse_selector`](crate::utils::parse_selector): unknown or malformed
+//! paths are a deserialization error.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use serialize_fields::SerializeFields;
+//! # use serde::{Deserialize, Serialize};
+//! # #[derive(SerializeFields, Serialize)]
+//! # struct User { id: u32, name: String }
+//! #[derive(Deserialize)]
+//! struct ListUsersQuery {
+//!     #[serde(with = "serialize_fields::comma_string")]
+//!     fields: UserSerializeFieldSelector,
+//! }
+//!
+//! let query: ListUsersQuery = serde_json::from_str(r#"{"fields": "id,name"}"#).unwrap();
+//! assert!(query.fields.name.is_some());
+//!
+//! let error = serde_json::from_str::<ListUsersQuery>(r#"{"fields": "id,nmae"}"#);
+//! assert!(error.is_err());
+//! ```
+
+use crate::FieldSelector;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Serialize the selector as its canonical field list, e.g. `id,profile.bio`.
+pub fn serialize<T, S>(selector: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: FieldSelector,
+    S: Serializer,
+{
+    serializer.serialize_str(&selector.enabled_paths().join(","))
+}
+
+/// Deserialize a selector from a comma-separated field list.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FieldSelector,
+    D: Deserializer<'de>,
+{
+    let fields = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+    crate::utils::parse_selector(&fields).map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,500 @@
+//! CSV export of the selected fields.
+//!
+//! Each record is flattened directly from the data, without going through an
+//! intermediate JSON document. Columns are the selector's
+//! [`to_path_list`](crate::FieldSelector::to_path_list), dot-joined: nested
+//! structs become `profile.bio` columns, while sequences, maps and other
+//! compound values are written as compact JSON in a single cell, e.g. a
+//! `posts` column holding the filtered array.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use serialize_fields::SerializeFields;
+//! # use serde::Serialize;
+//! # #[derive(SerializeFields, Serialize)]
+//! # struct User { id: u32, name: String, profile: Profile }
+//! # #[derive(SerializeFields, Serialize)]
+//! # struct Profile { bio: Option<String>, tags: Vec<String> }
+//! let users = vec![
+//!     User { id: 1, name: "Alice".into(), profile: Profile { bio: Some("Hi, all".into()), tags: vec!["rust".into()] } },
+//!     User { id: 2, name: "Bob".into(), profile: Profile { bio: None, tags: vec![] } },
+//! ];
+//! let selector: UserSerializeFieldSelector = "id,profile.*".parse().unwrap();
+//!
+//! let mut out = Vec::new();
+//! serialize_fields::csv::write_records(&mut out, &users, &selector).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(out).unwrap(),
+//!     "id,profile.bio,profile.tags\n1,\"Hi, all\",\"[\"\"rust\"\"]\"\n2,,[]\n"
+//! );
+//! ```
+
+use crate::{FieldSelector, PathListFormat, SequencePaths, SerializeFields, SerializeFieldsTrait};
+use serde::ser::{self, Serialize};
+use serde_json::value::Serializer as Json;
+use std::collections::HashMap;
+use std::io;
+
+/// Write a header row and one record per item, with the fields enabled in
+/// `selector`.
+///
+/// Fields that are not serialized for an item, such as a `None` nested
+/// struct, leave their cells empty.
+pub fn write_records<'a, W, T, I>(
+    mut writer: W,
+    items: I,
+    selector: &T::FieldSelector,
+) -> io::Result<()>
+where
+    W: io::Write,
+    T: SerializeFieldsTrait + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let headers = selector.to_path_list_with(&PathListFormat {
+        sequences: SequencePaths::Stop,
+        ..PathListFormat::DOTTED
+    });
+    write_row(&mut writer, &headers)?;
+
+    let columns: HashMap<&str, usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| (header.as_str(), index))
+        .collect();
+    let mut row = Row {
+        columns: &columns,
+        cells: vec![String::new(); headers.len()],
+    };
+    for item in items {
+        row.cells.iter_mut().for_each(String::clear);
+        SerializeFields(item, selector).serialize(Cell {
+            row: &mut row,
+            path: String::new(),
+        })?;
+        write_row(&mut writer, &row.cells)?;
+    }
+    Ok(())
+}
+
+fn write_row<W: io::Write>(writer: &mut W, cells: &[String]) -> io::Result<()> {
+    for (index, cell) in cells.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(cell.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\n")
+}
+
+struct Row<'h> {
+    columns: &'h HashMap<&'h str, usize>,
+    cells: Vec<String>,
+}
+
+impl Row<'_> {
+    fn set(&mut self, path: &str, value: String) {
+        // Values outside the header, which the selector did not list, are dropped
+        if let Some(&index) = self.columns.get(path) {
+            self.cells[index] = value;
+        }
+    }
+
+    fn set_json(&mut self, path: &str, value: serde_json::Value) {
+        self.set(path, value.to_string());
+    }
+}
+
+/// Serializer writing the value at `path` into its cell, or flattening it
+/// into the cells below `path` for structs.
+struct Cell<'r, 'h> {
+    row: &'r mut Row<'h>,
+    path: String,
+}
+
+impl<'r, 'h> Cell<'r, 'h> {
+    fn set(self, value: impl ToString) -> Result<(), serde_json::Error> {
+        self.row.set(&self.path, value.to_string());
+        Ok(())
+    }
+
+    fn compound<C>(self, compound: C) -> JsonCell<'r, 'h, C> {
+        JsonCell {
+            row: self.row,
+            path: self.path,
+            compound,
+        }
+    }
+}
+
+impl<'r, 'h> ser::Serializer for Cell<'r, 'h> {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = JsonCell<'r, 'h, <Json as ser::Serializer>::SerializeSeq>;
+    type SerializeTuple = JsonCell<'r, 'h, <Json as ser::Serializer>::SerializeTuple>;
+    type SerializeTupleStruct = JsonCell<'r, 'h, <Json as ser::Serializer>::SerializeTupleStruct>;
+    type SerializeTupleVariant = JsonCell<'r, 'h, <Json as ser::Serializer>::SerializeTupleVariant>;
+    type SerializeMap = JsonCell<'r, 'h, <Json as ser::Serializer>::SerializeMap>;
+    type SerializeStruct = StructCells<'r, 'h>;
+    type SerializeStructVariant = JsonCell<'r, 'h, <Json as ser::Serializer>::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Self::Error> {
+        self.set(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Self::Error> {
+        let value = ser::Serializer::serialize_bytes(Json, v)?;
+        self.row.set_json(&self.path, value);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Self::Error> {
+        self.set(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let value = ser::Serializer::serialize_newtype_variant(Json, name, variant_index, variant, value)?;
+        self.row.set_json(&self.path, value);
+        Ok(())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let compound = ser::Serializer::serialize_seq(Json, len)?;
+        Ok(self.compound(compound))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        let compound = ser::Serializer::serialize_tuple(Json, len)?;
+        Ok(self.compound(compound))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        let compound = ser::Serializer::serialize_tuple_struct(Json, name, len)?;
+        Ok(self.compound(compound))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let compound = ser::Serializer::serialize_tuple_variant(Json, name, variant_index, variant, len)?;
+        Ok(self.compound(compound))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let compound = ser::Serializer::serialize_map(Json, len)?;
+        Ok(self.compound(compound))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        // A struct listed as a column itself, such as an `always` nested field
+        // without a selector, is kept whole
+        if self.row.columns.contains_key(self.path.as_str()) {
+            let compound = ser::Serializer::serialize_struct(Json, name, len)?;
+            return Ok(StructCells::Json(self.compound(compound)));
+        }
+        Ok(StructCells::Flatten {
+            row: self.row,
+            prefix: self.path,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let compound =
+            ser::Serializer::serialize_struct_variant(Json, name, variant_index, variant, len)?;
+        Ok(self.compound(compound))
+    }
+}
+
+/// A compound value written as compact JSON into a single cell.
+struct JsonCell<'r, 'h, C> {
+    row: &'r mut Row<'h>,
+    path: String,
+    compound: C,
+}
+
+impl<C> ser::SerializeSeq for JsonCell<'_, '_, C>
+where
+    C: ser::SerializeSeq<Ok = serde_json::Value, Error = serde_json::Error>,
+{
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.compound.serialize_element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let JsonCell { row, path, compound } = self;
+        row.set_json(&path, ser::SerializeSeq::end(compound)?);
+        Ok(())
+    }
+}
+
+impl<C> ser::SerializeTuple for JsonCell<'_, '_, C>
+where
+    C: ser::SerializeTuple<Ok = serde_json::Value, Error = serde_json::Error>,
+{
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.compound.serialize_element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let JsonCell { row, path, compound } = self;
+        row.set_json(&path, ser::SerializeTuple::end(compound)?);
+        Ok(())
+    }
+}
+
+impl<C> ser::SerializeTupleStruct for JsonCell<'_, '_, C>
+where
+    C: ser::SerializeTupleStruct<Ok = serde_json::Value, Error = serde_json::Error>,
+{
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.compound.serialize_field(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let JsonCell { row, path, compound } = self;
+        row.set_json(&path, ser::SerializeTupleStruct::end(compound)?);
+        Ok(())
+    }
+}
+
+impl<C> ser::SerializeTupleVariant for JsonCell<'_, '_, C>
+where
+    C: ser::SerializeTupleVariant<Ok = serde_json::Value, Error = serde_json::Error>,
+{
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.compound.serialize_field(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let JsonCell { row, path, compound } = self;
+        row.set_json(&path, ser::SerializeTupleVariant::end(compound)?);
+        Ok(())
+    }
+}
+
+impl<C> ser::SerializeMap for JsonCell<'_, '_, C>
+where
+    C: ser::SerializeMap<Ok = serde_json::Value, Error = serde_json::Error>,
+{
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.compound.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.compound.serialize_value(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let JsonCell { row, path, compound } = self;
+        row.set_json(&path, ser::SerializeMap::end(compound)?);
+        Ok(())
+    }
+}
+
+impl<C> ser::SerializeStruct for JsonCell<'_, '_, C>
+where
+    C: ser::SerializeStruct<Ok = serde_json::Value, Error = serde_json::Error>,
+{
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.compound.serialize_field(key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let JsonCell { row, path, compound } = self;
+        row.set_json(&path, ser::SerializeStruct::end(compound)?);
+        Ok(())
+    }
+}
+
+impl<C> ser::SerializeStructVariant for JsonCell<'_, '_, C>
+where
+    C: ser::SerializeStructVariant<Ok = serde_json::Value, Error = serde_json::Error>,
+{
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.compound.serialize_field(key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let JsonCell { row, path, compound } = self;
+        row.set_json(&path, ser::SerializeStructVariant::end(compound)?);
+        Ok(())
+    }
+}
+
+/// The fields of a struct, each written to the cells below `prefix`.
+enum StructCells<'r, 'h> {
+    Flatten { row: &'r mut Row<'h>, prefix: String },
+    Json(JsonCell<'r, 'h, <Json as ser::Serializer>::SerializeStruct>),
+}
+
+impl ser::SerializeStruct for StructCells<'_, '_> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        match self {
+            StructCells::Flatten { row, prefix } => {
+                let path = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                value.serialize(Cell { row, path })
+            }
+            StructCells::Json(cell) => ser::SerializeStruct::serialize_field(cell, key, value),
+        }
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        match self {
+            StructCells::Flatten { .. } => Ok(()),
+            StructCells::Json(cell) => ser::SerializeStruct::end(cell),
+        }
+    }
+}
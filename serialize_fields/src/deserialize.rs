@@ -0,0 +1,230 @@
+//! Deserialization populating only the fields enabled in a selector.
+
+use crate::path_list::PathSegment;
+use crate::{FieldSelector, SerializeFieldsTrait};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// Deserializes a `T` from input in which only the fields enabled in the
+/// selector are read; every other field keeps its value in `T::default()`.
+/// Used as a write mask, e.g. for the body of a PATCH request whose clients
+/// may only write some fields.
+///
+/// Input keys are the serialized names. Fields not enabled in the selector,
+/// including `always` fields, are ignored, or rejected with
+/// [`deny_disallowed`](Self::deny_disallowed).
+///
+/// The input goes through a `serde_json::Value`, and `T::default()` is
+/// serialized to fill in the ignored fields, so round-tripping `T` through
+/// JSON must be lossless. Nested structs held directly are filled in field by
+/// field; in sequences and in `Option`s that are `None` by default, the input
+/// elements must have every field `T` requires.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(SerializeFields, Serialize, Deserialize, Default, Debug)]
+/// struct User {
+///     id: u32,
+///     name: String,
+///     role: String,
+/// }
+///
+/// use serialize_fields::DeserializeFields;
+///
+/// let writable: UserSerializeFieldSelector = "name".parse().unwrap();
+/// let body = r#"{"id": 7, "name": "Alicia"}"#;
+///
+/// let user: User = DeserializeFields::new(&writable).from_str(body).unwrap();
+/// assert_eq!((user.id, user.name.as_str()), (0, "Alicia"));
+///
+/// let error = DeserializeFields::<User, _>::new(&writable).deny_disallowed().from_str(body);
+/// assert_eq!(error.unwrap_err().to_string(), "field `id` is not allowed");
+/// ```
+pub struct DeserializeFields<'a, T, S> {
+    selector: &'a S,
+    deny_disallowed: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T, S> DeserializeFields<'a, T, S>
+where
+    T: SerializeFieldsTrait<FieldSelector = S> + Default + Serialize + DeserializeOwned,
+    S: FieldSelector,
+{
+    /// Read only the fields enabled in `selector`.
+    pub fn new(selector: &'a S) -> Self {
+        DeserializeFields {
+            selector,
+            deny_disallowed: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Fail on input fields that are not enabled in the selector, instead of
+    /// ignoring them.
+    pub fn deny_disallowed(mut self) -> Self {
+        self.deny_disallowed = true;
+        self
+    }
+
+    /// Deserialize a `T` from a JSON string.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(self, json: &str) -> serde_json::Result<T> {
+        self.deserialize(&mut serde_json::Deserializer::from_str(json))
+    }
+
+    /// Deserialize a `T` from a JSON value.
+    pub fn from_value(self, value: Value) -> serde_json::Result<T> {
+        self.deserialize(value)
+    }
+}
+
+impl<'de, T, S> DeserializeSeed<'de> for DeserializeFields<'_, T, S>
+where
+    T: SerializeFieldsTrait<FieldSelector = S> + Default + Serialize + DeserializeOwned,
+    S: FieldSelector,
+{
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        let input = Value::deserialize(deserializer)?;
+
+        let allowed = Allowed::new(self.selector);
+        let mut merged = serde_json::to_value(T::default()).map_err(de::Error::custom)?;
+        let mut path = Vec::new();
+        allowed
+            .merge(&mut merged, input, self.deny_disallowed, &mut path)
+            .map_err(de::Error::custom)?;
+        T::deserialize(merged).map_err(de::Error::custom)
+    }
+}
+
+/// The tree of serialized names allowed in the input. A leaf allows the
+/// whole value below it.
+#[derive(Default)]
+pub(crate) struct Allowed {
+    pub(crate) children: BTreeMap<&'static str, Allowed>,
+}
+
+impl Allowed {
+    /// The paths enabled in `selector`, without `always` fields.
+    pub(crate) fn new<S: FieldSelector>(selector: &S) -> Self {
+        let mut allowed = Allowed::default();
+        selector.__visit_output_paths(false, &mut Vec::new(), &mut |path| {
+            allowed.insert(path);
+        });
+        allowed
+    }
+
+    fn insert(&mut self, path: &[PathSegment]) {
+        let mut node = self;
+        for segment in path {
+            node = node.children.entry(segment.name).or_default();
+        }
+    }
+
+    /// Overwrite the allowed parts of `target` with those of `input`.
+    fn merge(
+        &self,
+        target: &mut Value,
+        input: Value,
+        deny_disallowed: bool,
+        path: &mut Vec<String>,
+    ) -> Result<(), String> {
+        let Value::Object(input) = input else {
+            // Not a struct: left for `T`'s deserialization to accept or reject
+            *target = input;
+            return Ok(());
+        };
+        if !target.is_object() {
+            // Nothing to fill in, such as an `Option` that is `None` by default
+            *target = Value::Object(self.filter(input, deny_disallowed, path)?);
+            return Ok(());
+        }
+        for (key, value) in input {
+            let Some(child) = self.allowed(&key, deny_disallowed, path)? else {
+                continue;
+            };
+            path.push(key.clone());
+            let map = target.as_object_mut().expect("checked above");
+            match map.get_mut(&key) {
+                Some(field) if !child.children.is_empty() => {
+                    child.merge(field, value, deny_disallowed, path)?;
+                }
+                _ => {
+                    let value = child.filter_value(value, deny_disallowed, path)?;
+                    map.insert(key, value);
+                }
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+
+    /// The allowed parts of `value`, looking into objects and arrays.
+    pub(crate) fn filter_value(
+        &self,
+        value: Value,
+        deny_disallowed: bool,
+        path: &mut Vec<String>,
+    ) -> Result<Value, String> {
+        if self.children.is_empty() {
+            return Ok(value);
+        }
+        match value {
+            Value::Object(map) => Ok(Value::Object(self.filter(map, deny_disallowed, path)?)),
+            Value::Array(elements) => elements
+                .into_iter()
+                .map(|element| self.filter_value(element, deny_disallowed, path))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            value => Ok(value),
+        }
+    }
+
+    fn filter(
+        &self,
+        map: Map<String, Value>,
+        deny_disallowed: bool,
+        path: &mut Vec<String>,
+    ) -> Result<Map<String, Value>, String> {
+        let mut filtered = Map::new();
+        for (key, value) in map {
+            let Some(child) = self.allowed(&key, deny_disallowed, path)? else {
+                continue;
+            };
+            path.push(key.clone());
+            let value = child.filter_value(value, deny_disallowed, path)?;
+            path.pop();
+            filtered.insert(key, value);
+        }
+        Ok(filtered)
+    }
+
+    /// The node for `key`, or `None` if it is ignored.
+    pub(crate) fn allowed(
+        &self,
+        key: &str,
+        deny_disallowed: bool,
+        path: &[String],
+    ) -> Result<Option<&Allowed>, String> {
+        match self.children.get(key) {
+            Some(child) => Ok(Some(child)),
+            None if deny_disallowed => {
+                let mut field = path.join(".");
+                if !field.is_empty() {
+                    field.push('.');
+                }
+                field.push_str(key);
+                Err(format!("field `{}` is not allowed", field))
+            }
+            None => Ok(None),
+        }
+    }
+}
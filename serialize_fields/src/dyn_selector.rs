@@ -0,0 +1,121 @@
+//! Object-safe access to field selectors of any type.
+
+use crate::FieldSelector;
+use std::any::Any;
+
+/// Object-safe view of a [`FieldSelector`].
+///
+/// [`FieldSelector`] has constructors and constants, so it can't be used as a
+/// trait object. This trait exposes the runtime, path-based part of it instead,
+/// which lets generic middleware walk and edit nested selectors returned by
+/// [`FieldSelector::at_path`] without naming their concrete types. Use
+/// [`as_any`](Self::as_any) to get back to the typed selector.
+///
+/// It is implemented for every [`FieldSelector`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::{DynFieldSelector, FieldSelector, SerializeFields};
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, profile: Profile }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Profile { bio: String, website: String }
+/// let mut selector = UserSerializeFieldSelector::new();
+/// selector.enable_dot_hierarchy("profile.bio");
+///
+/// let profile = selector.at_path_mut("profile").unwrap();
+/// profile.enable_dot_hierarchy("website");
+/// assert_eq!(profile.enabled_paths(), vec!["bio", "website"]);
+///
+/// let typed = profile.as_any().downcast_ref::<ProfileSerializeFieldSelector>().unwrap();
+/// assert!(typed.website.is_some());
+/// ```
+pub trait DynFieldSelector: Any {
+    /// See [`FieldSelector::FIELD_PATHS`].
+    fn field_paths(&self) -> &'static [&'static str];
+
+    /// See [`FieldSelector::enable_dot_hierarchy`].
+    fn enable_dot_hierarchy(&mut self, field: &str);
+
+    /// See [`FieldSelector::disable_dot_hierarchy`].
+    fn disable_dot_hierarchy(&mut self, field: &str);
+
+    /// See [`FieldSelector::enable_all`].
+    fn enable_all(&mut self);
+
+    /// See [`FieldSelector::contains_path`].
+    fn contains_path(&self, field: &str) -> bool;
+
+    /// See [`FieldSelector::enabled_paths`].
+    fn enabled_paths(&self) -> Vec<String>;
+
+    /// See [`FieldSelector::is_empty`].
+    fn is_empty(&self) -> bool;
+
+    /// See [`FieldSelector::active_nested`].
+    fn active_nested(&self) -> Vec<&'static str>;
+
+    /// See [`FieldSelector::at_path`].
+    fn at_path(&self, field: &str) -> Option<&dyn DynFieldSelector>;
+
+    /// See [`FieldSelector::at_path_mut`].
+    fn at_path_mut(&mut self, field: &str) -> Option<&mut dyn DynFieldSelector>;
+
+    /// Downcast to the concrete selector with [`Any::downcast_ref`].
+    fn as_any(&self) -> &dyn Any;
+
+    /// Downcast to the concrete selector with [`Any::downcast_mut`].
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: FieldSelector + 'static> DynFieldSelector for T {
+    fn field_paths(&self) -> &'static [&'static str] {
+        T::FIELD_PATHS
+    }
+
+    fn enable_dot_hierarchy(&mut self, field: &str) {
+        FieldSelector::enable_dot_hierarchy(self, field)
+    }
+
+    fn disable_dot_hierarchy(&mut self, field: &str) {
+        FieldSelector::disable_dot_hierarchy(self, field)
+    }
+
+    fn enable_all(&mut self) {
+        FieldSelector::enable_all(self)
+    }
+
+    fn contains_path(&self, field: &str) -> bool {
+        FieldSelector::contains_path(self, field)
+    }
+
+    fn enabled_paths(&self) -> Vec<String> {
+        FieldSelector::enabled_paths(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        FieldSelector::is_empty(self)
+    }
+
+    fn active_nested(&self) -> Vec<&'static str> {
+        FieldSelector::active_nested(self)
+    }
+
+    fn at_path(&self, field: &str) -> Option<&dyn DynFieldSelector> {
+        FieldSelector::at_path(self, field)
+    }
+
+    fn at_path_mut(&mut self, field: &str) -> Option<&mut dyn DynFieldSelector> {
+        FieldSelector::at_path_mut(self, field)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
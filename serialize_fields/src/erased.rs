@@ -0,0 +1,662 @@
+//! Object-safe serialization, for lists mixing different structs each with
+//! its own selector.
+
+use std::fmt;
+use std::mem;
+
+use serde::ser::{self, Serialize};
+
+/// An object-safe form of [`Serialize`], implemented for [`SerializeFields`]
+/// and the other wrappers, so that values of different types can be
+/// serialized through `Box<dyn ErasedSerializeFields>`.
+///
+/// A trait object forwards every call to the actual serializer, so keys keep
+/// their order and any serde format can be used, not only JSON.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Team { id: u32, members: Vec<User> }
+/// use serialize_fields::ErasedSerializeFields;
+///
+/// let user = User { id: 1, name: "Alice".into() };
+/// let team = Team { id: 7, members: vec![] };
+/// let user_selector: UserSerializeFieldSelector = "name".parse().unwrap();
+/// let team_selector: TeamSerializeFieldSelector = "id".parse().unwrap();
+///
+/// let parts: Vec<Box<dyn ErasedSerializeFields>> = vec![
+///     Box::new(SerializeFields(&user, &user_selector)),
+///     Box::new(SerializeFields(&team, &team_selector)),
+/// ];
+/// assert_eq!(serde_json::to_string(&parts).unwrap(), r#"[{"name":"Alice"},{"id":7}]"#);
+/// ```
+///
+/// [`SerializeFields`]: crate::SerializeFields
+pub trait ErasedSerializeFields {
+    /// The value as JSON.
+    fn to_json_value(&self) -> serde_json::Result<serde_json::Value>;
+
+    /// Serialize the value into a type-erased serializer.
+    #[doc(hidden)]
+    fn __erased_serialize(&self, serializer: &mut dyn ErasedSerializer) -> Result<(), ErasedError>;
+}
+
+impl<T: Serialize + ?Sized> ErasedSerializeFields for T {
+    fn to_json_value(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    fn __erased_serialize(&self, serializer: &mut dyn ErasedSerializer) -> Result<(), ErasedError> {
+        self.serialize(serializer)
+    }
+}
+
+impl Serialize for dyn ErasedSerializeFields + '_ {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut erased = Erase {
+            state: State::Ready(serializer),
+        };
+        let result = self.__erased_serialize(&mut erased);
+        match (erased.state, result) {
+            (State::Failed(error), _) => Err(error),
+            (_, Err(error)) => Err(ser::Error::custom(error)),
+            (State::Done(ok), Ok(())) => Ok(ok),
+            (_, Ok(())) => Err(ser::Error::custom("value was not fully serialized")),
+        }
+    }
+}
+
+impl Serialize for dyn ErasedSerializeFields + Send + Sync + '_ {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self as &dyn ErasedSerializeFields).serialize(serializer)
+    }
+}
+
+/// Error of a type-erased serializer.
+///
+/// Errors of the actual serializer are kept aside and returned as they are;
+/// this only carries the message of errors raised by `Serialize` impls.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ErasedError {
+    message: String,
+}
+
+impl fmt::Display for ErasedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ErasedError {}
+
+impl ser::Error for ErasedError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        ErasedError {
+            message: message.to_string(),
+        }
+    }
+}
+
+/// An object-safe form of [`ser::Serializer`].
+///
+/// Compound values are written with the `erased_element`, `erased_key`,
+/// `erased_value` and `erased_field` calls following the call that opened
+/// them, up to `erased_end`.
+#[doc(hidden)]
+pub trait ErasedSerializer {
+    fn erased_bool(&mut self, v: bool) -> Result<(), ErasedError>;
+    fn erased_i8(&mut self, v: i8) -> Result<(), ErasedError>;
+    fn erased_i16(&mut self, v: i16) -> Result<(), ErasedError>;
+    fn erased_i32(&mut self, v: i32) -> Result<(), ErasedError>;
+    fn erased_i64(&mut self, v: i64) -> Result<(), ErasedError>;
+    fn erased_i128(&mut self, v: i128) -> Result<(), ErasedError>;
+    fn erased_u8(&mut self, v: u8) -> Result<(), ErasedError>;
+    fn erased_u16(&mut self, v: u16) -> Result<(), ErasedError>;
+    fn erased_u32(&mut self, v: u32) -> Result<(), ErasedError>;
+    fn erased_u64(&mut self, v: u64) -> Result<(), ErasedError>;
+    fn erased_u128(&mut self, v: u128) -> Result<(), ErasedError>;
+    fn erased_f32(&mut self, v: f32) -> Result<(), ErasedError>;
+    fn erased_f64(&mut self, v: f64) -> Result<(), ErasedError>;
+    fn erased_char(&mut self, v: char) -> Result<(), ErasedError>;
+    fn erased_str(&mut self, v: &str) -> Result<(), ErasedError>;
+    fn erased_bytes(&mut self, v: &[u8]) -> Result<(), ErasedError>;
+    fn erased_none(&mut self) -> Result<(), ErasedError>;
+    fn erased_some(&mut self, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError>;
+    fn erased_unit(&mut self) -> Result<(), ErasedError>;
+    fn erased_unit_struct(&mut self, name: &'static str) -> Result<(), ErasedError>;
+    fn erased_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), ErasedError>;
+    fn erased_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn ErasedSerializeFields,
+    ) -> Result<(), ErasedError>;
+    fn erased_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn ErasedSerializeFields,
+    ) -> Result<(), ErasedError>;
+    fn erased_seq(&mut self, len: Option<usize>) -> Result<(), ErasedError>;
+    fn erased_tuple(&mut self, len: usize) -> Result<(), ErasedError>;
+    fn erased_tuple_struct(&mut self, name: &'static str, len: usize) -> Result<(), ErasedError>;
+    fn erased_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<(), ErasedError>;
+    fn erased_map(&mut self, len: Option<usize>) -> Result<(), ErasedError>;
+    fn erased_struct(&mut self, name: &'static str, len: usize) -> Result<(), ErasedError>;
+    fn erased_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<(), ErasedError>;
+    fn erased_element(&mut self, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError>;
+    fn erased_key(&mut self, key: &dyn ErasedSerializeFields) -> Result<(), ErasedError>;
+    fn erased_value(&mut self, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError>;
+    fn erased_field(&mut self, key: &'static str, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError>;
+    fn erased_skip_field(&mut self, key: &'static str) -> Result<(), ErasedError>;
+    fn erased_end(&mut self) -> Result<(), ErasedError>;
+    fn erased_is_human_readable(&self) -> bool;
+}
+
+/// A serializer behind [`ErasedSerializer`], with the compound value being
+/// written, if any.
+struct Erase<S: ser::Serializer> {
+    state: State<S>,
+}
+
+enum State<S: ser::Serializer> {
+    Ready(S),
+    Seq(S::SerializeSeq),
+    Tuple(S::SerializeTuple),
+    TupleStruct(S::SerializeTupleStruct),
+    TupleVariant(S::SerializeTupleVariant),
+    Map(S::SerializeMap),
+    Struct(S::SerializeStruct),
+    StructVariant(S::SerializeStructVariant),
+    Done(S::Ok),
+    Failed(S::Error),
+    Used,
+}
+
+fn misuse() -> ErasedError {
+    ser::Error::custom("serializer called out of order")
+}
+
+impl<S: ser::Serializer> Erase<S> {
+    /// Take the serializer to write a value, which may only be done once.
+    fn take(&mut self) -> Result<S, ErasedError> {
+        match mem::replace(&mut self.state, State::Used) {
+            State::Ready(serializer) => Ok(serializer),
+            _ => Err(misuse()),
+        }
+    }
+
+    /// Record the result of the actual serializer; its error is kept aside.
+    fn record<T>(&mut self, result: Result<T, S::Error>, state: impl FnOnce(T) -> State<S>) -> Result<(), ErasedError> {
+        match result {
+            Ok(value) => {
+                self.state = state(value);
+                Ok(())
+            }
+            Err(error) => {
+                self.state = State::Failed(error);
+                Err(ser::Error::custom("serialization failed"))
+            }
+        }
+    }
+
+    /// Record the result of writing part of the current compound value.
+    fn record_part(&mut self, result: Result<(), S::Error>) -> Result<(), ErasedError> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                self.state = State::Failed(error);
+                Err(ser::Error::custom("serialization failed"))
+            }
+        }
+    }
+
+    fn write(&mut self, write: impl FnOnce(S) -> Result<S::Ok, S::Error>) -> Result<(), ErasedError> {
+        let result = write(self.take()?);
+        self.record(result, State::Done)
+    }
+}
+
+impl<S: ser::Serializer> ErasedSerializer for Erase<S> {
+    fn erased_bool(&mut self, v: bool) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_bool(v))
+    }
+    fn erased_i8(&mut self, v: i8) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_i8(v))
+    }
+    fn erased_i16(&mut self, v: i16) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_i16(v))
+    }
+    fn erased_i32(&mut self, v: i32) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_i32(v))
+    }
+    fn erased_i64(&mut self, v: i64) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_i64(v))
+    }
+    fn erased_i128(&mut self, v: i128) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_i128(v))
+    }
+    fn erased_u8(&mut self, v: u8) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_u8(v))
+    }
+    fn erased_u16(&mut self, v: u16) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_u16(v))
+    }
+    fn erased_u32(&mut self, v: u32) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_u32(v))
+    }
+    fn erased_u64(&mut self, v: u64) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_u64(v))
+    }
+    fn erased_u128(&mut self, v: u128) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_u128(v))
+    }
+    fn erased_f32(&mut self, v: f32) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_f32(v))
+    }
+    fn erased_f64(&mut self, v: f64) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_f64(v))
+    }
+    fn erased_char(&mut self, v: char) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_char(v))
+    }
+    fn erased_str(&mut self, v: &str) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_str(v))
+    }
+    fn erased_bytes(&mut self, v: &[u8]) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_bytes(v))
+    }
+    fn erased_none(&mut self) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_none())
+    }
+    fn erased_some(&mut self, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_some(value))
+    }
+    fn erased_unit(&mut self) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_unit())
+    }
+    fn erased_unit_struct(&mut self, name: &'static str) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_unit_struct(name))
+    }
+    fn erased_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_unit_variant(name, variant_index, variant))
+    }
+    fn erased_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn ErasedSerializeFields,
+    ) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_newtype_struct(name, value))
+    }
+    fn erased_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn ErasedSerializeFields,
+    ) -> Result<(), ErasedError> {
+        self.write(|serializer| serializer.serialize_newtype_variant(name, variant_index, variant, value))
+    }
+    fn erased_seq(&mut self, len: Option<usize>) -> Result<(), ErasedError> {
+        let result = self.take()?.serialize_seq(len);
+        self.record(result, State::Seq)
+    }
+    fn erased_tuple(&mut self, len: usize) -> Result<(), ErasedError> {
+        let result = self.take()?.serialize_tuple(len);
+        self.record(result, State::Tuple)
+    }
+    fn erased_tuple_struct(&mut self, name: &'static str, len: usize) -> Result<(), ErasedError> {
+        let result = self.take()?.serialize_tuple_struct(name, len);
+        self.record(result, State::TupleStruct)
+    }
+    fn erased_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<(), ErasedError> {
+        let result = self.take()?.serialize_tuple_variant(name, variant_index, variant, len);
+        self.record(result, State::TupleVariant)
+    }
+    fn erased_map(&mut self, len: Option<usize>) -> Result<(), ErasedError> {
+        let result = self.take()?.serialize_map(len);
+        self.record(result, State::Map)
+    }
+    fn erased_struct(&mut self, name: &'static str, len: usize) -> Result<(), ErasedError> {
+        let result = self.take()?.serialize_struct(name, len);
+        self.record(result, State::Struct)
+    }
+    fn erased_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<(), ErasedError> {
+        let result = self.take()?.serialize_struct_variant(name, variant_index, variant, len);
+        self.record(result, State::StructVariant)
+    }
+    fn erased_element(&mut self, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError> {
+        use ser::{SerializeSeq, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+
+        let result = match &mut self.state {
+            State::Seq(seq) => seq.serialize_element(value),
+            State::Tuple(tuple) => tuple.serialize_element(value),
+            State::TupleStruct(tuple) => tuple.serialize_field(value),
+            State::TupleVariant(tuple) => tuple.serialize_field(value),
+            _ => return Err(misuse()),
+        };
+        self.record_part(result)
+    }
+    fn erased_key(&mut self, key: &dyn ErasedSerializeFields) -> Result<(), ErasedError> {
+        let State::Map(map) = &mut self.state else {
+            return Err(misuse());
+        };
+        let result = ser::SerializeMap::serialize_key(map, key);
+        self.record_part(result)
+    }
+    fn erased_value(&mut self, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError> {
+        let State::Map(map) = &mut self.state else {
+            return Err(misuse());
+        };
+        let result = ser::SerializeMap::serialize_value(map, value);
+        self.record_part(result)
+    }
+    fn erased_field(&mut self, key: &'static str, value: &dyn ErasedSerializeFields) -> Result<(), ErasedError> {
+        let result = match &mut self.state {
+            State::Struct(fields) => ser::SerializeStruct::serialize_field(fields, key, value),
+            State::StructVariant(fields) => ser::SerializeStructVariant::serialize_field(fields, key, value),
+            _ => return Err(misuse()),
+        };
+        self.record_part(result)
+    }
+    fn erased_skip_field(&mut self, key: &'static str) -> Result<(), ErasedError> {
+        let result = match &mut self.state {
+            State::Struct(fields) => ser::SerializeStruct::skip_field(fields, key),
+            State::StructVariant(fields) => ser::SerializeStructVariant::skip_field(fields, key),
+            _ => return Err(misuse()),
+        };
+        self.record_part(result)
+    }
+    fn erased_end(&mut self) -> Result<(), ErasedError> {
+        let result = match mem::replace(&mut self.state, State::Used) {
+            State::Seq(seq) => ser::SerializeSeq::end(seq),
+            State::Tuple(tuple) => ser::SerializeTuple::end(tuple),
+            State::TupleStruct(tuple) => ser::SerializeTupleStruct::end(tuple),
+            State::TupleVariant(tuple) => ser::SerializeTupleVariant::end(tuple),
+            State::Map(map) => ser::SerializeMap::end(map),
+            State::Struct(fields) => ser::SerializeStruct::end(fields),
+            State::StructVariant(fields) => ser::SerializeStructVariant::end(fields),
+            _ => return Err(misuse()),
+        };
+        self.record(result, State::Done)
+    }
+    fn erased_is_human_readable(&self) -> bool {
+        match &self.state {
+            State::Ready(serializer) => serializer.is_human_readable(),
+            _ => true,
+        }
+    }
+}
+
+impl ser::Serializer for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), ErasedError> {
+        self.erased_bool(v)
+    }
+    fn serialize_i8(self, v: i8) -> Result<(), ErasedError> {
+        self.erased_i8(v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<(), ErasedError> {
+        self.erased_i16(v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<(), ErasedError> {
+        self.erased_i32(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<(), ErasedError> {
+        self.erased_i64(v)
+    }
+    fn serialize_i128(self, v: i128) -> Result<(), ErasedError> {
+        self.erased_i128(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<(), ErasedError> {
+        self.erased_u8(v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<(), ErasedError> {
+        self.erased_u16(v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<(), ErasedError> {
+        self.erased_u32(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<(), ErasedError> {
+        self.erased_u64(v)
+    }
+    fn serialize_u128(self, v: u128) -> Result<(), ErasedError> {
+        self.erased_u128(v)
+    }
+    fn serialize_f32(self, v: f32) -> Result<(), ErasedError> {
+        self.erased_f32(v)
+    }
+    fn serialize_f64(self, v: f64) -> Result<(), ErasedError> {
+        self.erased_f64(v)
+    }
+    fn serialize_char(self, v: char) -> Result<(), ErasedError> {
+        self.erased_char(v)
+    }
+    fn serialize_str(self, v: &str) -> Result<(), ErasedError> {
+        self.erased_str(v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), ErasedError> {
+        self.erased_bytes(v)
+    }
+    fn serialize_none(self) -> Result<(), ErasedError> {
+        self.erased_none()
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), ErasedError> {
+        self.erased_some(&value)
+    }
+    fn serialize_unit(self) -> Result<(), ErasedError> {
+        self.erased_unit()
+    }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), ErasedError> {
+        self.erased_unit_struct(name)
+    }
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), ErasedError> {
+        self.erased_unit_variant(name, variant_index, variant)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), ErasedError> {
+        self.erased_newtype_struct(name, &value)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), ErasedError> {
+        self.erased_newtype_variant(name, variant_index, variant, &value)
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, ErasedError> {
+        self.erased_seq(len)?;
+        Ok(self)
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self, ErasedError> {
+        self.erased_tuple(len)?;
+        Ok(self)
+    }
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self, ErasedError> {
+        self.erased_tuple_struct(name, len)?;
+        Ok(self)
+    }
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, ErasedError> {
+        self.erased_tuple_variant(name, variant_index, variant, len)?;
+        Ok(self)
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, ErasedError> {
+        self.erased_map(len)?;
+        Ok(self)
+    }
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self, ErasedError> {
+        self.erased_struct(name, len)?;
+        Ok(self)
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, ErasedError> {
+        self.erased_struct_variant(name, variant_index, variant, len)?;
+        Ok(self)
+    }
+    fn is_human_readable(&self) -> bool {
+        self.erased_is_human_readable()
+    }
+}
+
+impl ser::SerializeSeq for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ErasedError> {
+        self.erased_element(&value)
+    }
+    fn end(self) -> Result<(), ErasedError> {
+        self.erased_end()
+    }
+}
+
+impl ser::SerializeTuple for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ErasedError> {
+        self.erased_element(&value)
+    }
+    fn end(self) -> Result<(), ErasedError> {
+        self.erased_end()
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ErasedError> {
+        self.erased_element(&value)
+    }
+    fn end(self) -> Result<(), ErasedError> {
+        self.erased_end()
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ErasedError> {
+        self.erased_element(&value)
+    }
+    fn end(self) -> Result<(), ErasedError> {
+        self.erased_end()
+    }
+}
+
+impl ser::SerializeMap for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ErasedError> {
+        self.erased_key(&key)
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ErasedError> {
+        self.erased_value(&value)
+    }
+    fn end(self) -> Result<(), ErasedError> {
+        self.erased_end()
+    }
+}
+
+impl ser::SerializeStruct for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), ErasedError> {
+        self.erased_field(key, &value)
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), ErasedError> {
+        self.erased_skip_field(key)
+    }
+    fn end(self) -> Result<(), ErasedError> {
+        self.erased_end()
+    }
+}
+
+impl ser::SerializeStructVariant for &mut dyn ErasedSerializer {
+    type Ok = ();
+    type Error = ErasedError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), ErasedError> {
+        self.erased_field(key, &value)
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), ErasedError> {
+        self.erased_skip_field(key)
+    }
+    fn end(self) -> Result<(), ErasedError> {
+        self.erased_end()
+    }
+}
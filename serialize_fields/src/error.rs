@@ -0,0 +1,166 @@
+//! Errors returned when parsing, strictly enabling, validating or decoding
+//! field selections.
+
+use std::fmt;
+
+/// Error returned when a field list cannot be parsed into a selector.
+///
+/// Every invalid entry of the list is reported, not only the first one, so an
+/// API can tell its client about all typos at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorParseError {
+    /// The invalid entries, in the order they appear in the input.
+    pub errors: Vec<InvalidPath>,
+}
+
+/// One invalid entry of a field list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPath {
+    /// The path as written, without any `-`/`!` exclusion prefix.
+    pub path: String,
+    /// Byte offset of the entry in the parsed input, or its index when the
+    /// input is a list of paths.
+    pub position: usize,
+    /// Why the path was rejected.
+    pub reason: InvalidPathReason,
+}
+
+/// Why a path of a field list was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidPathReason {
+    /// The segment at index `at_segment` does not name a selectable field.
+    Unknown {
+        /// Zero-based index of the first unknown segment.
+        at_segment: usize,
+        /// The closest valid path, if one is similar enough.
+        suggestion: Option<String>,
+    },
+    /// The path has an empty segment, as in `profile..bio` or `profile.`.
+    Malformed,
+    /// The query language around the path is invalid, e.g. an unclosed
+    /// parenthesis. `path` holds the rest of the input from the error on.
+    Syntax {
+        /// What the parser expected at `position`.
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for SelectorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid field selection: ")?;
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for InvalidPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            InvalidPathReason::Unknown {
+                at_segment,
+                suggestion,
+            } => {
+                let segment = self.path.split('.').nth(*at_segment).unwrap_or_default();
+                write!(
+                    f,
+                    "unknown field `{}` in `{}` at position {}",
+                    segment, self.path, self.position
+                )?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean `{}`?", suggestion)?;
+                }
+                Ok(())
+            }
+            InvalidPathReason::Malformed => {
+                write!(f, "malformed path `{}` at position {}", self.path, self.position)
+            }
+            InvalidPathReason::Syntax { expected } => {
+                write!(f, "expected {} at position {}", expected, self.position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SelectorParseError {}
+
+/// Error returned by `try_enable` when a path does not name a selectable field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// The full path, in dot notation.
+    pub path: String,
+    /// Zero-based index of the first unknown segment.
+    ///
+    /// For `id.invalid_nested` on a plain `id` field this is `1`.
+    pub at_segment: usize,
+    /// The closest valid path, if one is similar enough.
+    pub suggestion: Option<String>,
+}
+
+impl UnknownField {
+    /// The segment that was not recognised.
+    pub fn segment(&self) -> &str {
+        self.path.split('.').nth(self.at_segment).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown field `{}` in `{}`", self.segment(), self.path)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean `{}`?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownField {}
+
+/// Error returned by `from_bytes` when the input is not a valid encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorDecodeError {
+    /// The input ended before every field was read.
+    UnexpectedEnd,
+    /// The input has bits or bytes left after the last field.
+    TrailingData,
+}
+
+impl fmt::Display for SelectorDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectorDecodeError::UnexpectedEnd => f.write_str("encoded selector is truncated"),
+            SelectorDecodeError::TrailingData => {
+                f.write_str("encoded selector has trailing data")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SelectorDecodeError {}
+
+/// Error returned by `validate_required` when fields marked
+/// `#[serialize_fields(required)]` would not be serialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingRequiredFields {
+    /// Dot paths of the missing fields, in declaration order.
+    pub paths: Vec<String>,
+}
+
+impl fmt::Display for MissingRequiredFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("required fields are not selected: ")?;
+        for (i, path) in self.paths.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "`{}`", path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingRequiredFields {}
@@ -0,0 +1,345 @@
+//! Serialization of the selected fields as a single map with dot-joined keys.
+
+use crate::{SerializeFields, SerializeFieldsTrait};
+use serde::ser::{self, Impossible, Serialize, SerializeMap};
+
+/// Like [`SerializeFields`], but writes the selected fields as one flat map
+/// keyed by dot-joined serialized names instead of nested objects.
+///
+/// Nested structs, including those inside an `Option`, are flattened into
+/// their own keys; any other value, such as a `Vec` of nested structs, is
+/// written as is under its path.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, profile: Profile }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Profile { bio: String, tags: Vec<String> }
+/// use serialize_fields::SerializeFieldsFlat;
+///
+/// let user = User { id: 1, profile: Profile { bio: "Hi".into(), tags: vec!["rust".into()] } };
+/// let selector: UserSerializeFieldSelector = "id,profile.*".parse().unwrap();
+///
+/// assert_eq!(
+///     serde_json::to_string(&SerializeFieldsFlat(&user, &selector)).unwrap(),
+///     r#"{"id":1,"profile.bio":"Hi","profile.tags":["rust"]}"#
+/// );
+/// ```
+pub struct SerializeFieldsFlat<'a, T, S>(pub &'a T, pub &'a S);
+
+impl<T, S> Serialize for SerializeFieldsFlat<'_, T, S>
+where
+    T: SerializeFieldsTrait<FieldSelector = S>,
+    S: crate::FieldSelector,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        SerializeFields(self.0, self.1).serialize(Flatten {
+            map: &mut map,
+            prefix: String::new(),
+        })?;
+        map.end()
+    }
+}
+
+/// Serializer flattening a struct's fields into `map`, below `prefix`.
+struct Flatten<'m, M> {
+    map: &'m mut M,
+    prefix: String,
+}
+
+macro_rules! not_a_struct {
+    ($($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<(), Self::Error> {
+                Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+            }
+        )*
+    };
+}
+
+impl<'m, M: SerializeMap> ser::Serializer for Flatten<'m, M> {
+    type Ok = ();
+    type Error = M::Error;
+    type SerializeSeq = Impossible<(), M::Error>;
+    type SerializeTuple = Impossible<(), M::Error>;
+    type SerializeTupleStruct = Impossible<(), M::Error>;
+    type SerializeTupleVariant = Impossible<(), M::Error>;
+    type SerializeMap = Impossible<(), M::Error>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), M::Error>;
+
+    not_a_struct! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Self::Error> {
+        Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ser::Error::custom("SerializeFieldsFlat can only flatten structs"))
+    }
+}
+
+impl<M: SerializeMap> ser::SerializeStruct for Flatten<'_, M> {
+    type Ok = ();
+    type Error = M::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let path = if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.prefix, key)
+        };
+        if is_struct(value) {
+            value.serialize(Flatten {
+                map: &mut *self.map,
+                prefix: path,
+            })
+        } else {
+            self.map.serialize_entry(&path, value)
+        }
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Whether the value serializes as a struct, possibly inside `Some` or a
+/// newtype, without serializing its fields.
+fn is_struct<T: Serialize + ?Sized>(value: &T) -> bool {
+    value.serialize(Probe).is_ok()
+}
+
+/// Serializer succeeding only for structs; the field values are discarded
+/// without being serialized.
+struct Probe;
+
+#[derive(Debug)]
+struct NotAStruct;
+
+impl std::fmt::Display for NotAStruct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("not a struct")
+    }
+}
+
+impl std::error::Error for NotAStruct {}
+
+impl ser::Error for NotAStruct {
+    fn custom<T: std::fmt::Display>(_msg: T) -> Self {
+        NotAStruct
+    }
+}
+
+impl ser::Serializer for Probe {
+    type Ok = ();
+    type Error = NotAStruct;
+    type SerializeSeq = Impossible<(), NotAStruct>;
+    type SerializeTuple = Impossible<(), NotAStruct>;
+    type SerializeTupleStruct = Impossible<(), NotAStruct>;
+    type SerializeTupleVariant = Impossible<(), NotAStruct>;
+    type SerializeMap = Impossible<(), NotAStruct>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), NotAStruct>;
+
+    not_a_struct! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), NotAStruct> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), NotAStruct> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), NotAStruct> {
+        Err(NotAStruct)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, NotAStruct> {
+        Err(NotAStruct)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, NotAStruct> {
+        Err(NotAStruct)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, NotAStruct> {
+        Err(NotAStruct)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, NotAStruct> {
+        Err(NotAStruct)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, NotAStruct> {
+        Err(NotAStruct)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, NotAStruct> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, NotAStruct> {
+        Err(NotAStruct)
+    }
+}
+
+impl ser::SerializeStruct for Probe {
+    type Ok = ();
+    type Error = NotAStruct;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        _value: &T,
+    ) -> Result<(), NotAStruct> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), NotAStruct> {
+        Ok(())
+    }
+}
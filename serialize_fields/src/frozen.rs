@@ -0,0 +1,106 @@
+//! Immutable, reference-counted selectors for sharing across tasks.
+
+use crate::FieldSelector;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// An immutable selector behind an [`Arc`].
+///
+/// Cloning only bumps a reference count, so a selector parsed once can be
+/// cached and handed to every worker without copying its nested tree. It
+/// dereferences to the selector, so it can be passed anywhere a `&S` is
+/// expected, including [`SerializeFields`](crate::SerializeFields).
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::{FieldSelector, SerializeFields};
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String }
+/// let frozen = "id".parse::<UserSerializeFieldSelector>().unwrap().freeze();
+/// let shared = frozen.clone();
+///
+/// let user = User { id: 1, name: "Alice".to_string() };
+/// let json = serde_json::to_string(&SerializeFields(&user, &*shared)).unwrap();
+/// assert_eq!(json, r#"{"id":1}"#);
+/// ```
+pub struct FrozenSelector<S>(Arc<S>);
+
+impl<S: FieldSelector> FrozenSelector<S> {
+    /// Freeze `selector`.
+    pub fn new(selector: S) -> Self {
+        FrozenSelector(Arc::new(selector))
+    }
+
+    /// Whether both handles share the same selector allocation.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<S: FieldSelector + Clone> FrozenSelector<S> {
+    /// A mutable copy of the selector, cloned unless this is the last reference.
+    pub fn thaw(self) -> S {
+        Arc::try_unwrap(self.0).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+impl<S> Clone for FrozenSelector<S> {
+    fn clone(&self) -> Self {
+        FrozenSelector(Arc::clone(&self.0))
+    }
+}
+
+impl<S> Deref for FrozenSelector<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S> AsRef<S> for FrozenSelector<S> {
+    fn as_ref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: FieldSelector> From<S> for FrozenSelector<S> {
+    fn from(selector: S) -> Self {
+        FrozenSelector::new(selector)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for FrozenSelector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenSelector").field(&*self.0).finish()
+    }
+}
+
+impl<S: fmt::Display> fmt::Display for FrozenSelector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<S: PartialEq> PartialEq for FrozenSelector<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<S: Eq> Eq for FrozenSelector<S> {}
+
+impl<S: std::hash::Hash> std::hash::Hash for FrozenSelector<S> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<S: serde::Serialize> serde::Serialize for FrozenSelector<S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.0.serialize(serializer)
+    }
+}
@@ -0,0 +1,108 @@
+//! Parser for GraphQL-style selection sets, e.g. `{ id profile { bio } }`.
+
+use crate::parse::Cursor;
+use crate::{FieldSelector, InvalidPath, SelectorParseError};
+
+/// Parse a selection set into a selector.
+pub(crate) fn parse<T: FieldSelector>(query: &str) -> Result<T, SelectorParseError> {
+    let mut cursor = Cursor::new(query);
+    document(&mut cursor).map_err(|error| SelectorParseError { errors: vec![error] })?;
+    crate::utils::selector_from_paths(&cursor.paths)
+}
+
+/// A single selection set, surrounded by ignored tokens.
+fn document(cursor: &mut Cursor<'_>) -> Result<(), InvalidPath> {
+    skip_ignored(cursor);
+    if !cursor.eat(b'{') {
+        return Err(cursor.syntax_error("`{`"));
+    }
+    selection_set(cursor, "")?;
+    skip_ignored(cursor);
+    cursor.end("end of input")
+}
+
+/// The selections after an opening `{`, up to and including the `}`.
+fn selection_set(cursor: &mut Cursor<'_>, prefix: &str) -> Result<(), InvalidPath> {
+    let mut empty = true;
+    loop {
+        skip_ignored(cursor);
+        if cursor.peek() == Some(b'}') {
+            if empty {
+                return Err(cursor.syntax_error("a field name"));
+            }
+            cursor.at += 1;
+            return Ok(());
+        }
+
+        let position = cursor.at;
+        let mut name = field_name(cursor)?;
+        // `alias: field` selects `field`
+        skip_ignored(cursor);
+        if cursor.eat(b':') {
+            skip_ignored(cursor);
+            name = field_name(cursor)?;
+            skip_ignored(cursor);
+        }
+        if cursor.peek() == Some(b'(') {
+            skip_arguments(cursor)?;
+            skip_ignored(cursor);
+        }
+
+        if cursor.eat(b'{') {
+            selection_set(cursor, &format!("{}{}.", prefix, name))?;
+        } else if name != "__typename" {
+            cursor.paths.push((format!("{}{}", prefix, name), position));
+        }
+        empty = false;
+    }
+}
+
+fn field_name<'a>(cursor: &mut Cursor<'a>) -> Result<&'a str, InvalidPath> {
+    let start = cursor.at;
+    let name = cursor.take_while(|byte| byte == b'_' || byte.is_ascii_alphanumeric());
+    if name.is_empty() || name.as_bytes()[0].is_ascii_digit() {
+        cursor.at = start;
+        return Err(cursor.syntax_error(if cursor.peek().is_some() {
+            "a field name"
+        } else {
+            "`}`"
+        }));
+    }
+    Ok(name)
+}
+
+/// Skip `(...)` arguments, which do not affect the selection.
+fn skip_arguments(cursor: &mut Cursor<'_>) -> Result<(), InvalidPath> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    while let Some(byte) = cursor.peek() {
+        cursor.at += 1;
+        match byte {
+            b'\\' if in_string => cursor.at = (cursor.at + 1).min(cursor.input.len()),
+            b'"' => in_string = !in_string,
+            b'(' if !in_string => depth += 1,
+            b')' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(cursor.syntax_error("`)`"))
+}
+
+/// Skip whitespace, commas and `#` comments, which GraphQL ignores.
+fn skip_ignored(cursor: &mut Cursor<'_>) {
+    while let Some(byte) = cursor.peek() {
+        match byte {
+            b'#' => {
+                cursor.take_while(|byte| byte != b'\n');
+            }
+            b',' => cursor.at += 1,
+            byte if byte.is_ascii_whitespace() => cursor.at += 1,
+            _ => return,
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Process-wide hooks observing unknown fields requested through
+//! `enable_dot_hierarchy` and sensitive fields being serialized.
+
+use crate::{FieldSelector, SerializeOptions, UnknownField, suggest};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// A hook that can be installed and removed at any time.
+struct HookSlot<F: ?Sized> {
+    // Checked before taking the lock, so unhooked programs only pay an atomic load
+    hooked: AtomicBool,
+    hook: RwLock<Option<Arc<F>>>,
+}
+
+impl<F: ?Sized> HookSlot<F> {
+    const fn new() -> Self {
+        HookSlot {
+            hooked: AtomicBool::new(false),
+            hook: RwLock::new(None),
+        }
+    }
+
+    fn set(&self, hook: Option<Arc<F>>) {
+        let mut slot = self.hook.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.hooked.store(hook.is_some(), Ordering::Release);
+        *slot = hook;
+    }
+
+    /// The installed hook, cloned out so that it may itself install or clear
+    /// hooks.
+    fn get(&self) -> Option<Arc<F>> {
+        if !self.hooked.load(Ordering::Acquire) {
+            return None;
+        }
+        self.hook
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+type UnknownFieldHook = dyn Fn(&'static str, &UnknownField) + Send + Sync;
+type SensitiveAccessHook = dyn Fn(&SensitiveAccess<'_>) + Send + Sync;
+
+static UNKNOWN_FIELD_HOOK: HookSlot<UnknownFieldHook> = HookSlot::new();
+static SENSITIVE_ACCESS_HOOK: HookSlot<SensitiveAccessHook> = HookSlot::new();
+
+/// Install `hook` to be called whenever `enable_dot_hierarchy` ignores a path
+/// that does not name a selectable field, with the
+/// [`TYPE_NAME`](FieldSelector::TYPE_NAME) of the struct and the path, e.g.
+/// to log or count the nonexistent fields clients request.
+///
+/// Sensitive fields are reported too, as `enable_dot_hierarchy` ignores them.
+/// Replaces any previously installed hook.
+///
+/// # Examples
+///
+/// ```rust,standalone_crate
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String }
+/// use std::sync::{Arc, Mutex};
+///
+/// let seen = Arc::new(Mutex::new(Vec::new()));
+/// let sink = seen.clone();
+/// serialize_fields::set_unknown_field_hook(move |type_name, field| {
+///     sink.lock().unwrap().push(format!("{}: {}", type_name.rsplit("::").next().unwrap(), field));
+/// });
+///
+/// let mut selector = UserSerializeFieldSelector::new();
+/// selector.enable_dot_hierarchy("nmae");
+/// serialize_fields::clear_unknown_field_hook();
+///
+/// assert_eq!(
+///     *seen.lock().unwrap(),
+///     ["User: unknown field `nmae` in `nmae`, did you mean `name`?"]
+/// );
+/// ```
+pub fn set_unknown_field_hook<F>(hook: F)
+where
+    F: Fn(&'static str, &UnknownField) + Send + Sync + 'static,
+{
+    UNKNOWN_FIELD_HOOK.set(Some(Arc::new(hook)));
+}
+
+/// Remove the hook installed with [`set_unknown_field_hook`].
+pub fn clear_unknown_field_hook() {
+    UNKNOWN_FIELD_HOOK.set(None);
+}
+
+/// Call the hook, if any, when `field_hierarchy` is not a selectable path of
+/// `S`.
+pub fn observe_enable<S: FieldSelector>(field_hierarchy: &[&str]) {
+    let Some(hook) = UNKNOWN_FIELD_HOOK.get() else {
+        return;
+    };
+    if let Err(at_segment) = S::__validate_path(field_hierarchy) {
+        let path = field_hierarchy.join(".");
+        let field = UnknownField {
+            suggestion: suggest::closest_path(&path, S::__SELECTABLE_PATHS),
+            path,
+            at_segment,
+        };
+        hook(S::TYPE_NAME, &field);
+    }
+}
+
+/// A sensitive field whose value is being serialized, passed to the hook
+/// installed with [`set_sensitive_access_hook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensitiveAccess<'a> {
+    /// [`TYPE_NAME`](FieldSelector::TYPE_NAME) of the struct holding the
+    /// field.
+    pub type_name: &'static str,
+    /// Name of the field in that struct, as used in selections.
+    pub path: &'static str,
+    /// The context given with [`SerializeOptions::audit_context`], such as
+    /// the user exporting the data.
+    pub context: Option<&'a str>,
+}
+
+/// Install `hook` to be called whenever the value of a field marked
+/// `#[serialize_fields(sensitive)]` is serialized, e.g. to keep evidence of
+/// who exported personal data. Redacted values are not reported.
+///
+/// Fields of nested structs are reported with the name of the nested struct.
+/// Replaces any previously installed hook.
+///
+/// # Examples
+///
+/// ```rust,standalone_crate
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, #[serialize_fields(sensitive)] ssn: String }
+/// use std::sync::{Arc, Mutex};
+///
+/// let seen = Arc::new(Mutex::new(Vec::new()));
+/// let sink = seen.clone();
+/// serialize_fields::set_sensitive_access_hook(move |access| {
+///     sink.lock().unwrap().push(format!("{} read {}", access.context.unwrap_or("?"), access.path));
+/// });
+///
+/// let user = User { id: 1, ssn: "123-45-6789".into() };
+/// let mut selector = UserSerializeFieldSelector::all();
+/// selector.enable_sensitive("ssn");
+/// serde_json::to_string(&SerializeFields(&user, &selector).audit_context("admin 7")).unwrap();
+/// serialize_fields::clear_sensitive_access_hook();
+///
+/// assert_eq!(*seen.lock().unwrap(), ["admin 7 read ssn"]);
+/// ```
+pub fn set_sensitive_access_hook<F>(hook: F)
+where
+    F: Fn(&SensitiveAccess<'_>) + Send + Sync + 'static,
+{
+    SENSITIVE_ACCESS_HOOK.set(Some(Arc::new(hook)));
+}
+
+/// Remove the hook installed with [`set_sensitive_access_hook`].
+pub fn clear_sensitive_access_hook() {
+    SENSITIVE_ACCESS_HOOK.set(None);
+}
+
+/// Call the hook, if any, for the sensitive field `path` of the struct of
+/// selector `S`, about to be serialized.
+pub fn observe_sensitive<S: FieldSelector>(path: &'static str, options: &SerializeOptions<'_>) {
+    if let Some(hook) = SENSITIVE_ACCESS_HOOK.get() {
+        hook(&SensitiveAccess {
+            type_name: S::TYPE_NAME,
+            path,
+            context: options.audit_context,
+        });
+    }
+}
@@ -0,0 +1,324 @@
+//! Building selectors from HTTP requests.
+//!
+//! [`Fields`] holds the selector requested by a client, parsed strictly and
+//! within [`SelectorLimits`] from the query string or a header, as set up by
+//! [`FieldsConfig`]. Framework integrations only need to hand it the request
+//! and turn a [`FieldsRejection`] into a `400 Bad Request`.
+//! [`Selected`] pairs the response data with that selector.
+//!
+//! Nothing here depends on a web framework, and no extractor is provided for
+//! any: the README shows the few lines adapting [`Fields`] to axum's
+//! `FromRequestParts` and actix-web's `FromRequest`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use serialize_fields::SerializeFields;
+//! # use serde::Serialize;
+//! # #[derive(SerializeFields, Serialize)]
+//! # struct User { id: u32, name: String, email: String }
+//! use serialize_fields::http::Fields;
+//!
+//! let Fields(selector) = Fields::<UserSerializeFieldSelector>::from_query("page=2&fields=id%2Cname").unwrap();
+//! assert_eq!(selector.to_string(), "id,name");
+//!
+//! let rejection = Fields::<UserSerializeFieldSelector>::from_query("fields=id,nmae").unwrap_err();
+//! assert_eq!(rejection.status(), 400);
+//! assert!(rejection.to_string().contains("`nmae`"));
+//! ```
+
+use crate::{FieldSelector, SelectorLimitError, SelectorLimits, SerializeFields};
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Name of the query parameter read by [`Fields::from_query`].
+pub const DEFAULT_QUERY_PARAM: &str = "fields";
+
+/// Where the requested fields are read from, and how they are checked.
+///
+/// This is the single entry point shared by framework integrations: they pass
+/// the raw query string and a header lookup to [`extract`](Self::extract).
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String }
+/// use serialize_fields::http::FieldsConfig;
+///
+/// let config = FieldsConfig::new().header("X-Fields");
+///
+/// // The header takes precedence over the query string
+/// let fields = config
+///     .extract::<UserSerializeFieldSelector>(Some("fields=id"), |name| {
+///         name.eq_ignore_ascii_case("x-fields").then_some("name")
+///     })
+///     .unwrap();
+/// assert_eq!(fields.to_string(), "name");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldsConfig {
+    /// Query parameter to read, [`DEFAULT_QUERY_PARAM`] by default.
+    pub query_param: Option<&'static str>,
+    /// Header to read, none by default.
+    pub header: Option<&'static str>,
+    /// Limits the requested selection must stay within.
+    pub limits: SelectorLimits,
+}
+
+impl FieldsConfig {
+    /// Read the `fields` query parameter with the default limits.
+    pub fn new() -> Self {
+        FieldsConfig {
+            query_param: Some(DEFAULT_QUERY_PARAM),
+            header: None,
+            limits: SelectorLimits::default(),
+        }
+    }
+
+    /// Read the query parameter `name` instead of `fields`.
+    pub fn query_param(mut self, name: &'static str) -> Self {
+        self.query_param = Some(name);
+        self
+    }
+
+    /// Ignore the query string.
+    pub fn without_query(mut self) -> Self {
+        self.query_param = None;
+        self
+    }
+
+    /// Also read header `name`, which takes precedence over the query string.
+    pub fn header(mut self, name: &'static str) -> Self {
+        self.header = Some(name);
+        self
+    }
+
+    /// Check the selection against `limits`.
+    pub fn limits(mut self, limits: SelectorLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Build the requested selector from a raw query string and a header
+    /// lookup, which receives the configured header name.
+    ///
+    /// Header values are used as they are, query values are percent-decoded.
+    /// When neither is present the selector is [`FieldSelector::with_defaults`].
+    pub fn extract<'h, S: FieldSelector>(
+        &self,
+        query: Option<&str>,
+        header: impl FnOnce(&str) -> Option<&'h str>,
+    ) -> Result<Fields<S>, FieldsRejection> {
+        if let Some(name) = self.header
+            && let Some(value) = header(name)
+        {
+            return Fields::parse(value, name, &self.limits);
+        }
+        match (self.query_param, query) {
+            (Some(name), Some(query)) => Fields::from_query_param(query, name, &self.limits),
+            _ => Ok(Fields(S::with_defaults())),
+        }
+    }
+}
+
+impl Default for FieldsConfig {
+    fn default() -> Self {
+        FieldsConfig::new()
+    }
+}
+
+/// A selector requested by the client.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Fields<S>(pub S);
+
+impl<S: FieldSelector> Fields<S> {
+    /// Parse the `fields` parameter of a raw query string, such as
+    /// `page=2&fields=id,name`.
+    ///
+    /// Without the parameter the selector is [`FieldSelector::with_defaults`].
+    /// Repeated parameters are combined, as in `fields=id&fields=name`.
+    pub fn from_query(query: &str) -> Result<Self, FieldsRejection> {
+        FieldsConfig::new().extract(Some(query), |_| None)
+    }
+
+    /// Parse the query parameter `name` of a raw query string, with `limits`.
+    pub fn from_query_param(
+        query: &str,
+        name: &str,
+        limits: &SelectorLimits,
+    ) -> Result<Self, FieldsRejection> {
+        let mut fields: Option<String> = None;
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if percent_decode(key) != name {
+                continue;
+            }
+            let value = percent_decode(value);
+            match &mut fields {
+                Some(fields) => {
+                    fields.push(',');
+                    fields.push_str(&value);
+                }
+                None => fields = Some(value.into_owned()),
+            }
+        }
+
+        match fields {
+            Some(fields) => Self::parse(&fields, name, limits),
+            None => Ok(Fields(S::with_defaults())),
+        }
+    }
+
+    /// Parse a field list that was already extracted from the request, so that
+    /// errors name `source`, e.g. the parameter or header it came from.
+    pub fn parse(fields: &str, source: &str, limits: &SelectorLimits) -> Result<Self, FieldsRejection> {
+        limits.parse(fields).map(Fields).map_err(|error| FieldsRejection {
+            source: source.to_string(),
+            error,
+        })
+    }
+
+    /// Return the selector.
+    pub fn into_inner(self) -> S {
+        self.0
+    }
+}
+
+impl<S> Deref for Fields<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S> DerefMut for Fields<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.0
+    }
+}
+
+/// Content type of the bodies written by [`Selected::to_json`].
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// A response body serialized with only the selected fields.
+///
+/// `T` is the data, a struct deriving `SerializeFields` or a `Vec` or
+/// `Option` of one, and `S` its selector. It does not implement any
+/// framework's response trait such as axum's `IntoResponse`; since it is
+/// `Serialize`, wrapping it in the framework's JSON response (`axum::Json`)
+/// sets the content type, or write [`to_json`](Self::to_json) with
+/// [`JSON_CONTENT_TYPE`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String }
+/// use serialize_fields::http::{Fields, Selected};
+///
+/// let Fields(fields) = Fields::<UserSerializeFieldSelector>::from_query("fields=name").unwrap();
+/// let users = vec![User { id: 1, name: "Alice".to_string() }];
+///
+/// let body = Selected(users, fields).to_json().unwrap();
+/// assert_eq!(body, br#"[{"name":"Alice"}]"#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selected<T, S>(pub T, pub S);
+
+impl<T, S> Selected<T, S>
+where
+    for<'a> SerializeFields<'a, T, S>: serde::Serialize,
+{
+    /// Serialize the selected fields as a JSON body.
+    pub fn to_json(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+}
+
+impl<T, S> serde::Serialize for Selected<T, S>
+where
+    for<'a> SerializeFields<'a, T, S>: serde::Serialize,
+{
+    fn serialize<Se: serde::Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        SerializeFields(&self.0, &self.1).serialize(serializer)
+    }
+}
+
+/// The requested fields are invalid or exceed the limits.
+///
+/// Its message names the offending path, so it can be sent back to the client
+/// as the body of a `400 Bad Request`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldsRejection {
+    source: String,
+    error: SelectorLimitError,
+}
+
+impl FieldsRejection {
+    /// The status code to respond with, always 400.
+    pub fn status(&self) -> u16 {
+        400
+    }
+
+    /// The parameter or header the fields were read from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// What was wrong with the fields.
+    pub fn error(&self) -> &SelectorLimitError {
+        &self.error
+    }
+}
+
+impl fmt::Display for FieldsRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid `{}`: {}", self.source, self.error)
+    }
+}
+
+impl std::error::Error for FieldsRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Decode `+` and `%XX` escapes of a query string component. Invalid escapes
+/// are kept as they are.
+fn percent_decode(component: &str) -> Cow<'_, str> {
+    if !component.contains(['%', '+']) {
+        return Cow::Borrowed(component);
+    }
+
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = match bytes[i] {
+            b'+' => b' ',
+            b'%' => match bytes.get(i + 1..i + 3).and_then(|pair| hex_pair(pair[0], pair[1])) {
+                Some(byte) => {
+                    i += 2;
+                    byte
+                }
+                None => b'%',
+            },
+            byte => byte,
+        };
+        decoded.push(byte);
+        i += 1;
+    }
+    Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+fn hex_pair(high: u8, low: u8) -> Option<u8> {
+    let digit = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+    Some(digit(high)? << 4 | digit(low)?)
+}
@@ -0,0 +1,73 @@
+//! Serialization of the items of an iterator as a sequence of filtered structs.
+
+use crate::{SerializeFieldsTrait, SerializeFieldsWith, SerializeOptions};
+use serde::ser::{self, Serialize};
+use std::cell::RefCell;
+
+/// Serializes the items of an iterator of references as a sequence, each
+/// with the fields enabled in the selector, without collecting them first.
+///
+/// The iterator is consumed by serializing, so a `SerializeFieldsIter` can be
+/// serialized only once; serializing it again fails with an error.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, active: bool }
+/// use serialize_fields::SerializeFieldsIter;
+///
+/// let users = vec![User { id: 1, active: true }, User { id: 2, active: false }];
+/// let selector: UserSerializeFieldSelector = "id".parse().unwrap();
+///
+/// let active = SerializeFieldsIter::new(users.iter().filter(|user| user.active), &selector);
+/// assert_eq!(serde_json::to_string(&active).unwrap(), r#"[{"id":1}]"#);
+/// ```
+pub struct SerializeFieldsIter<'a, I, S> {
+    items: RefCell<Option<I>>,
+    selector: &'a S,
+    options: SerializeOptions<'a>,
+}
+
+impl<'a, I, S> SerializeFieldsIter<'a, I, S> {
+    /// Serialize the items of `items` with the fields enabled in `selector`.
+    pub fn new(items: I, selector: &'a S) -> Self {
+        SerializeFieldsIter {
+            items: RefCell::new(Some(items)),
+            selector,
+            options: SerializeOptions::new(),
+        }
+    }
+
+    /// Serialize every item with `options`; see [`SerializeOptions`].
+    pub fn with_options(mut self, options: SerializeOptions<'a>) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<'a, 't, I, T, S> Serialize for SerializeFieldsIter<'a, I, S>
+where
+    I: IntoIterator<Item = &'t T>,
+    T: SerializeFieldsTrait<FieldSelector = S> + 't,
+    S: crate::FieldSelector,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: ser::Serializer,
+    {
+        let items = self
+            .items
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| ser::Error::custom("SerializeFieldsIter can only be serialized once"))?;
+        let options = self.options.record(self.selector);
+        serializer.collect_seq(
+            items
+                .into_iter()
+                .map(|item| SerializeFieldsWith(item, self.selector, options)),
+        )
+    }
+}
@@ -105,7 +105,164 @@
 // Re-export the derive macro
 pub use serialize_fields_macro::SerializeFields;
 
+mod bits;
+mod catalog;
+pub mod comma_string;
+#[cfg(feature = "csv")]
+pub mod csv;
+mod deserialize;
+mod dyn_selector;
+mod erased;
+mod error;
+mod flat;
+mod fnv;
+mod frozen;
+mod graphql;
+mod hook;
+pub mod http;
+mod iter;
+mod limits;
 mod macros;
+mod odata;
+pub mod openapi;
+mod parse;
+mod partial_response;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod patch;
+mod redact;
+mod rename;
+mod path_list;
+mod policy;
+mod present;
+#[cfg(feature = "schemars")]
+mod schema;
+pub mod stream;
+mod suggest;
+mod usage;
+mod writer;
+
+pub use deserialize::DeserializeFields;
+pub use dyn_selector::DynFieldSelector;
+pub use erased::ErasedSerializeFields;
+pub use flat::SerializeFieldsFlat;
+pub use frozen::FrozenSelector;
+pub use hook::{
+    SensitiveAccess, clear_sensitive_access_hook, clear_unknown_field_hook,
+    set_sensitive_access_hook, set_unknown_field_hook,
+};
+pub use iter::SerializeFieldsIter;
+pub use error::{
+    InvalidPath, InvalidPathReason, MissingRequiredFields, SelectorDecodeError, SelectorParseError,
+    UnknownField,
+};
+pub use limits::{SelectorLimitError, SelectorLimits};
+pub use path_list::{PathListFormat, SequencePaths};
+pub use policy::FieldAccessPolicy;
+pub use present::{Present, PresentFields};
+pub use rename::RenameMap;
+pub use usage::{FieldUsageCounter, FieldUsageRecorder};
+
+// Dependencies used by the generated code, so that deriving crates only need to
+// depend on `serialize_fields` (possibly through a re-export).
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+    pub use serde_json;
+
+    #[cfg(feature = "schemars")]
+    pub use crate::schema::filter_struct_schema;
+    #[cfg(feature = "schemars")]
+    pub use schemars;
+
+    pub use crate::bits::{BitReader, BitWriter};
+    pub use crate::catalog::*;
+    pub use crate::hook::{observe_enable, observe_sensitive};
+    pub use crate::path_list::PathSegment;
+    pub use crate::redact::Redacted;
+    pub use crate::writer::FieldWriter;
+
+    /// Enable the fields present in the objects of `shape`, returning
+    /// whether it held any.
+    pub fn enable_json_shape<S: crate::FieldSelector>(selector: &mut S, shape: &serde_json::Value) -> bool {
+        match shape {
+            serde_json::Value::Object(map) => {
+                selector.__enable_json_shape(map);
+                true
+            }
+            serde_json::Value::Array(elements) => {
+                let mut found = false;
+                for element in elements {
+                    found |= enable_json_shape(selector, element);
+                }
+                found
+            }
+            _ => false,
+        }
+    }
+
+    /// Deserialize a leaf selector entry: any value enables the field except
+    /// `false`, so both `{"id": null}` and `{"id": true}` turn `id` on.
+    pub fn deserialize_enabled<'de, D>(deserializer: D) -> Result<Option<()>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct EnabledVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for EnabledVisitor {
+            type Value = Option<()>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("any value")
+            }
+
+            fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                Ok(v.then_some(()))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, _: i64) -> Result<Self::Value, E> {
+                Ok(Some(()))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, _: u64) -> Result<Self::Value, E> {
+                Ok(Some(()))
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, _: f64) -> Result<Self::Value, E> {
+                Ok(Some(()))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, _: &str) -> Result<Self::Value, E> {
+                Ok(Some(()))
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Some(()))
+            }
+
+            fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                Ok(Some(()))
+            }
+
+            fn visit_some<D: serde::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+                <serde::de::IgnoredAny as serde::Deserialize>::deserialize(d)?;
+                Ok(Some(()))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                Ok(Some(()))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                while map.next_entry::<serde::de::IgnoredAny, serde::de::IgnoredAny>()?.is_some() {}
+                Ok(Some(()))
+            }
+        }
+
+        deserializer.deserialize_any(EnabledVisitor)
+    }
+}
 
 /// Trait for types that can provide field selectors for dynamic serialization.
 ///
@@ -155,10 +312,97 @@ pub trait SerializeFieldsTrait {
         field_selector: &Self::FieldSelector,
         __serializer: __S,
     ) -> Result<__S::Ok, __S::Error>
+    where
+        __S: serde::Serializer,
+    {
+        self.__serialize_with(field_selector, &SerializeOptions::new(), __serializer)
+    }
+
+    /// Serialize this struct with the fields enabled in `field_selector`, as
+    /// changed by `options`.
+    #[doc(hidden)]
+    fn __serialize_with<__S>(
+        &self,
+        field_selector: &Self::FieldSelector,
+        options: &SerializeOptions<'_>,
+        __serializer: __S,
+    ) -> Result<__S::Ok, __S::Error>
     where
         __S: serde::Serializer;
+
+    /// Serialize the fields enabled in `field_selector` into a
+    /// `serde_json::Value`; shorthand for
+    /// `serde_json::to_value(SerializeFields(self, field_selector))`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use serialize_fields::{SerializeFields, SerializeFieldsTrait};
+    /// # use serde::Serialize;
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct User { id: u32, name: String }
+    /// let user = User { id: 1, name: "Alice".into() };
+    /// let selector: UserSerializeFieldSelector = "id".parse().unwrap();
+    ///
+    /// assert_eq!(user.to_filtered_value(&selector).unwrap(), serde_json::json!({"id": 1}));
+    /// assert_eq!(user.to_filtered_string(&selector).unwrap(), r#"{"id":1}"#);
+    /// ```
+    fn to_filtered_value(
+        &self,
+        field_selector: &Self::FieldSelector,
+    ) -> serde_json::Result<serde_json::Value>
+    where
+        Self: Sized,
+    {
+        serde_json::to_value(SerializeFields(self, field_selector))
+    }
+
+    /// Serialize the fields enabled in `field_selector` into a JSON string;
+    /// shorthand for `serde_json::to_string(&SerializeFields(self, field_selector))`.
+    fn to_filtered_string(&self, field_selector: &Self::FieldSelector) -> serde_json::Result<String>
+    where
+        Self: Sized,
+    {
+        serde_json::to_string(&SerializeFields(self, field_selector))
+    }
+}
+
+// References and smart pointers serialize like the value they point to, so
+// that `SerializeFields(&Arc<User>, &selector)` works.
+macro_rules! forward_serialize_fields_trait {
+    ($($pointer:ty),* $(,)?) => {
+        $(
+            impl<T: SerializeFieldsTrait + ?Sized> SerializeFieldsTrait for $pointer {
+                type FieldSelector = T::FieldSelector;
+
+                fn serialize_fields(&self) -> Self::FieldSelector {
+                    (**self).serialize_fields()
+                }
+
+                fn __serialize_with<__S>(
+                    &self,
+                    field_selector: &Self::FieldSelector,
+                    options: &SerializeOptions<'_>,
+                    __serializer: __S,
+                ) -> Result<__S::Ok, __S::Error>
+                where
+                    __S: serde::Serializer,
+                {
+                    (**self).__serialize_with(field_selector, options, __serializer)
+                }
+            }
+        )*
+    };
 }
 
+forward_serialize_fields_trait!(
+    &T,
+    &mut T,
+    Box<T>,
+    std::rc::Rc<T>,
+    std::sync::Arc<T>,
+);
+
 /// A wrapper struct that combines data with a field selector for serialization.
 ///
 /// This is the core type that enables dynamic field selection. It wraps your data
@@ -242,7 +486,348 @@ where
     }
 }
 
+impl<'a, T, S> SerializeFields<'a, T, S> {
+    /// Write disabled fields as `null` instead of leaving them out; see
+    /// [`SerializeOptions::with_nulls`].
+    pub fn with_nulls(self) -> SerializeFieldsWith<'a, T, S> {
+        self.with_options(SerializeOptions::new().with_nulls())
+    }
+
+    /// Write disabled and sensitive fields with their values replaced by
+    /// `placeholder`; see [`SerializeOptions::redacted`].
+    pub fn redacted(self, placeholder: &'a str) -> SerializeFieldsWith<'a, T, S> {
+        self.with_options(SerializeOptions::new().redacted(placeholder))
+    }
+
+    /// Emit the keys given by `renames`; see [`SerializeOptions::with_renames`].
+    pub fn with_renames(self, renames: &'a RenameMap) -> SerializeFieldsWith<'a, T, S> {
+        self.with_options(SerializeOptions::new().with_renames(renames))
+    }
+
+    /// Write fields sorted by key; see [`SerializeOptions::sorted`].
+    pub fn sorted(self) -> SerializeFieldsWith<'a, T, S> {
+        self.with_options(SerializeOptions::new().sorted())
+    }
+
+    /// Tell `recorder` which fields are enabled; see
+    /// [`SerializeOptions::record_usage`].
+    pub fn record_usage(self, recorder: &'a dyn FieldUsageRecorder) -> SerializeFieldsWith<'a, T, S> {
+        self.with_options(SerializeOptions::new().record_usage(recorder))
+    }
+
+    /// Pass `context` to the sensitive access hook; see
+    /// [`SerializeOptions::audit_context`].
+    pub fn audit_context(self, context: &'a str) -> SerializeFieldsWith<'a, T, S> {
+        self.with_options(SerializeOptions::new().audit_context(context))
+    }
+
+    /// Serialize with the given options.
+    pub fn with_options(self, options: SerializeOptions<'a>) -> SerializeFieldsWith<'a, T, S> {
+        SerializeFieldsWith(self.0, self.1, options)
+    }
+}
+
+/// Options changing how the selected fields are written, used through
+/// [`SerializeFieldsWith`].
+///
+/// The defaults match [`SerializeFields`]: disabled fields are left out.
+#[derive(Clone, Copy, Default)]
+pub struct SerializeOptions<'a> {
+    /// Write disabled fields as `null`.
+    pub nulls: bool,
+    /// Write disabled and sensitive fields with this placeholder in place of
+    /// their values.
+    pub redact: Option<&'a str>,
+    /// Key names to emit instead of the serialized names.
+    pub rename: Option<&'a RenameMap>,
+    /// Write the fields of each struct sorted by key.
+    pub sorted: bool,
+    /// Told which fields are enabled, once per serialized value.
+    pub usage: Option<&'a dyn FieldUsageRecorder>,
+    /// Passed to the sensitive access hook for each sensitive field written.
+    pub audit_context: Option<&'a str>,
+}
+
+impl std::fmt::Debug for SerializeOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SerializeOptions")
+            .field("nulls", &self.nulls)
+            .field("redact", &self.redact)
+            .field("rename", &self.rename)
+            .field("sorted", &self.sorted)
+            .field("usage", &self.usage.is_some())
+            .field("audit_context", &self.audit_context)
+            .finish()
+    }
+}
+
+impl<'a> SerializeOptions<'a> {
+    /// The default options.
+    pub const fn new() -> Self {
+        SerializeOptions {
+            nulls: false,
+            redact: None,
+            rename: None,
+            sorted: false,
+            usage: None,
+            audit_context: None,
+        }
+    }
+
+    /// Write every disabled field, plain or nested, as `null` instead of
+    /// leaving it out, so every record has the same keys. The values are still
+    /// hidden; for formats such as bincode this keeps the layout of `Option`
+    /// fields.
+    pub const fn with_nulls(mut self) -> Self {
+        self.nulls = true;
+        self
+    }
+
+    /// Write every field, replacing the values of disabled fields, and of
+    /// sensitive ones even when enabled, with `placeholder`.
+    ///
+    /// The output keeps the shape of the full struct: disabled nested structs
+    /// are written with all of their fields redacted, and inside a redacted
+    /// value every string, number, boolean and enum variant becomes the
+    /// placeholder while sequences, maps and `null`s keep their structure.
+    pub const fn redacted(mut self, placeholder: &'a str) -> Self {
+        self.redact = Some(placeholder);
+        self
+    }
+
+    /// Emit the keys given by `renames` instead of the serialized names, at
+    /// every level. Structs are then written as maps.
+    pub const fn with_renames(mut self, renames: &'a RenameMap) -> Self {
+        self.rename = Some(renames);
+        self
+    }
+
+    /// Write the fields of every struct sorted by emitted key, comparing bytes,
+    /// instead of in declaration order, for canonical output such as signed
+    /// or diffed JSON. Map and sequence contents keep their own order.
+    pub const fn sorted(mut self) -> Self {
+        self.sorted = true;
+        self
+    }
+
+    /// Tell `recorder` which fields are enabled each time a value is
+    /// serialized with these options; see [`FieldUsageRecorder`].
+    pub const fn record_usage(mut self, recorder: &'a dyn FieldUsageRecorder) -> Self {
+        self.usage = Some(recorder);
+        self
+    }
+
+    /// Pass `context`, such as the user exporting the data, to the hook set
+    /// with [`set_sensitive_access_hook`] for each sensitive field written.
+    pub const fn audit_context(mut self, context: &'a str) -> Self {
+        self.audit_context = Some(context);
+        self
+    }
+
+    /// Report the fields of `selector` to the recorder, returning the options
+    /// to serialize with, without it so that inner values are not reported.
+    pub(crate) fn record<S: FieldSelector>(self, selector: &S) -> Self {
+        match self.usage {
+            Some(recorder) => {
+                recorder.record(S::TYPE_NAME, &selector.enabled_paths());
+                SerializeOptions { usage: None, ..self }
+            }
+            None => self,
+        }
+    }
+}
+
+/// [`SerializeFields`] with [`SerializeOptions`], created with
+/// [`SerializeFields::with_options`] or its shorthands.
+///
+/// The options also apply to nested structs.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, email: Option<String> }
+/// let user = User { id: 1, email: Some("alice@example.com".into()) };
+/// let selector: UserSerializeFieldSelector = "id".parse().unwrap();
+///
+/// assert_eq!(
+///     serde_json::to_string(&SerializeFields(&user, &selector).with_nulls()).unwrap(),
+///     r#"{"id":1,"email":null}"#
+/// );
+/// ```
+pub struct SerializeFieldsWith<'a, T, S>(pub &'a T, pub &'a S, pub SerializeOptions<'a>);
+
+impl<'a, T, S> SerializeFieldsWith<'a, T, S> {
+    /// Also write disabled fields as `null`.
+    pub fn with_nulls(mut self) -> Self {
+        self.2 = self.2.with_nulls();
+        self
+    }
+
+    /// Also redact disabled and sensitive fields with `placeholder`.
+    pub fn redacted(mut self, placeholder: &'a str) -> Self {
+        self.2 = self.2.redacted(placeholder);
+        self
+    }
+
+    /// Also emit the keys given by `renames`.
+    pub fn with_renames(mut self, renames: &'a RenameMap) -> Self {
+        self.2 = self.2.with_renames(renames);
+        self
+    }
+
+    /// Also write fields sorted by key.
+    pub fn sorted(mut self) -> Self {
+        self.2 = self.2.sorted();
+        self
+    }
+
+    /// Also tell `recorder` which fields are enabled.
+    pub fn record_usage(mut self, recorder: &'a dyn FieldUsageRecorder) -> Self {
+        self.2 = self.2.record_usage(recorder);
+        self
+    }
+
+    /// Also pass `context` to the sensitive access hook.
+    pub fn audit_context(mut self, context: &'a str) -> Self {
+        self.2 = self.2.audit_context(context);
+        self
+    }
+}
+
+/// Serializes every field of the data, with disabled and sensitive fields
+/// replaced by a placeholder, for same-shaped audit logs.
+///
+/// Shorthand for `SerializeFields(data, selector).redacted(placeholder)`; see
+/// [`SerializeOptions::redacted`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String, #[serialize_fields(sensitive)] ssn: String }
+/// use serialize_fields::SerializeFieldsRedacted;
+///
+/// let user = User { id: 1, name: "Alice".into(), ssn: "123-45-6789".into() };
+/// let mut selector = UserSerializeFieldSelector::all();
+/// selector.enable_sensitive("ssn");
+///
+/// assert_eq!(
+///     serde_json::to_string(&SerializeFieldsRedacted(&user, &selector, "***")).unwrap(),
+///     r#"{"id":1,"name":"Alice","ssn":"***"}"#
+/// );
+/// ```
+pub struct SerializeFieldsRedacted<'a, T, S>(pub &'a T, pub &'a S, pub &'a str);
+
+impl<'a, T, S> serde::Serialize for SerializeFieldsRedacted<'a, T, S>
+where
+    SerializeFieldsWith<'a, T, S>: serde::Serialize,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: serde::Serializer,
+    {
+        SerializeFields(self.0, self.1).redacted(self.2).serialize(serializer)
+    }
+}
+
+impl<'a, T, S> serde::Serialize for SerializeFieldsWith<'a, T, S>
+where
+    T: SerializeFieldsTrait<FieldSelector = S>,
+    S: FieldSelector,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: serde::Serializer,
+    {
+        self.0.__serialize_with(self.1, &self.2.record(self.1), serializer)
+    }
+}
+
+impl<'a, T, S> serde::Serialize for SerializeFieldsWith<'a, Vec<T>, S>
+where
+    T: SerializeFieldsTrait<FieldSelector = S>,
+    S: FieldSelector,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: serde::Serializer,
+    {
+        let options = self.2.record(self.1);
+        serializer.collect_seq(self.0.iter().map(|item| SerializeFieldsWith(item, self.1, options)))
+    }
+}
+
+impl<'a, T, S> serde::Serialize for SerializeFieldsWith<'a, Option<T>, S>
+where
+    T: SerializeFieldsTrait<FieldSelector = S>,
+    S: FieldSelector,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: serde::Serializer,
+    {
+        let options = self.2.record(self.1);
+        match self.0 {
+            Some(inner) => SerializeFieldsWith(inner, self.1, options).serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Serializes the selected fields under `data`, next to the applied field list
+/// under `_fields`, so a response says which projection it carries.
+///
+/// `_fields` is the selector's [`to_path_list`](FieldSelector::to_path_list):
+/// the dot paths of the serialized fields, under their serialized names and
+/// with `always` fields included. It works for collections too.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String, email: String }
+/// use serialize_fields::SerializeFieldsEnvelope;
+///
+/// let users = vec![User { id: 1, name: "Alice".into(), email: "alice@example.com".into() }];
+/// let selector: UserSerializeFieldSelector = "id,name".parse().unwrap();
+///
+/// assert_eq!(
+///     serde_json::to_string(&SerializeFieldsEnvelope(&users, &selector)).unwrap(),
+///     r#"{"data":[{"id":1,"name":"Alice"}],"_fields":["id","name"]}"#
+/// );
+/// ```
+pub struct SerializeFieldsEnvelope<'a, T, S>(pub &'a T, pub &'a S);
+
+impl<'a, T, S> serde::Serialize for SerializeFieldsEnvelope<'a, T, S>
+where
+    SerializeFields<'a, T, S>: serde::Serialize,
+    S: FieldSelector,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("SerializeFieldsEnvelope", 2)?;
+        state.serialize_field("data", &SerializeFields(self.0, self.1))?;
+        state.serialize_field("_fields", &self.1.to_path_list())?;
+        state.end()
+    }
+}
+
 // implement JsonSchema for SerializeFields<T, S> where T implements JsonSchema
+//
+// The schema is `T`'s with no `required` fields in `T` or its nested selectable
+// structs, since the selector may leave any of them out. It gets its own name
+// so that it does not replace the definition of `T` itself.
 #[cfg(feature = "schemars")]
 impl<'a, T, S> schemars::JsonSchema for SerializeFields<'a, T, S>
 where
@@ -251,10 +836,16 @@ where
 {
     // Required methods
     fn schema_name() -> std::borrow::Cow<'static, str> {
-        T::schema_name()
+        format!("{}Fields", T::schema_name()).into()
     }
     fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
-        T::json_schema(generator)
+        let known = generator.definitions().keys().cloned().collect();
+        let mut schema = T::json_schema(generator).to_value();
+        schema::filter_struct_schema(&mut schema, generator, &mut |properties| {
+            S::__relax_schema_properties(properties, generator)
+        });
+        schema::prune_definitions(generator, &known, &schema);
+        schema.try_into().expect("a relaxed schema is still an object")
     }
 
     // Provided methods
@@ -265,54 +856,1096 @@ where
         T::inline_schema()
     }
     fn schema_id() -> std::borrow::Cow<'static, str> {
-        T::schema_id()
+        format!("serialize_fields::SerializeFields<{}>", T::schema_id()).into()
     }
 }
 
-/// Helper trait for field selectors to provide common functionality.
+/// Copy the fields enabled in a selector from one value to another.
 ///
-/// This trait is automatically implemented for all generated field selectors.
-pub trait FieldSelector {
-    /// Create a new selector with all fields disabled.
-    fn new() -> Self;
+/// This is the write-side counterpart of [`SerializeFields`]: a `PATCH`
+/// handler can apply the same selector to the stored entity that a `GET`
+/// would use to filter it. Only fields enabled in the selector are written;
+/// `always` has no effect here. Implemented with
+/// `#[serialize_fields(update)]`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// #[derive(SerializeFields, Serialize)]
+/// #[serialize_fields(update)]
+/// struct User {
+///     id: u32,
+///     name: String,
+///     email: String,
+/// }
+///
+/// let mut stored = User { id: 1, name: "Alice".into(), email: "alice@example.com".into() };
+/// let patch = User { id: 0, name: "Alicia".into(), email: String::new() };
+///
+/// let mask: UserSerializeFieldSelector = "name".parse().unwrap();
+/// mask.apply_update(&mut stored, &patch);
+/// assert_eq!((stored.id, stored.name.as_str()), (1, "Alicia"));
+/// assert_eq!(stored.email, "alice@example.com");
+/// ```
+pub trait UpdateFields: SerializeFieldsTrait {
+    /// Overwrite the fields of `self` enabled in `selector` with those of
+    /// `source`, recursing into nested structs.
+    fn update_fields(&mut self, source: &Self, selector: &Self::FieldSelector);
+}
 
-    /// Enable a field using dot notation.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// selector.enable_dot_hierarchy("name");           // Simple field
-    /// selector.enable_dot_hierarchy("profile.bio");    // Nested field
-    /// selector.enable_dot_hierarchy("posts.title");    // Field in collection
-    /// ```
-    fn enable_dot_hierarchy(&mut self, field: &str);
+/// Resetting the fields not enabled in a selector of type `S` to their
+/// default, with `None` for `Option`s, so that data handed to code bypassing
+/// the serializer, such as a template engine, holds only what would be
+/// serialized. `always` fields are kept. Implemented with
+/// `#[serialize_fields(clear)]`, and for containers of such structs.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// #[derive(SerializeFields, Serialize)]
+/// #[serialize_fields(clear)]
+/// struct User {
+///     id: u32,
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// let mut user = User { id: 1, name: "Alice".into(), email: Some("alice@example.com".into()) };
+/// let selector: UserSerializeFieldSelector = "name".parse().unwrap();
+///
+/// user.clear_unselected(&selector);
+/// assert_eq!((user.id, user.name.as_str(), user.email), (0, "Alice", None));
+/// ```
+pub trait ClearUnselected<S> {
+    /// Reset the fields of `self` not enabled in `selector`, recursing into
+    /// nested structs.
+    fn clear_unselected(&mut self, selector: &S);
+}
 
-    /// Enable a field using a slice of field names.
-    ///
-    /// This is useful when you already have the field path split.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// selector.enable(&["name"]);                  // Simple field
-    /// selector.enable(&["profile", "bio"]);       // Nested field
-    /// ```
-    fn enable(&mut self, field_hierarchy: &[&str]);
+impl<S, T: ClearUnselected<S>> ClearUnselected<S> for Option<T> {
+    fn clear_unselected(&mut self, selector: &S) {
+        if let Some(value) = self {
+            value.clear_unselected(selector);
+        }
+    }
 }
 
-/// Utility functions for working with field selectors.
-pub mod utils {
-    /// Parse a comma-separated list of field names.
-    ///
-    /// This is useful for parsing query parameters or configuration strings.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use serialize_fields::utils::parse_field_list;
-    ///
-    /// let fields = parse_field_list("id,name,profile.bio");
-    /// assert_eq!(fields, vec!["id", "name", "profile.bio"]);
+impl<S, T: ClearUnselected<S>> ClearUnselected<S> for Vec<T> {
+    fn clear_unselected(&mut self, selector: &S) {
+        self.as_mut_slice().clear_unselected(selector);
+    }
+}
+
+impl<S, T: ClearUnselected<S>> ClearUnselected<S> for [T] {
+    fn clear_unselected(&mut self, selector: &S) {
+        for value in self {
+            value.clear_unselected(selector);
+        }
+    }
+}
+
+impl<S, T: ClearUnselected<S>, const N: usize> ClearUnselected<S> for [T; N] {
+    fn clear_unselected(&mut self, selector: &S) {
+        self.as_mut_slice().clear_unselected(selector);
+    }
+}
+
+impl<S, T: ClearUnselected<S> + ?Sized> ClearUnselected<S> for Box<T> {
+    fn clear_unselected(&mut self, selector: &S) {
+        (**self).clear_unselected(selector);
+    }
+}
+
+/// Equality over the fields that would be serialized with a selector of type
+/// `S`, for change detection on projected data. Implemented with
+/// `#[serialize_fields(eq)]`, and for containers of such structs.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// #[derive(SerializeFields, Serialize)]
+/// #[serialize_fields(eq, hash)]
+/// struct User {
+///     id: u32,
+///     name: String,
+///     last_seen: u64,
+/// }
+///
+/// let selector: UserSerializeFieldSelector = "id,name".parse().unwrap();
+/// let before = User { id: 1, name: "Alice".into(), last_seen: 100 };
+/// let after = User { id: 1, name: "Alice".into(), last_seen: 200 };
+/// assert!(before.eq_selected(&after, &selector));
+///
+/// let hash = |user: &User| {
+///     let mut hasher = std::collections::hash_map::DefaultHasher::new();
+///     user.hash_selected(&selector, &mut hasher);
+///     std::hash::Hasher::finish(&hasher)
+/// };
+/// assert_eq!(hash(&before), hash(&after));
+/// ```
+pub trait SelectedEq<S> {
+    /// Whether the fields of `self` and `other` serialized with `selector` are
+    /// equal. `always` fields are compared, and nested structs serialized in
+    /// full are compared as a whole.
+    fn eq_selected(&self, other: &Self, selector: &S) -> bool;
+}
+
+/// Hashing of the fields that would be serialized with a selector of type
+/// `S`, consistent with [`SelectedEq`], e.g. for cache keys over projected
+/// data. Implemented with `#[serialize_fields(hash)]`, and for containers of
+/// such structs.
+pub trait SelectedHash<S> {
+    /// Feed the fields of `self` serialized with `selector` into `state`.
+    fn hash_selected<H: std::hash::Hasher>(&self, selector: &S, state: &mut H);
+}
+
+impl<S, T: SelectedEq<S>> SelectedEq<S> for Option<T> {
+    fn eq_selected(&self, other: &Self, selector: &S) -> bool {
+        match (self, other) {
+            (Some(value), Some(other)) => value.eq_selected(other, selector),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<S, T: SelectedHash<S>> SelectedHash<S> for Option<T> {
+    fn hash_selected<H: std::hash::Hasher>(&self, selector: &S, state: &mut H) {
+        state.write_u8(self.is_some() as u8);
+        if let Some(value) = self {
+            value.hash_selected(selector, state);
+        }
+    }
+}
+
+impl<S, T: SelectedEq<S>> SelectedEq<S> for [T] {
+    fn eq_selected(&self, other: &Self, selector: &S) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other)
+                .all(|(value, other)| value.eq_selected(other, selector))
+    }
+}
+
+impl<S, T: SelectedHash<S>> SelectedHash<S> for [T] {
+    fn hash_selected<H: std::hash::Hasher>(&self, selector: &S, state: &mut H) {
+        state.write_usize(self.len());
+        for value in self {
+            value.hash_selected(selector, state);
+        }
+    }
+}
+
+impl<S, T: SelectedEq<S>> SelectedEq<S> for Vec<T> {
+    fn eq_selected(&self, other: &Self, selector: &S) -> bool {
+        self.as_slice().eq_selected(other, selector)
+    }
+}
+
+impl<S, T: SelectedHash<S>> SelectedHash<S> for Vec<T> {
+    fn hash_selected<H: std::hash::Hasher>(&self, selector: &S, state: &mut H) {
+        self.as_slice().hash_selected(selector, state);
+    }
+}
+
+impl<S, T: SelectedEq<S>, const N: usize> SelectedEq<S> for [T; N] {
+    fn eq_selected(&self, other: &Self, selector: &S) -> bool {
+        self.as_slice().eq_selected(other, selector)
+    }
+}
+
+impl<S, T: SelectedHash<S>, const N: usize> SelectedHash<S> for [T; N] {
+    fn hash_selected<H: std::hash::Hasher>(&self, selector: &S, state: &mut H) {
+        self.as_slice().hash_selected(selector, state);
+    }
+}
+
+impl<S, T: SelectedEq<S> + ?Sized> SelectedEq<S> for Box<T> {
+    fn eq_selected(&self, other: &Self, selector: &S) -> bool {
+        (**self).eq_selected(other, selector)
+    }
+}
+
+impl<S, T: SelectedHash<S> + ?Sized> SelectedHash<S> for Box<T> {
+    fn hash_selected<H: std::hash::Hasher>(&self, selector: &S, state: &mut H) {
+        (**self).hash_selected(selector, state);
+    }
+}
+
+/// Cloning of the fields enabled in a selector of type `S` only, the others
+/// being set to their default, e.g. to hand a trimmed copy of a large struct
+/// to a background job. `always` fields are cloned. Implemented with
+/// `#[serialize_fields(clone)]`, and for containers of such structs.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// #[derive(SerializeFields, Serialize)]
+/// #[serialize_fields(clone)]
+/// struct Report {
+///     id: u32,
+///     title: String,
+///     rows: Vec<String>,
+/// }
+///
+/// let report = Report { id: 1, title: "Q3".into(), rows: vec!["a".into(); 1000] };
+/// let selector: ReportSerializeFieldSelector = "id,title".parse().unwrap();
+///
+/// let trimmed = report.clone_selected(&selector);
+/// assert_eq!((trimmed.id, trimmed.title.as_str(), trimmed.rows.len()), (1, "Q3", 0));
+/// ```
+pub trait CloneSelected<S>: Sized {
+    /// A copy of `self` with only the fields enabled in `selector`, recursing
+    /// into nested structs.
+    fn clone_selected(&self, selector: &S) -> Self;
+}
+
+impl<S, T: CloneSelected<S>> CloneSelected<S> for Option<T> {
+    fn clone_selected(&self, selector: &S) -> Self {
+        self.as_ref().map(|value| value.clone_selected(selector))
+    }
+}
+
+impl<S, T: CloneSelected<S>> CloneSelected<S> for Vec<T> {
+    fn clone_selected(&self, selector: &S) -> Self {
+        self.iter().map(|value| value.clone_selected(selector)).collect()
+    }
+}
+
+impl<S, T: CloneSelected<S>, const N: usize> CloneSelected<S> for [T; N] {
+    fn clone_selected(&self, selector: &S) -> Self {
+        self.each_ref().map(|value| value.clone_selected(selector))
+    }
+}
+
+impl<S, T: CloneSelected<S>> CloneSelected<S> for Box<T> {
+    fn clone_selected(&self, selector: &S) -> Self {
+        Box::new((**self).clone_selected(selector))
+    }
+}
+
+/// Field selection for a type from another crate, implemented on a local
+/// struct mirroring its fields with
+/// `#[serialize_fields(remote = "other_crate::Type")]`, as with serde's
+/// remote derive. The mirrored fields must match the remote ones in name and
+/// type, and be visible to the mirror.
+///
+/// Values of the remote type are serialized with [`SerializeRemoteFields`].
+/// Remote types cannot be nested in other deriving structs.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// mod other_crate {
+///     pub struct Money {
+///         pub amount: i64,
+///         pub currency: String,
+///     }
+/// }
+///
+/// #[derive(SerializeFields)]
+/// #[serialize_fields(remote = "other_crate::Money")]
+/// struct MoneyDef {
+///     amount: i64,
+///     currency: String,
+/// }
+///
+/// use serialize_fields::SerializeRemoteFields;
+///
+/// let price = other_crate::Money { amount: 1250, currency: "EUR".into() };
+/// let selector: MoneyDefSerializeFieldSelector = "amount".parse().unwrap();
+/// assert_eq!(
+///     serde_json::to_string(&SerializeRemoteFields::<MoneyDef>(&price, &selector)).unwrap(),
+///     r#"{"amount":1250}"#
+/// );
+/// ```
+pub trait RemoteFields {
+    /// The type from another crate whose fields are mirrored.
+    type Remote;
+    /// The type of field selector for the remote type.
+    type FieldSelector: FieldSelector;
+
+    /// Serialize `data` with the fields enabled in `field_selector`, as
+    /// changed by `options`.
+    #[doc(hidden)]
+    fn __serialize_remote_with<__S>(
+        data: &Self::Remote,
+        field_selector: &Self::FieldSelector,
+        options: &SerializeOptions<'_>,
+        __serializer: __S,
+    ) -> Result<__S::Ok, __S::Error>
+    where
+        __S: serde::Serializer;
+
+    /// Checks at compile time that the mirrored fields have the remote types.
+    #[doc(hidden)]
+    fn __assert_fields(&self, remote: &Self::Remote);
+}
+
+/// [`SerializeFields`] for a value of a remote type, through the mirror `M`
+/// implementing [`RemoteFields`].
+pub struct SerializeRemoteFields<'a, M: RemoteFields>(pub &'a M::Remote, pub &'a M::FieldSelector);
+
+impl<M: RemoteFields> serde::Serialize for SerializeRemoteFields<'_, M> {
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: serde::Serializer,
+    {
+        M::__serialize_remote_with(self.0, self.1, &SerializeOptions::new(), serializer)
+    }
+}
+
+fn dedup_columns(columns: Vec<&'static str>) -> Vec<&'static str> {
+    let mut unique = Vec::with_capacity(columns.len());
+    for column in columns {
+        if !unique.contains(&column) {
+            unique.push(column);
+        }
+    }
+    unique
+}
+
+/// A SQL column returned by [`FieldSelector::to_sql_column_refs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SqlColumn {
+    /// The table or alias qualifying the column, if any.
+    pub table: Option<&'static str>,
+    /// The column name.
+    pub column: &'static str,
+}
+
+impl SqlColumn {
+    /// Split `users.user_name` at its last dot; an unqualified name has no table.
+    pub fn parse(qualified: &'static str) -> Self {
+        match qualified.rsplit_once('.') {
+            Some((table, column)) => SqlColumn {
+                table: Some(table),
+                column,
+            },
+            None => SqlColumn {
+                table: None,
+                column: qualified,
+            },
+        }
+    }
+}
+
+/// Helper trait for field selectors to provide common functionality.
+///
+/// This trait is automatically implemented for all generated field selectors.
+pub trait FieldSelector {
+    /// Every leaf field path of the selected struct, in dot notation.
+    ///
+    /// Useful to validate a client-provided field list or to document which
+    /// fields an endpoint exposes.
+    const FIELD_PATHS: &'static [&'static str];
+
+    /// Fully qualified name of the selected struct, e.g. `my_app::models::User`.
+    ///
+    /// Built from the module path and struct name at compile time, so unlike
+    /// [`std::any::type_name`] it is stable across compiler versions.
+    const TYPE_NAME: &'static str;
+
+    /// Leaf paths accepted by `enable`, i.e. [`Self::FIELD_PATHS`] without
+    /// sensitive fields. Used for "did you mean" suggestions.
+    #[doc(hidden)]
+    const __SELECTABLE_PATHS: &'static [&'static str];
+
+    /// Create a new selector with all fields disabled.
+    fn new() -> Self;
+
+    /// Create a selector with only the fields marked
+    /// `#[serialize_fields(default)]` enabled.
+    fn with_defaults() -> Self;
+
+    /// Create a selector with every non-sensitive field enabled, recursively.
+    fn all() -> Self;
+
+    /// Enable every non-sensitive field, recursing into nested selectors.
+    fn enable_all(&mut self);
+
+    /// Create a selector with every non-sensitive field enabled except the
+    /// given dot paths.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Everything but the password hash and the whole settings struct
+    /// let selector = UserSerializeFieldSelector::all_except(&["password_hash", "settings"]);
+    /// ```
+    fn all_except(excluded: &[&str]) -> Self
+    where
+        Self: Sized,
+    {
+        let mut selector = Self::all();
+        for field in excluded {
+            selector.disable_dot_hierarchy(field);
+        }
+        selector
+    }
+
+    /// Enable a field using dot notation.
+    ///
+    /// Unknown paths are ignored, and reported to the hook set with
+    /// [`set_unknown_field_hook`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// selector.enable_dot_hierarchy("name");           // Simple field
+    /// selector.enable_dot_hierarchy("profile.bio");    // Nested field
+    /// selector.enable_dot_hierarchy("posts.title");    // Field in collection
+    /// ```
+    fn enable_dot_hierarchy(&mut self, field: &str);
+
+    /// Enable a field using a slice of field names.
+    ///
+    /// This is useful when you already have the field path split.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// selector.enable(&["name"]);                  // Simple field
+    /// selector.enable(&["profile", "bio"]);       // Nested field
+    /// ```
+    fn enable(&mut self, field_hierarchy: &[&str]);
+
+    /// Enable a field using dot notation, failing on unknown paths.
+    ///
+    /// Unlike [`enable_dot_hierarchy`](Self::enable_dot_hierarchy), which ignores
+    /// paths it does not recognise, this reports the first unknown segment,
+    /// including a nested suffix on a plain field such as `id.invalid_nested`.
+    /// Sensitive fields are reported as unknown.
+    fn try_enable_dot_hierarchy(&mut self, field: &str) -> Result<(), UnknownField>
+    where
+        Self: Sized,
+    {
+        let split: Vec<&str> = field.split('.').collect();
+        self.try_enable(&split)
+    }
+
+    /// Enable a field using a slice of field names, failing on unknown paths.
+    fn try_enable(&mut self, field_hierarchy: &[&str]) -> Result<(), UnknownField>
+    where
+        Self: Sized,
+    {
+        Self::__validate_path(field_hierarchy).map_err(|at_segment| {
+            let path = field_hierarchy.join(".");
+            UnknownField {
+                suggestion: suggest::closest_path(&path, Self::__SELECTABLE_PATHS),
+                path,
+                at_segment,
+            }
+        })?;
+        self.enable(field_hierarchy);
+        Ok(())
+    }
+
+    /// Enable a field using dot notation, like
+    /// [`enable_dot_hierarchy`](Self::enable_dot_hierarchy), except for the
+    /// leaf fields `policy` vetoes. Returns the vetoed leaf paths.
+    ///
+    /// The policy is asked about each path the field enables, as listed by
+    /// [`enabled_paths`](Self::enabled_paths): the leaves `profile.bio` and
+    /// `profile.phone` for `profile.*`, or `profile` itself for a bare nested
+    /// field.
+    fn enable_with_policy<P>(&mut self, field: &str, policy: &P) -> Vec<String>
+    where
+        Self: Sized,
+        P: FieldAccessPolicy + ?Sized,
+    {
+        let mut requested = Self::new();
+        requested.enable_dot_hierarchy(field);
+        let mut denied = Vec::new();
+        for path in requested.enabled_paths() {
+            if policy.allows(Self::TYPE_NAME, &path) {
+                self.enable_dot_hierarchy(&path);
+            } else {
+                denied.push(path);
+            }
+        }
+        denied
+    }
+
+    /// Disable a field using dot notation.
+    ///
+    /// Nested selectors left without any enabled field are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// selector.disable_dot_hierarchy("email");          // Simple field
+    /// selector.disable_dot_hierarchy("profile");        // Whole nested struct
+    /// selector.disable_dot_hierarchy("profile.bio");    // Nested field
+    /// ```
+    fn disable_dot_hierarchy(&mut self, field: &str);
+
+    /// Disable a field using a slice of field names.
+    fn disable(&mut self, field_hierarchy: &[&str]);
+
+    /// Enable every field enabled in `other`, recursively (set union).
+    fn merge(&mut self, other: &Self);
+
+    /// Keep only the fields enabled in both selectors, recursively (set
+    /// intersection).
+    fn intersect(&mut self, other: &Self);
+
+    /// Remove every field enabled in `other`, recursively (set difference).
+    fn subtract(&mut self, other: &Self);
+
+    /// Check a split path without enabling it.
+    ///
+    /// Returns the index of the first segment that is not a selectable field.
+    /// Sensitive fields are not selectable here.
+    #[doc(hidden)]
+    fn __validate_path(field_hierarchy: &[&str]) -> Result<(), usize>
+    where
+        Self: Sized;
+
+    /// The canonical dot path for a split path written with output keys
+    /// (`rename`), field names or aliases, if it names a field.
+    #[doc(hidden)]
+    fn __canonical_path(path: &[&str]) -> Option<String>
+    where
+        Self: Sized;
+
+    /// Remove nested selectors that have nothing enabled, recursively.
+    fn prune(&mut self);
+
+    /// Normalize the selector so that selectors producing the same output
+    /// compare and hash equal: empty nested selectors are dropped and `always`
+    /// leaves are cleared. Aliases are resolved when fields are enabled.
+    fn canonicalize(&mut self);
+
+    /// Whether no field is enabled.
+    fn is_empty(&self) -> bool;
+
+    /// Number of leaf fields enabled, recursively.
+    fn enabled_leaf_count(&self) -> usize;
+
+    /// Check that every field marked `#[serialize_fields(required)]` is
+    /// serialized, for consumers that break without them.
+    ///
+    /// Required fields of a nested struct are only checked when the nested
+    /// field is enabled. `always` fields are always present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use serialize_fields::{FieldSelector, SerializeFields};
+    /// # use serde::Serialize;
+    /// #[derive(SerializeFields, Serialize)]
+    /// struct Event {
+    ///     #[serialize_fields(required)]
+    ///     id: u64,
+    ///     #[serialize_fields(required, rename = "type")]
+    ///     kind: String,
+    ///     payload: String,
+    /// }
+    ///
+    /// let selector: EventSerializeFieldSelector = "id,payload".parse().unwrap();
+    /// let error = selector.validate_required().unwrap_err();
+    /// assert_eq!(error.paths, ["kind"]);
+    /// assert_eq!(error.to_string(), "required fields are not selected: `kind`");
+    /// ```
+    fn validate_required(&self) -> Result<(), MissingRequiredFields>
+    where
+        Self: Sized,
+    {
+        let mut paths = Vec::new();
+        self.__collect_missing_required("", &mut paths);
+        if paths.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingRequiredFields { paths })
+        }
+    }
+
+    #[doc(hidden)]
+    fn __collect_missing_required(&self, prefix: &str, missing: &mut Vec<String>);
+
+    /// Number of segments in the longest enabled path; `0` when empty.
+    fn depth(&self) -> usize;
+
+    /// Names of the nested fields that will be serialized with some of their
+    /// fields, in declaration order, e.g. to decide which relations to join or
+    /// eager-load.
+    ///
+    /// Nested fields marked `always` are listed even when not enabled.
+    fn active_nested(&self) -> Vec<&'static str>;
+
+    /// Encode the selector compactly: one presence bit per field in declaration
+    /// order, with the bits of each enabled nested selector after its own.
+    ///
+    /// Both sides must use the same struct definition, since the encoding
+    /// carries no field names.
+    fn to_bytes(&self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut writer = __private::BitWriter::new();
+        self.__write_bits(&mut writer);
+        writer.into_bytes()
+    }
+
+    /// Decode a selector encoded by [`to_bytes`](Self::to_bytes).
+    ///
+    /// This restores sensitive fields too, so only decode trusted input.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, SelectorDecodeError>
+    where
+        Self: Sized,
+    {
+        let mut reader = __private::BitReader::new(bytes);
+        let selector = Self::__read_bits(&mut reader)?;
+        reader.finish()?;
+        Ok(selector)
+    }
+
+    /// Build a selector from arbitrary bytes, for property and fuzz tests.
+    ///
+    /// The bytes are read as in [`from_bytes`](Self::from_bytes), except that
+    /// missing bits count as disabled and leftover bytes are ignored, so every
+    /// input maps to a selector and every selector, sensitive fields included,
+    /// is reachable. This is what an `Arbitrary` impl or a `proptest` strategy
+    /// needs:
+    ///
+    /// ```ignore
+    /// // arbitrary
+    /// let selector = UserSerializeFieldSelector::from_entropy(u.bytes(8)?);
+    /// // proptest
+    /// vec(any::<u8>(), 0..8).prop_map(|bytes| UserSerializeFieldSelector::from_entropy(&bytes))
+    /// ```
+    ///
+    /// Unlike the parsers and `enable`, it turns on sensitive fields without
+    /// an explicit `enable_sensitive` call, and serializing them still calls
+    /// the sensitive access hook. It is therefore hidden from the
+    /// documentation and must never be fed request data.
+    #[doc(hidden)]
+    fn from_entropy(bytes: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        let mut reader = __private::BitReader::lenient(bytes);
+        Self::__read_bits(&mut reader).expect("lenient reads never fail")
+    }
+
+    /// Create a selector enabling every field present in `shape`, an example
+    /// of the wanted output such as `{"id": 0, "author": {"name": ""}}`.
+    ///
+    /// Keys are serialized names; unknown keys and sensitive fields are
+    /// ignored. Objects, and arrays of objects, enable the nested fields they
+    /// hold, and any other value under a nested field enables it in full.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use serialize_fields::{FieldSelector, SerializeFields};
+    /// # use serde::Serialize;
+    /// #[derive(SerializeFields, Serialize)]
+    /// struct Post {
+    ///     id: u32,
+    ///     title: String,
+    ///     body: String,
+    ///     comments: Vec<Comment>,
+    /// }
+    ///
+    /// #[derive(SerializeFields, Serialize)]
+    /// struct Comment {
+    ///     author: String,
+    ///     text: String,
+    /// }
+    ///
+    /// let shape = serde_json::json!({"id": 1, "comments": [{"author": "alice"}]});
+    /// let selector = PostSerializeFieldSelector::from_json_shape(&shape);
+    /// assert_eq!(selector.enabled_paths(), ["id", "comments.author"]);
+    /// ```
+    fn from_json_shape(shape: &serde_json::Value) -> Self
+    where
+        Self: Sized,
+    {
+        let mut selector = Self::new();
+        __private::enable_json_shape(&mut selector, shape);
+        selector
+    }
+
+    #[doc(hidden)]
+    fn __enable_json_shape(&mut self, shape: &serde_json::Map<String, serde_json::Value>);
+
+    #[doc(hidden)]
+    fn __write_bits(&self, writer: &mut __private::BitWriter);
+
+    #[doc(hidden)]
+    fn __read_bits(reader: &mut __private::BitReader<'_>) -> Result<Self, SelectorDecodeError>
+    where
+        Self: Sized;
+
+    /// A key identifying the selected struct and the set of enabled paths.
+    ///
+    /// The key is stable across processes and releases of this crate, and does
+    /// not depend on the order in which fields were enabled, so it can key
+    /// shared response caches or memoized projections.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let a: UserSerializeFieldSelector = "id,name".parse()?;
+    /// let b: UserSerializeFieldSelector = "name,id".parse()?;
+    /// assert_eq!(a.cache_key(), b.cache_key());
+    /// ```
+    fn cache_key(&self) -> u64
+    where
+        Self: Sized,
+    {
+        let mut paths = self.enabled_paths();
+        paths.sort_unstable();
+
+        let mut hasher = fnv::Fnv1a::new();
+        hasher.write(Self::TYPE_NAME.as_bytes());
+        for path in &paths {
+            // Paths never contain NUL, so it separates them unambiguously
+            hasher.write(&[0]);
+            hasher.write(path.as_bytes());
+        }
+        hasher.finish()
+    }
+
+    /// The JSON schema of `T` with only the properties this selector enables.
+    ///
+    /// Properties of disabled fields are removed, recursively through nested
+    /// structs, and nothing is marked `required`, since a selected field may
+    /// still be skipped by serde attributes on `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut generator = schemars::SchemaGenerator::default();
+    /// let schema = selector.filtered_schema::<User>(&mut generator);
+    /// ```
+    #[cfg(feature = "schemars")]
+    fn filtered_schema<T>(&self, generator: &mut schemars::SchemaGenerator) -> schemars::Schema
+    where
+        Self: Sized,
+        T: schemars::JsonSchema + SerializeFieldsTrait<FieldSelector = Self>,
+    {
+        let known = generator.definitions().keys().cloned().collect();
+        let mut schema = T::json_schema(generator).to_value();
+        schema::filter_struct_schema(&mut schema, generator, &mut |properties| {
+            self.__filter_schema_properties(properties, generator)
+        });
+        schema::prune_definitions(generator, &known, &schema);
+        schema.try_into().expect("a filtered schema is still an object")
+    }
+
+    /// Drop `required` from the schemas of nested selectable structs.
+    #[cfg(feature = "schemars")]
+    #[doc(hidden)]
+    fn __relax_schema_properties(
+        properties: &mut serde_json::Map<String, serde_json::Value>,
+        generator: &schemars::SchemaGenerator,
+    ) where
+        Self: Sized;
+
+    /// Keep only the properties of enabled fields, recursing into nested ones.
+    #[cfg(feature = "schemars")]
+    #[doc(hidden)]
+    fn __filter_schema_properties(
+        &self,
+        properties: &mut serde_json::Map<String, serde_json::Value>,
+        generator: &schemars::SchemaGenerator,
+    );
+
+    /// SQL columns of the selected fields, for a `SELECT` matching the
+    /// projection.
+    ///
+    /// Columns come from `#[serialize_fields(column = "...")]`, or default to
+    /// the field name, prefixed by the struct's `table`. Fields marked
+    /// `always` are always included, and a column shared by several fields is
+    /// listed once.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let columns = selector.to_sql_columns().join(", ");
+    /// let sql = format!("SELECT {columns} FROM users JOIN profiles ON ...");
+    /// ```
+    fn to_sql_columns(&self) -> Vec<&'static str>
+    where
+        Self: Sized,
+    {
+        let mut columns = Vec::new();
+        self.__collect_sql_columns(true, &mut columns);
+        dedup_columns(columns)
+    }
+
+    /// SQL columns of the enabled fields, for the `SET` list of an `UPDATE`
+    /// applying the same mask as [`UpdateFields`].
+    ///
+    /// Unlike [`to_sql_columns`](Self::to_sql_columns), fields marked `always`
+    /// are only listed when enabled, since they are read unconditionally but
+    /// should not be written unless asked for.
+    fn to_update_columns(&self) -> Vec<&'static str>
+    where
+        Self: Sized,
+    {
+        let mut columns = Vec::new();
+        self.__collect_sql_columns(false, &mut columns);
+        dedup_columns(columns)
+    }
+
+    /// [`to_sql_columns`](Self::to_sql_columns) split into table and column,
+    /// for query builders that take them separately.
+    ///
+    /// The crate has no `sea-query` feature and no `apply_to_select`: the
+    /// loop below is all it takes to add the columns to a select.
+    ///
+    /// # Examples
+    ///
+    /// With sea-query:
+    ///
+    /// ```ignore
+    /// for SqlColumn { table, column } in selector.to_sql_column_refs() {
+    ///     match table {
+    ///         Some(table) => query.column((Alias::new(table), Alias::new(column))),
+    ///         None => query.column(Alias::new(column)),
+    ///     };
+    /// }
+    /// ```
+    fn to_sql_column_refs(&self) -> Vec<SqlColumn>
+    where
+        Self: Sized,
+    {
+        self.to_sql_columns().into_iter().map(SqlColumn::parse).collect()
+    }
+
+    #[doc(hidden)]
+    fn __collect_sql_columns(&self, with_always: bool, columns: &mut Vec<&'static str>);
+
+    /// Dot paths of the serialized leaf fields, using their serialized names.
+    ///
+    /// Unlike [`enabled_paths`](Self::enabled_paths), this lists what ends up
+    /// in the output: renamed fields use their serialized name, and fields
+    /// marked `always` are included. Use it as a field mask for the store
+    /// holding the serialized documents.
+    fn to_path_list(&self) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        self.to_path_list_with(&PathListFormat::DOTTED)
+    }
+
+    /// [`to_path_list`](Self::to_path_list) in another format, such as
+    /// [`PathListFormat::FIRESTORE`].
+    fn to_path_list_with(&self, format: &PathListFormat) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        let mut paths: Vec<String> = Vec::new();
+        self.__visit_output_paths(true, &mut Vec::new(), &mut |path| {
+            let path = format.format(path);
+            // Paths stopped at a sequence repeat for each of its fields
+            if paths.last() != Some(&path) {
+                paths.push(path);
+            }
+        });
+        paths
+    }
+
+    /// JSONPath expressions for the serialized leaf fields, such as
+    /// `$.profile.bio` or `$.posts[*].title`, for fetching part of a stored
+    /// document, e.g. with RedisJSON's `JSON.GET key path...`.
+    ///
+    /// Paths follow [`to_path_list`](Self::to_path_list): serialized names,
+    /// with `always` fields included. Names that are not plain identifiers use
+    /// bracket notation, as in `$['display-name']`.
+    fn to_json_paths(&self) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        let mut paths = Vec::new();
+        self.__visit_output_paths(true, &mut Vec::new(), &mut |path| {
+            paths.push(path_list::json_path(path));
+        });
+        paths
+    }
+
+    /// Visit the serialized path of each enabled leaf field, and of `always`
+    /// fields with `with_always`.
+    #[doc(hidden)]
+    fn __visit_output_paths(
+        &self,
+        with_always: bool,
+        path: &mut Vec<__private::PathSegment>,
+        visit: &mut dyn FnMut(&[__private::PathSegment]),
+    );
+
+    /// The `_source` filter of an Elasticsearch search fetching exactly the
+    /// fields the selector serializes, using their serialized names.
+    ///
+    /// The shorter of the two forms is used: `{"includes": [...]}` listing the
+    /// serialized fields, or `{"excludes": [...]}` listing the others, so a
+    /// selector built with [`all_except`](Self::all_except) stays an exclusion
+    /// list. A nested field that is not serialized is excluded as a whole, and
+    /// one enabled without any of its own fields is included as a whole, since
+    /// it still serializes as an empty object.
+    /// When nothing is serialized the filter is `false`, which skips
+    /// `_source` entirely.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let body = json!({
+    ///     "query": query,
+    ///     "_source": selector.to_es_source_filter(),
+    /// });
+    /// ```
+    fn to_es_source_filter(&self) -> serde_json::Value
+    where
+        Self: Sized,
+    {
+        let mut includes = Vec::new();
+        let mut excludes = Vec::new();
+        self.__collect_source_paths("", &mut includes, &mut excludes);
+        if includes.is_empty() {
+            serde_json::Value::Bool(false)
+        } else if excludes.len() < includes.len() {
+            serde_json::json!({ "excludes": excludes })
+        } else {
+            serde_json::json!({ "includes": includes })
+        }
+    }
+
+    #[doc(hidden)]
+    fn __collect_source_paths(
+        &self,
+        prefix: &str,
+        includes: &mut Vec<String>,
+        excludes: &mut Vec<String>,
+    );
+
+    /// Wrap the selector in a [`FrozenSelector`] that is cheap to clone and share.
+    fn freeze(self) -> FrozenSelector<Self>
+    where
+        Self: Sized,
+    {
+        FrozenSelector::new(self)
+    }
+
+    /// Dot paths of the enabled leaf fields, in declaration order.
+    fn enabled_paths(&self) -> Vec<String>;
+
+    /// Whether the field at the dot path is enabled, checked at runtime.
+    ///
+    /// This is the runtime counterpart of the [`contains!`](crate::contains)
+    /// macro, for paths received as strings.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// selector.contains_path("profile.bio");    // Nested field
+    /// selector.contains_path("profile");        // Any part of profile enabled
+    /// ```
+    fn contains_path(&self, field: &str) -> bool;
+
+    /// Whether the field at a split path is enabled.
+    fn contains(&self, field_hierarchy: &[&str]) -> bool;
+
+    /// The nested selector at a dot path, such as `"profile"`.
+    ///
+    /// An empty path returns the selector itself. Returns `None` for unknown
+    /// paths, plain fields, and nested fields that are not enabled.
+    fn at_path(&self, field: &str) -> Option<&dyn DynFieldSelector>
+    where
+        Self: Sized + 'static,
+    {
+        if field.is_empty() {
+            return Some(self);
+        }
+        let split: Vec<&str> = field.split('.').collect();
+        self.at(&split)
+    }
+
+    /// Mutable counterpart of [`at_path`](Self::at_path).
+    fn at_path_mut(&mut self, field: &str) -> Option<&mut dyn DynFieldSelector>
+    where
+        Self: Sized + 'static,
+    {
+        if field.is_empty() {
+            return Some(self);
+        }
+        let split: Vec<&str> = field.split('.').collect();
+        self.at_mut(&split)
+    }
+
+    /// The nested selector at a split path; an empty path returns `self`.
+    fn at(&self, field_hierarchy: &[&str]) -> Option<&dyn DynFieldSelector>;
+
+    /// Mutable counterpart of [`at`](Self::at).
+    fn at_mut(&mut self, field_hierarchy: &[&str]) -> Option<&mut dyn DynFieldSelector>;
+
+    /// Whether every field enabled here is also enabled in `other`.
+    fn is_subset_of(&self, other: &Self) -> bool;
+
+    /// Whether every field enabled in `other` is also enabled here.
+    fn is_superset_of(&self, other: &Self) -> bool {
+        other.is_subset_of(self)
+    }
+}
+
+/// Field selectors with a generated typed builder.
+///
+/// This trait is automatically implemented for all generated field selectors.
+/// Nested builder methods use it to reach the builder of the nested selector.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String, profile: Profile }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Profile { bio: String, avatar_url: String }
+/// let selector = UserSerializeFieldSelector::builder()
+///     .id()
+///     .profile(|p| p.bio().avatar_url())
+///     .build();
+///
+/// assert!(selector.id.is_some());
+/// assert!(selector.name.is_none());
+/// ```
+pub trait BuildSelector: FieldSelector + Sized {
+    /// The builder type, convertible to and from the selector.
+    type Builder: From<Self> + Into<Self>;
+
+    /// Start building a selector with all fields disabled.
+    fn builder() -> Self::Builder {
+        Self::new().into()
+    }
+}
+
+/// Utility functions for working with field selectors.
+pub mod utils {
+    /// Parse a comma-separated list of field names.
+    ///
+    /// This is useful for parsing query parameters or configuration strings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serialize_fields::utils::parse_field_list;
+    ///
+    /// let fields = parse_field_list("id,name,profile.bio");
+    /// assert_eq!(fields, vec!["id", "name", "profile.bio"]);
     /// ```
     pub fn parse_field_list(fields: &str) -> Vec<&str> {
         fields
@@ -322,9 +1955,404 @@ pub mod utils {
             .collect()
     }
 
+    /// One entry of a field list, as returned by [`parse_field_specs`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FieldSpec<'a> {
+        /// A field to enable, e.g. `profile.bio`.
+        Include(&'a str),
+        /// A field to remove, written `-password` or `!settings.privacy_level`.
+        Exclude(&'a str),
+    }
+
+    /// Parse a comma-separated list of field names, recognising exclusions.
+    ///
+    /// Entries prefixed with `-` or `!` are exclusions, following the
+    /// Elasticsearch and JSON:API conventions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serialize_fields::utils::{FieldSpec, parse_field_specs};
+    ///
+    /// let specs = parse_field_specs("id, -password, !settings.privacy_level");
+    /// assert_eq!(
+    ///     specs,
+    ///     vec![
+    ///         FieldSpec::Include("id"),
+    ///         FieldSpec::Exclude("password"),
+    ///         FieldSpec::Exclude("settings.privacy_level"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn parse_field_specs(fields: &str) -> Vec<FieldSpec<'_>> {
+        parse_field_list(fields).into_iter().filter_map(field_spec).collect()
+    }
+
+    /// Read one trimmed, non-empty entry of a field list. A `-` or `!` with
+    /// no path after it is ignored, like an empty entry.
+    fn field_spec(entry: &str) -> Option<FieldSpec<'_>> {
+        match entry.strip_prefix(['-', '!']) {
+            Some(excluded) => {
+                let excluded = excluded.trim_start();
+                (!excluded.is_empty()).then_some(FieldSpec::Exclude(excluded))
+            }
+            None => Some(FieldSpec::Include(entry)),
+        }
+    }
+
+    /// Parse a field list into a selector, rejecting unknown or malformed paths.
+    ///
+    /// This accepts the same syntax as [`create_selector_from_list`], including
+    /// `*`/`**` wildcards and `-`/`!` exclusions, but reports every invalid
+    /// entry instead of ignoring it. Sensitive fields count as unknown. Generated
+    /// selectors implement `FromStr` with this function.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use serialize_fields::SerializeFields;
+    /// # use serde::Serialize;
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct User { id: u32, name: String }
+    /// use serialize_fields::{InvalidPathReason, utils::parse_selector};
+    ///
+    /// let selector: UserSerializeFieldSelector = parse_selector("id,name").unwrap();
+    /// assert_eq!(selector.to_string(), "id,name");
+    ///
+    /// let error = parse_selector::<UserSerializeFieldSelector>("id, nmae").unwrap_err();
+    /// assert_eq!(error.errors[0].path, "nmae");
+    /// assert_eq!(error.errors[0].position, 4);
+    /// assert_eq!(
+    ///     error.errors[0].reason,
+    ///     InvalidPathReason::Unknown { at_segment: 0, suggestion: Some("name".to_string()) }
+    /// );
+    /// ```
+    pub fn parse_selector<T>(fields: &str) -> Result<T, crate::SelectorParseError>
+    where
+        T: crate::FieldSelector,
+    {
+        let mut errors = Vec::new();
+        let mut offset = 0;
+        for entry in fields.split(',') {
+            let position = offset + (entry.len() - entry.trim_start().len());
+            offset += entry.len() + 1;
+
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let path = match field_spec(entry) {
+                Some(FieldSpec::Include(path) | FieldSpec::Exclude(path)) => path,
+                None => continue,
+            };
+
+            // The path ends the entry, after any exclusion prefix
+            errors.extend(check_path::<T>(path, position + entry.len() - path.len()));
+        }
+
+        if errors.is_empty() {
+            Ok(create_selector_from_list(fields))
+        } else {
+            Err(crate::SelectorParseError { errors })
+        }
+    }
+
+    /// Parse OData `$select` and `$expand` query options into a selector.
+    ///
+    /// `$select` lists the fields of a level, with `/` or `.` for nested
+    /// paths, and `$expand` lists nested structs with their own options in
+    /// parentheses, separated by `;`. A level without `$select` selects all of
+    /// its plain fields, as `*` does, and expanded fields are included even if
+    /// not selected. Other options, such as `$filter` or `$top`, are ignored.
+    /// The input must already be percent-decoded.
+    ///
+    /// Unknown fields and syntax errors are reported with their byte position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use serialize_fields::SerializeFields;
+    /// # use serde::Serialize;
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct User { id: u32, name: String, profile: Profile }
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct Profile { bio: String, avatar_url: String }
+    /// use serialize_fields::utils::parse_odata;
+    ///
+    /// let selector: UserSerializeFieldSelector =
+    ///     parse_odata("$select=name,profile&$expand=profile($select=bio)").unwrap();
+    /// assert_eq!(selector.to_string(), "name,profile.bio");
+    ///
+    /// let selector: UserSerializeFieldSelector = parse_odata("$select=id&$expand=profile").unwrap();
+    /// assert_eq!(selector.to_string(), "id,profile.bio,profile.avatar_url");
+    /// ```
+    pub fn parse_odata<T>(query: &str) -> Result<T, crate::SelectorParseError>
+    where
+        T: crate::FieldSelector,
+    {
+        crate::odata::parse(query)
+    }
+
+    /// Parse a field mask in the Google API partial response syntax.
+    ///
+    /// Fields are separated by commas, nested with `/` or `.`, and grouped
+    /// with parentheses: `items(id,author/name)` selects `items.id` and
+    /// `items.author.name`. `*` selects every plain field of a level, and a
+    /// nested field without subfields, such as `items`, all of its own.
+    ///
+    /// Unknown fields and syntax errors, such as an unclosed parenthesis, are
+    /// reported with their byte position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use serialize_fields::SerializeFields;
+    /// # use serde::Serialize;
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct Page { items: Item, next_page_token: String }
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct Item { id: u32, name: String }
+    /// use serialize_fields::utils::parse_partial_response;
+    ///
+    /// let selector: PageSerializeFieldSelector =
+    ///     parse_partial_response("items(id,name),next_page_token").unwrap();
+    /// assert_eq!(selector.to_string(), "items.id,items.name,next_page_token");
+    ///
+    /// let error = parse_partial_response::<PageSerializeFieldSelector>("items(id").unwrap_err();
+    /// assert_eq!(error.to_string(), "invalid field selection: expected `)` at position 8");
+    /// ```
+    pub fn parse_partial_response<T>(fields: &str) -> Result<T, crate::SelectorParseError>
+    where
+        T: crate::FieldSelector,
+    {
+        crate::partial_response::parse(fields)
+    }
+
+    /// Parse a GraphQL-style selection set, such as
+    /// `{ id name profile { bio } }`.
+    ///
+    /// Fields with a selection set are nested structs; commas, comments,
+    /// aliases (`label: name`), arguments and `__typename` are accepted and
+    /// ignored, as GraphQL does. Fragments and directives are not supported.
+    ///
+    /// Unknown fields and syntax errors are reported with their byte position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use serialize_fields::SerializeFields;
+    /// # use serde::Serialize;
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct User { id: u32, name: String, profile: Profile }
+    /// # #[derive(SerializeFields, Serialize)]
+    /// # struct Profile { bio: String, avatar_url: String }
+    /// use serialize_fields::utils::parse_graphql;
+    ///
+    /// let selector: UserSerializeFieldSelector = parse_graphql("{ id profile { bio } }").unwrap();
+    /// assert_eq!(selector.to_string(), "id,profile.bio");
+    /// ```
+    pub fn parse_graphql<T>(selection_set: &str) -> Result<T, crate::SelectorParseError>
+    where
+        T: crate::FieldSelector,
+    {
+        crate::graphql::parse(selection_set)
+    }
+
+    /// Build a selector from a tree of selected fields, such as the look-ahead
+    /// of a GraphQL resolver.
+    ///
+    /// `name` returns the name of a node and `children` its selected
+    /// subfields, which are empty for plain fields. Names are matched against
+    /// the serialized keys set with `rename`, which GraphQL schemas usually
+    /// expose, as well as field names and aliases. Nodes that do not name a
+    /// field of the selector, e.g. `__typename` or computed fields resolved
+    /// elsewhere, are ignored, since the GraphQL schema already validated them.
+    /// A nested field whose subfields are all ignored, as in
+    /// `profile { __typename }`, is still enabled, with none of its fields.
+    ///
+    /// The crate has no `async-graphql` or `juniper` feature, and no `From`
+    /// conversion from their look-ahead types; the closures below adapt them.
+    ///
+    /// # Examples
+    ///
+    /// With async-graphql, inside a resolver:
+    ///
+    /// ```ignore
+    /// let selector: UserSerializeFieldSelector = selector_from_tree(
+    ///     ctx.field().selection_set(),
+    ///     |field| field.name(),
+    ///     |field| field.selection_set(),
+    /// );
+    /// ```
+    ///
+    /// With Juniper, from the executor's look-ahead:
+    ///
+    /// ```ignore
+    /// let selector: UserSerializeFieldSelector = selector_from_tree(
+    ///     executor.look_ahead().children(),
+    ///     |field| field.field_original_name(),
+    ///     |field| field.children(),
+    /// );
+    /// ```
+    pub fn selector_from_tree<T, N, I>(
+        roots: impl IntoIterator<Item = N>,
+        name: impl Fn(&N) -> &str,
+        children: impl Fn(&N) -> I,
+    ) -> T
+    where
+        T: crate::FieldSelector,
+        I: IntoIterator<Item = N>,
+    {
+        fn walk<N, I>(
+            nodes: impl IntoIterator<Item = N>,
+            prefix: &str,
+            name: &impl Fn(&N) -> &str,
+            children: &impl Fn(&N) -> I,
+            paths: &mut Vec<(String, bool)>,
+        ) where
+            I: IntoIterator<Item = N>,
+        {
+            for node in nodes {
+                let path = format!("{}{}", prefix, name(&node));
+                let mut nested = children(&node).into_iter().peekable();
+                if nested.peek().is_some() {
+                    walk(nested, &format!("{}.", path), name, children, paths);
+                    // After its children, to tell whether any of them was known
+                    paths.push((path, true));
+                } else {
+                    paths.push((path, false));
+                }
+            }
+        }
+
+        let mut paths = Vec::new();
+        walk(roots, "", &name, &children, &mut paths);
+
+        let mut selector = T::new();
+        let mut enabled: Vec<String> = Vec::new();
+        for (path, has_children) in &paths {
+            let segments: Vec<&str> = path.split('.').collect();
+            let Some(path) = T::__canonical_path(&segments) else {
+                continue;
+            };
+            let prefix = format!("{}.", path);
+            if *has_children && enabled.iter().any(|enabled| enabled.starts_with(&prefix)) {
+                continue;
+            }
+            let segments: Vec<&str> = path.split('.').collect();
+            if T::__validate_path(&segments).is_ok() {
+                selector.enable(&segments);
+                enabled.push(path);
+            }
+        }
+        selector
+    }
+
+    /// Build a selector from the paths of a protobuf `FieldMask`, clamped to
+    /// the fields in `allowed`.
+    ///
+    /// Every path must be selectable; otherwise all invalid ones are reported,
+    /// with their index in `paths` as position, so an RPC can fail with
+    /// `INVALID_ARGUMENT`. A path naming a nested field selects all of it, as
+    /// with `profile.**`. Paths outside `allowed` are silently dropped, and an
+    /// empty mask selects all of `allowed`, following the `FieldMask`
+    /// convention that no mask means every field.
+    ///
+    /// # Examples
+    ///
+    /// In a tonic service:
+    ///
+    /// ```ignore
+    /// let request = request.into_inner();
+    /// let mask = request.read_mask.map(|mask| mask.paths).unwrap_or_default();
+    /// let selector = selector_from_field_mask(&mask, &UserSerializeFieldSelector::all())
+    ///     .map_err(|error| Status::invalid_argument(error.to_string()))?;
+    /// ```
+    pub fn selector_from_field_mask<T, P>(paths: &[P], allowed: &T) -> Result<T, crate::SelectorParseError>
+    where
+        T: crate::FieldSelector + Clone,
+        P: AsRef<str>,
+    {
+        if paths.is_empty() {
+            return Ok(allowed.clone());
+        }
+
+        let paths: Vec<(String, usize)> = paths
+            .iter()
+            .enumerate()
+            .map(|(index, path)| (whole_subtree::<T>(path.as_ref().trim()), index))
+            .collect();
+        let mut selector: T = selector_from_paths(&paths)?;
+        selector.intersect(allowed);
+        Ok(selector)
+    }
+
+    /// `path` followed by `.**` when it names a nested field, so that it
+    /// selects the whole subtree rather than an empty nested selector.
+    pub(crate) fn whole_subtree<T: crate::FieldSelector>(path: &str) -> String {
+        let segments: Vec<&str> = path.split('.').collect();
+        let nested = T::__canonical_path(&segments).is_some_and(|canonical| {
+            let prefix = format!("{}.", canonical);
+            T::__SELECTABLE_PATHS.iter().any(|selectable| selectable.starts_with(&prefix))
+        });
+        if nested {
+            format!("{}.**", path)
+        } else {
+            path.to_string()
+        }
+    }
+
+    /// Check that `path`, found at `position` in the input, is a selectable
+    /// dot path.
+    pub(crate) fn check_path<T: crate::FieldSelector>(
+        path: &str,
+        position: usize,
+    ) -> Option<crate::InvalidPath> {
+        let segments: Vec<&str> = path.split('.').collect();
+        let reason = if segments.iter().any(|segment| segment.is_empty()) {
+            crate::InvalidPathReason::Malformed
+        } else {
+            let at_segment = T::__validate_path(&segments).err()?;
+            crate::InvalidPathReason::Unknown {
+                at_segment,
+                suggestion: crate::suggest::closest_path(path, T::__SELECTABLE_PATHS),
+            }
+        };
+        Some(crate::InvalidPath {
+            path: path.to_string(),
+            position,
+            reason,
+        })
+    }
+
+    /// Enable every path, or report all the ones that are not selectable.
+    pub(crate) fn selector_from_paths<T: crate::FieldSelector>(
+        paths: &[(String, usize)],
+    ) -> Result<T, crate::SelectorParseError> {
+        let errors: Vec<_> = paths
+            .iter()
+            .filter_map(|(path, position)| check_path::<T>(path, *position))
+            .collect();
+        if !errors.is_empty() {
+            return Err(crate::SelectorParseError { errors });
+        }
+
+        let mut selector = T::new();
+        for (path, _) in paths {
+            selector.enable_dot_hierarchy(path);
+        }
+        Ok(selector)
+    }
+
     /// Create a field selector from a list of field names.
     ///
     /// This is a convenience function that combines parsing and enabling fields.
+    /// Exclusions (`-field` or `!field`) are removed after the inclusions are
+    /// enabled. A list made only of exclusions starts from the
+    /// [`with_defaults`](crate::FieldSelector::with_defaults) selector, or from
+    /// every field, as with
+    /// [`FieldSelector::all_except`](crate::FieldSelector::all_except), when
+    /// no field is marked `default`.
     ///
     /// # Examples
     ///
@@ -333,14 +2361,34 @@ pub mod utils {
     ///
     /// let selector: UserSerializeFieldSelector =
     ///     create_selector_from_list("id,name,profile.bio");
+    ///
+    /// // Everything except the password hash
+    /// let selector: UserSerializeFieldSelector =
+    ///     create_selector_from_list("-password_hash");
     /// ```
     pub fn create_selector_from_list<T>(fields: &str) -> T
     where
         T: crate::FieldSelector,
     {
-        let mut selector = T::new();
-        for field in parse_field_list(fields) {
-            selector.enable_dot_hierarchy(field);
+        let specs = parse_field_specs(fields);
+        let only_exclusions = !specs.is_empty()
+            && specs.iter().all(|spec| matches!(spec, FieldSpec::Exclude(_)));
+
+        let mut selector = if only_exclusions {
+            let defaults = T::with_defaults();
+            if defaults.is_empty() { T::all() } else { defaults }
+        } else {
+            T::new()
+        };
+        for spec in &specs {
+            if let FieldSpec::Include(field) = spec {
+                selector.enable_dot_hierarchy(field);
+            }
+        }
+        for spec in &specs {
+            if let FieldSpec::Exclude(field) = spec {
+                selector.disable_dot_hierarchy(field);
+            }
         }
         selector
     }
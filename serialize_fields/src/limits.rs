@@ -0,0 +1,146 @@
+//! Depth and size limits for selectors built from untrusted input.
+
+use crate::{FieldSelector, SelectorParseError};
+use std::fmt;
+
+/// Limits on how deep and how large a selector may be.
+///
+/// Use [`parse`](Self::parse) instead of [`utils::parse_selector`](crate::utils::parse_selector)
+/// for `?fields=` values coming from clients: the raw list is checked before
+/// any parsing work, and the resulting selector is checked again, since
+/// wildcards such as `**` can enable more fields than were listed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String, profile: Profile }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Profile { bio: String }
+/// use serialize_fields::{SelectorLimitError, SelectorLimits};
+///
+/// let limits = SelectorLimits { max_depth: 1, max_fields: 2 };
+///
+/// let selector: UserSerializeFieldSelector = limits.parse("id,name").unwrap();
+/// assert_eq!(selector.to_string(), "id,name");
+///
+/// assert_eq!(
+///     limits.parse::<UserSerializeFieldSelector>("profile.bio"),
+///     Err(SelectorLimitError::TooDeep { depth: 2, max_depth: 1 })
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectorLimits {
+    /// Maximum number of segments in an enabled path; `profile.bio` has 2.
+    pub max_depth: usize,
+    /// Maximum number of enabled leaf fields.
+    pub max_fields: usize,
+}
+
+impl Default for SelectorLimits {
+    /// 8 levels and 256 fields, generous for hand-written field lists.
+    fn default() -> Self {
+        SelectorLimits {
+            max_depth: 8,
+            max_fields: 256,
+        }
+    }
+}
+
+/// Error returned when a selector exceeds its [`SelectorLimits`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorLimitError {
+    /// The field list itself is invalid.
+    Invalid(SelectorParseError),
+    /// An enabled path is deeper than allowed.
+    TooDeep {
+        /// Depth of the deepest path.
+        depth: usize,
+        /// The configured limit.
+        max_depth: usize,
+    },
+    /// More leaf fields are enabled, or listed, than allowed.
+    TooManyFields {
+        /// Number of fields enabled or listed.
+        count: usize,
+        /// The configured limit.
+        max_fields: usize,
+    },
+}
+
+impl SelectorLimits {
+    /// Check an existing selector against the limits.
+    pub fn check<T: FieldSelector>(&self, selector: &T) -> Result<(), SelectorLimitError> {
+        let depth = selector.depth();
+        if depth > self.max_depth {
+            return Err(SelectorLimitError::TooDeep {
+                depth,
+                max_depth: self.max_depth,
+            });
+        }
+        let count = selector.enabled_leaf_count();
+        if count > self.max_fields {
+            return Err(SelectorLimitError::TooManyFields {
+                count,
+                max_fields: self.max_fields,
+            });
+        }
+        Ok(())
+    }
+
+    /// Parse a field list strictly, rejecting it if it exceeds the limits.
+    pub fn parse<T: FieldSelector>(&self, fields: &str) -> Result<T, SelectorLimitError> {
+        // Cheap checks on the raw list first, so abusive input is rejected
+        // before any validation or allocation per entry
+        let mut count = 0;
+        for entry in fields.split(',').filter(|entry| !entry.trim().is_empty()) {
+            count += 1;
+            if count > self.max_fields {
+                return Err(SelectorLimitError::TooManyFields {
+                    count,
+                    max_fields: self.max_fields,
+                });
+            }
+            let depth = entry.split('.').count();
+            if depth > self.max_depth {
+                return Err(SelectorLimitError::TooDeep {
+                    depth,
+                    max_depth: self.max_depth,
+                });
+            }
+        }
+
+        let selector = crate::utils::parse_selector(fields).map_err(SelectorLimitError::Invalid)?;
+        self.check(&selector)?;
+        Ok(selector)
+    }
+}
+
+impl fmt::Display for SelectorLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectorLimitError::Invalid(error) => write!(f, "{}", error),
+            SelectorLimitError::TooDeep { depth, max_depth } => write!(
+                f,
+                "field selection is {} levels deep, at most {} are allowed",
+                depth, max_depth
+            ),
+            SelectorLimitError::TooManyFields { count, max_fields } => write!(
+                f,
+                "field selection has {} fields, at most {} are allowed",
+                count, max_fields
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SelectorLimitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SelectorLimitError::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -4,6 +4,7 @@
 //! - `contains!`: Check if a field path is enabled
 //! - `copy_selected_fields!`: Create structs with conditional field copying
 //! - `filter_field_set!`: Create filtered sets of enabled field paths
+//! - `select!`: Build a selector from a compile-time checked field list
 
 /// Check if a field path is enabled in a field selector.
 ///
@@ -43,7 +44,7 @@ macro_rules! contains {
     // Level 3+: Three or more fields - recursive for simplicity
     ($selector:expr, $field:ident . $($rest:ident).+) => {
         if let Some(ref nested) = $selector.$field {
-            contains!(nested, $($rest).+)
+            $crate::contains!(nested, $($rest).+)
         } else {
             false
         }
@@ -238,7 +239,7 @@ macro_rules! field_path {
 macro_rules! create_field_selector {
     // Main entry point
     ($struct_name:ident { $($fields:tt)* }) => {{
-        let mut selector = <$struct_name as serialize_fields::SerializeFieldsTrait>::FieldSelector::default();
+        let mut selector = <$struct_name as $crate::SerializeFieldsTrait>::FieldSelector::default();
         $crate::create_field_selector!(@fields selector, $($fields)*);
         selector
     }};
@@ -257,4 +258,63 @@ macro_rules! create_field_selector {
         $selector.$field = Some($crate::create_field_selector!($nested_struct { $($nested_fields)* }));
         $crate::create_field_selector!(@fields $selector, $($($rest)*)?);
     };
-}
\ No newline at end of file
+}
+/// Build a field selector from a literal field list, checked at compile time.
+///
+/// Nested structs are selected with `field: { ... }`. Each field becomes a call
+/// on the generated selector builder, so a misspelled field is a compile error
+/// instead of a silently ignored path.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::{SerializeFields, select};
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String, profile: Profile }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Profile { bio: String, stats: Stats }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Stats { followers_count: u32 }
+/// let selector = select!(User { id, profile: { bio, stats: { followers_count } } });
+///
+/// assert!(selector.id.is_some());
+/// assert!(selector.name.is_none());
+/// assert!(serialize_fields::contains!(selector, profile.stats.followers_count));
+/// ```
+///
+/// ```rust,compile_fail
+/// # use serialize_fields::{SerializeFields, select};
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32 }
+/// let selector = select!(User { idd });
+/// ```
+#[macro_export]
+macro_rules! select {
+    // Main entry point
+    ($struct_name:ty { $($fields:tt)* }) => {{
+        let builder = <<$struct_name as $crate::SerializeFieldsTrait>::FieldSelector as $crate::BuildSelector>::builder();
+        let selector: <$struct_name as $crate::SerializeFieldsTrait>::FieldSelector =
+            $crate::select!(@fields builder, $($fields)*).into();
+        selector
+    }};
+
+    // Parse fields recursively
+    (@fields $builder:expr $(,)?) => {
+        $builder
+    };
+
+    // Nested field with its own field list
+    (@fields $builder:expr, $field:ident : { $($nested:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::select!(
+            @fields $builder.$field(|nested| $crate::select!(@fields nested, $($nested)*)),
+            $($($rest)*)?
+        )
+    };
+
+    // Simple field (just identifier)
+    (@fields $builder:expr, $field:ident $(, $($rest:tt)*)?) => {
+        $crate::select!(@fields $builder.$field(), $($($rest)*)?)
+    };
+}
@@ -0,0 +1,135 @@
+//! Parser for OData `$select` and `$expand` query options.
+
+use crate::parse::Cursor;
+use crate::{FieldSelector, InvalidPath, SelectorParseError};
+
+/// Parse `$select=name,profile&$expand=profile($select=bio)` into a selector.
+pub(crate) fn parse<T: FieldSelector>(query: &str) -> Result<T, SelectorParseError> {
+    let mut cursor = Cursor::new(query);
+    options(&mut cursor, "", None)
+        .and_then(|()| cursor.end("end of input"))
+        .map_err(|error| SelectorParseError { errors: vec![error] })?;
+    crate::utils::selector_from_paths(&cursor.paths)
+}
+
+/// Parse the options of one level: the whole query when `close` is `None`,
+/// separated by `&`, or the `;`-separated options inside `name(...)`.
+fn options(cursor: &mut Cursor<'_>, prefix: &str, close: Option<u8>) -> Result<(), InvalidPath> {
+    let separator = if close.is_some() { b';' } else { b'&' };
+    let level_start = cursor.at;
+    let mut selected: Vec<(String, usize)> = Vec::new();
+    let mut has_select = false;
+    let mut expanded: Vec<String> = Vec::new();
+
+    loop {
+        if cursor.peek().is_some() && cursor.peek() != close {
+            let name_start = cursor.at;
+            let name = cursor.take_while(|byte| byte != b'=' && byte != separator && Some(byte) != close);
+            if cursor.peek() == Some(b'=') {
+                cursor.at += 1;
+            }
+            match name.trim() {
+                "$select" => {
+                    has_select = true;
+                    select_list(cursor, separator, close, &mut selected)?;
+                }
+                "$expand" => expand_list(cursor, prefix, separator, close, &mut expanded)?,
+                "" => {
+                    cursor.at = name_start;
+                    return Err(cursor.syntax_error("a query option"));
+                }
+                // Other options such as `$filter` do not affect the selection
+                _ => skip_value(cursor, separator, close),
+            }
+        }
+
+        match cursor.peek() {
+            Some(byte) if byte == separator => cursor.at += 1,
+            _ => break,
+        }
+    }
+
+    if has_select {
+        for (item, position) in selected {
+            // Expanded fields are included by their expansion
+            if !expanded.contains(&item) {
+                cursor.paths.push((format!("{}{}", prefix, item), position));
+            }
+        }
+    } else {
+        cursor.paths.push((format!("{}*", prefix), level_start));
+    }
+    Ok(())
+}
+
+fn select_list(
+    cursor: &mut Cursor<'_>,
+    separator: u8,
+    close: Option<u8>,
+    selected: &mut Vec<(String, usize)>,
+) -> Result<(), InvalidPath> {
+    loop {
+        let (item, position) = property(cursor, separator, close);
+        if item.is_empty() {
+            return Err(cursor.syntax_error("a property name"));
+        }
+        selected.push((item, position));
+        if !cursor.eat(b',') {
+            return Ok(());
+        }
+    }
+}
+
+fn expand_list(
+    cursor: &mut Cursor<'_>,
+    prefix: &str,
+    separator: u8,
+    close: Option<u8>,
+    expanded: &mut Vec<String>,
+) -> Result<(), InvalidPath> {
+    loop {
+        let (item, position) = property(cursor, separator, close);
+        if item.is_empty() {
+            return Err(cursor.syntax_error("a navigation property name"));
+        }
+        let nested_prefix = format!("{}{}.", prefix, item);
+        if cursor.eat(b'(') {
+            options(cursor, &nested_prefix, Some(b')'))?;
+            if !cursor.eat(b')') {
+                return Err(cursor.syntax_error("`)`"));
+            }
+        } else {
+            cursor.paths.push((format!("{}*", nested_prefix), position));
+        }
+        expanded.push(item);
+        if !cursor.eat(b',') {
+            return Ok(());
+        }
+    }
+}
+
+/// A property path, with `/` nesting turned into dots.
+fn property(cursor: &mut Cursor<'_>, separator: u8, close: Option<u8>) -> (String, usize) {
+    let raw = cursor.take_while(|byte| {
+        !matches!(byte, b',' | b'(' | b')') && byte != separator && Some(byte) != close
+    });
+    let trimmed = raw.trim_start();
+    let position = cursor.at - raw.len() + (raw.len() - trimmed.len());
+    (trimmed.trim_end().replace('/', "."), position)
+}
+
+/// Skip the value of an ignored option, including any parentheses in it.
+fn skip_value(cursor: &mut Cursor<'_>, separator: u8, close: Option<u8>) {
+    let mut depth = 0usize;
+    while let Some(byte) = cursor.peek() {
+        if depth == 0 && (byte == separator || Some(byte) == close) {
+            break;
+        }
+        match byte {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        cursor.at += 1;
+    }
+}
@@ -0,0 +1,59 @@
+//! OpenAPI description of the standard `fields` query parameter.
+//!
+//! The parameter is returned as a plain OpenAPI 3 [Parameter Object] so it can
+//! be added to documents built by any generator, for example by pushing it
+//! onto an operation's parameters in a `utoipa` `Modify` impl. There is no
+//! `utoipa` feature: `ToSchema` and `IntoParams` impls for `SerializeFields`
+//! are not provided.
+//!
+//! [Parameter Object]: https://spec.openapis.org/oas/v3.1.0#parameter-object
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use serialize_fields::SerializeFields;
+//! # use serde::Serialize;
+//! # #[derive(SerializeFields, Serialize)]
+//! # struct User { id: u32, name: String }
+//! let parameter = serialize_fields::openapi::fields_parameter::<UserSerializeFieldSelector>("fields");
+//! assert_eq!(parameter["in"], "query");
+//! assert!(parameter["description"].as_str().unwrap().ends_with("`id`, `name`."));
+//! ```
+
+use crate::FieldSelector;
+use serde_json::{Value, json};
+
+/// An optional query parameter named `name` taking a comma-separated list of
+/// the fields of `S`.
+///
+/// The description lists the paths accepted by
+/// [`parse_selector`](crate::utils::parse_selector), without sensitive fields,
+/// and explains wildcards and `-` exclusions. Since aliases, renamed keys,
+/// wildcards and exclusions are accepted too, the item schema only checks the
+/// shape of a path with a `pattern` rather than listing every value.
+pub fn fields_parameter<S: FieldSelector>(name: &str) -> Value {
+    let paths: Vec<String> = S::__SELECTABLE_PATHS.iter().map(|path| format!("`{}`", path)).collect();
+    json!({
+        "name": name,
+        "in": "query",
+        "required": false,
+        "description": format!(
+            "Comma-separated fields to include, in dot notation. \
+             `profile.*` selects every field of `profile`, `profile.**` every \
+             field below it, and `-field` excludes a field. Fields: {}.",
+            paths.join(", ")
+        ),
+        "style": "form",
+        "explode": false,
+        "schema": {
+            "type": "array",
+            "items": {
+                "type": "string",
+                "pattern": PATH_PATTERN,
+            },
+        },
+    })
+}
+
+/// An optional `-` or `!`, then dot-separated segments, none of them empty.
+const PATH_PATTERN: &str = r"^[-!]?[^.,\s]+(\.[^.,\s]+)*$";
@@ -0,0 +1,58 @@
+//! Cursor shared by the GraphQL, partial response and OData parsers.
+
+use crate::{InvalidPath, InvalidPathReason};
+
+/// Position in the input of a parser, and the dot paths it collected.
+pub(crate) struct Cursor<'a> {
+    pub(crate) input: &'a str,
+    pub(crate) at: usize,
+    /// Dot paths to enable, with their position in the input.
+    pub(crate) paths: Vec<(String, usize)>,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Cursor {
+            input,
+            at: 0,
+            paths: Vec::new(),
+        }
+    }
+
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.at).copied()
+    }
+
+    pub(crate) fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    pub(crate) fn take_while(&mut self, keep: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.at;
+        while self.peek().is_some_and(&keep) {
+            self.at += 1;
+        }
+        &self.input[start..self.at]
+    }
+
+    /// Fail with `expected` unless the whole input was read.
+    pub(crate) fn end(&self, expected: &'static str) -> Result<(), InvalidPath> {
+        if self.at < self.input.len() {
+            return Err(self.syntax_error(expected));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn syntax_error(&self, expected: &'static str) -> InvalidPath {
+        InvalidPath {
+            // Skipped escapes may stop inside a multi-byte character
+            path: self.input.get(self.at..).unwrap_or_default().to_string(),
+            position: self.at,
+            reason: InvalidPathReason::Syntax { expected },
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Parser for the Google API partial response syntax, e.g.
+//! `items(id,name),nextPageToken`.
+
+use crate::parse::Cursor;
+use crate::{FieldSelector, InvalidPath, SelectorParseError};
+
+/// Parse a partial response field mask into a selector.
+pub(crate) fn parse<T: FieldSelector>(fields: &str) -> Result<T, SelectorParseError> {
+    let mut cursor = Cursor::new(fields);
+    list(&mut cursor, "")
+        .and_then(|()| cursor.end("`,` or end of input"))
+        .map_err(|error| SelectorParseError { errors: vec![error] })?;
+    // A sub-object selected without subfields is returned whole
+    let paths: Vec<(String, usize)> = cursor
+        .paths
+        .iter()
+        .map(|(path, position)| (crate::utils::whole_subtree::<T>(path), *position))
+        .collect();
+    crate::utils::selector_from_paths(&paths)
+}
+
+/// `item (',' item)*`, where each item is a path optionally followed by a
+/// parenthesized list of its subfields.
+fn list(cursor: &mut Cursor<'_>, prefix: &str) -> Result<(), InvalidPath> {
+    loop {
+        let start = cursor.at;
+        let raw = cursor.take_while(|byte| !matches!(byte, b',' | b'(' | b')'));
+        let name = raw.trim();
+        if name.is_empty() {
+            return Err(cursor.syntax_error("a field name"));
+        }
+        let position = start + (raw.len() - raw.trim_start().len());
+        let path = format!("{}{}", prefix, name.replace('/', "."));
+
+        if cursor.eat(b'(') {
+            list(cursor, &format!("{}.", path))?;
+            if !cursor.eat(b')') {
+                return Err(cursor.syntax_error("`)`"));
+            }
+        } else {
+            cursor.paths.push((path, position));
+        }
+
+        if !cursor.eat(b',') {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Selector-guided merging of partial JSON documents into structs.
+//!
+//! The selector is a write mask: [`apply`] copies the fields of a PATCH body
+//! enabled in it onto an entity, and reports every field that is not enabled
+//! or has an invalid value.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use serialize_fields::SerializeFields;
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(SerializeFields, Serialize, Deserialize)]
+//! struct User {
+//!     id: u32,
+//!     name: String,
+//!     age: u32,
+//! }
+//!
+//! let mut user = User { id: 1, name: "Alice".into(), age: 30 };
+//! let writable: UserSerializeFieldSelector = "name,age".parse().unwrap();
+//!
+//! serialize_fields::patch::apply(&mut user, &serde_json::json!({"name": "Alicia"}), &writable).unwrap();
+//! assert_eq!((user.id, user.name.as_str(), user.age), (1, "Alicia", 30));
+//!
+//! let error = serialize_fields::patch::apply(
+//!     &mut user,
+//!     &serde_json::json!({"id": 2, "age": "old"}),
+//!     &writable,
+//! )
+//! .unwrap_err();
+//! assert_eq!(error.errors[0].path, "id");
+//! assert_eq!(error.errors[1].path, "age");
+//! assert_eq!(user.age, 30);
+//! ```
+
+use crate::SerializeFieldsTrait;
+use crate::deserialize::Allowed;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+
+/// Copy the fields of `patch` enabled in `selector` onto `entity`.
+///
+/// Keys are serialized names; nested objects are merged field by field,
+/// while any other value, such as an array of nested structs, replaces the
+/// field as a whole. `always` fields are only written if enabled.
+///
+/// The entity is only changed if the whole patch applies: fields that are
+/// not enabled, and values that don't deserialize into the field, are all
+/// reported in the returned [`PatchError`].
+pub fn apply<T>(entity: &mut T, patch: &Value, selector: &T::FieldSelector) -> Result<(), PatchError>
+where
+    T: SerializeFieldsTrait + Serialize + DeserializeOwned,
+{
+    let allowed = Allowed::new(selector);
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    match patch {
+        Value::Object(_) => collect(&allowed, patch, &mut Vec::new(), &mut entries, &mut errors),
+        _ => errors.push(FieldError::new(String::new(), "expected an object")),
+    }
+
+    let current = serde_json::to_value(&*entity)
+        .map_err(|error| PatchError::new(FieldError::new(String::new(), error)))?;
+    let mut combined_error = None;
+    if errors.is_empty() {
+        let mut patched = current.clone();
+        for (path, value) in &entries {
+            set(&mut patched, path, value.clone());
+        }
+        match T::deserialize(patched) {
+            Ok(value) => {
+                *entity = value;
+                return Ok(());
+            }
+            Err(error) => combined_error = Some(error),
+        }
+    }
+
+    // Find the values at fault by applying each one alone
+    for (path, value) in &entries {
+        let mut single = current.clone();
+        set(&mut single, path, value.clone());
+        if let Err(error) = T::deserialize(single) {
+            errors.push(FieldError::new(path.join("."), error));
+        }
+    }
+    if let (true, Some(error)) = (errors.is_empty(), combined_error) {
+        errors.push(FieldError::new(String::new(), error));
+    }
+    Err(PatchError { errors })
+}
+
+/// Error returned by [`apply`], listing every field that could not be written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    pub errors: Vec<FieldError>,
+}
+
+impl PatchError {
+    fn new(error: FieldError) -> Self {
+        PatchError { errors: vec![error] }
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// A field of the patch that could not be written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Dot-joined serialized path of the field; empty for the whole patch.
+    pub path: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(path: String, message: impl fmt::Display) -> Self {
+        FieldError {
+            path,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "`{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Collect the values to write, with their paths, from the patch object
+/// `value` below `node`.
+fn collect(
+    node: &Allowed,
+    value: &Value,
+    path: &mut Vec<String>,
+    entries: &mut Vec<(Vec<String>, Value)>,
+    errors: &mut Vec<FieldError>,
+) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for (key, value) in map {
+        path.push(key.clone());
+        match node.children.get(key.as_str()) {
+            None => errors.push(FieldError::new(path.join("."), "field is not writable")),
+            Some(child) if !child.children.is_empty() && value.is_object() => {
+                collect(child, value, path, entries, errors);
+            }
+            Some(child) => match child.filter_value(value.clone(), true, path) {
+                Ok(value) => entries.push((path.clone(), value)),
+                Err(message) => errors.push(FieldError::new(path.join("."), message)),
+            },
+        }
+        path.pop();
+    }
+}
+
+/// Set the value at `path` in `target`, replacing anything that is not an
+/// object on the way.
+fn set(target: &mut Value, path: &[String], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut node = target;
+    for key in parents {
+        if !node.is_object() {
+            *node = Value::Object(Default::default());
+        }
+        node = node
+            .as_object_mut()
+            .expect("replaced above")
+            .entry(key.clone())
+            .or_insert(Value::Null);
+    }
+    if !node.is_object() {
+        *node = Value::Object(Default::default());
+    }
+    node.as_object_mut().expect("replaced above").insert(last.clone(), value);
+}
@@ -0,0 +1,147 @@
+//! Path lists in the formats expected by document stores' field masks.
+
+/// How paths that go through a sequence field, such as a `Vec` of nested
+/// structs, are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SequencePaths {
+    /// Continue into the elements, as in `posts.title`.
+    Traverse,
+    /// Stop at the sequence field, listing `posts` once, for stores whose
+    /// masks cannot select inside array elements.
+    Stop,
+}
+
+/// Format of the paths returned by
+/// [`FieldSelector::to_path_list_with`](crate::FieldSelector::to_path_list_with).
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User {
+/// #     id: u32,
+/// #     #[serialize_fields(rename = "display-name")]
+/// #     name: String,
+/// #     posts: Vec<Post>,
+/// # }
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct Post { title: String }
+/// use serialize_fields::PathListFormat;
+///
+/// let selector: UserSerializeFieldSelector = "name,posts.title".parse().unwrap();
+/// assert_eq!(selector.to_path_list(), ["display-name", "posts.title"]);
+/// assert_eq!(
+///     selector.to_path_list_with(&PathListFormat::FIRESTORE),
+///     ["`display-name`", "posts"]
+/// );
+/// assert_eq!(
+///     selector.to_path_list_with(&PathListFormat { separator: "/", ..PathListFormat::DOTTED }),
+///     ["display-name", "posts/title"]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathListFormat {
+    /// Joins the segments of a path.
+    pub separator: &'static str,
+    /// Paths through sequence fields.
+    pub sequences: SequencePaths,
+    /// Quote segments that are not plain identifiers with backticks, as
+    /// Firestore field paths require.
+    pub quote_segments: bool,
+}
+
+impl PathListFormat {
+    /// Dot-joined serialized names, continuing into sequences.
+    pub const DOTTED: Self = PathListFormat {
+        separator: ".",
+        sequences: SequencePaths::Traverse,
+        quote_segments: false,
+    };
+
+    /// Firestore field mask paths: dot-joined, backtick-quoted where needed,
+    /// and stopping at arrays.
+    pub const FIRESTORE: Self = PathListFormat {
+        separator: ".",
+        sequences: SequencePaths::Stop,
+        quote_segments: true,
+    };
+
+    pub(crate) fn format(&self, path: &[PathSegment]) -> String {
+        let path = match self.sequences {
+            SequencePaths::Traverse => path,
+            SequencePaths::Stop => match path.iter().position(|segment| segment.sequence) {
+                Some(index) => &path[..=index],
+                None => path,
+            },
+        };
+        let mut formatted = String::new();
+        for (index, segment) in path.iter().enumerate() {
+            if index > 0 {
+                formatted.push_str(self.separator);
+            }
+            if self.quote_segments && !is_identifier(segment.name) {
+                formatted.push('`');
+                for c in segment.name.chars() {
+                    if matches!(c, '`' | '\\') {
+                        formatted.push('\\');
+                    }
+                    formatted.push(c);
+                }
+                formatted.push('`');
+            } else {
+                formatted.push_str(segment.name);
+            }
+        }
+        formatted
+    }
+}
+
+impl Default for PathListFormat {
+    fn default() -> Self {
+        Self::DOTTED
+    }
+}
+
+/// A JSONPath expression for the path, such as `$.posts[*].title`, with
+/// bracket notation for names that are not plain identifiers.
+pub(crate) fn json_path(path: &[PathSegment]) -> String {
+    let mut formatted = String::from("$");
+    for segment in path {
+        if is_identifier(segment.name) {
+            formatted.push('.');
+            formatted.push_str(segment.name);
+        } else {
+            formatted.push_str("['");
+            for c in segment.name.chars() {
+                if matches!(c, '\'' | '\\') {
+                    formatted.push('\\');
+                }
+                formatted.push(c);
+            }
+            formatted.push_str("']");
+        }
+        if segment.sequence {
+            formatted.push_str("[*]");
+        }
+    }
+    formatted
+}
+
+/// One serialized segment of a path visited by the generated code.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct PathSegment {
+    pub name: &'static str,
+    /// The field is a sequence of nested structs.
+    pub sequence: bool,
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,51 @@
+//! Authorization of individual fields when enabling them.
+
+/// Decides which fields may be enabled, consulted by
+/// [`enable_with_policy`](crate::FieldSelector::enable_with_policy) so that an
+/// authorization engine can veto paths in one place instead of every handler
+/// filtering selections.
+///
+/// Closures taking the same arguments implement it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// #[derive(SerializeFields, Serialize)]
+/// struct User {
+///     id: u32,
+///     name: String,
+///     profile: Profile,
+/// }
+///
+/// #[derive(SerializeFields, Serialize)]
+/// struct Profile {
+///     bio: String,
+///     phone: String,
+/// }
+///
+/// let is_admin = false;
+/// let policy = |_type_name: &str, path: &str| is_admin || path != "profile.phone";
+///
+/// let mut selector = UserSerializeFieldSelector::new();
+/// selector.enable_with_policy("id", &policy);
+/// let denied = selector.enable_with_policy("profile.*", &policy);
+/// assert_eq!(denied, ["profile.phone"]);
+/// assert_eq!(selector.enabled_paths(), ["id", "profile.bio"]);
+/// ```
+pub trait FieldAccessPolicy {
+    /// Whether the leaf field at the dot `path`, in field names, may be
+    /// enabled on a selector of the struct named `type_name`, its
+    /// [`TYPE_NAME`](crate::FieldSelector::TYPE_NAME).
+    fn allows(&self, type_name: &str, path: &str) -> bool;
+}
+
+impl<F> FieldAccessPolicy for F
+where
+    F: Fn(&str, &str) -> bool,
+{
+    fn allows(&self, type_name: &str, path: &str) -> bool {
+        self(type_name, path)
+    }
+}
@@ -0,0 +1,177 @@
+//! Selectors built from the fields an instance actually holds.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Whether a field value holds data: `Some` for `Option`s, non-empty for
+/// strings and collections, always for numbers and booleans.
+///
+/// Used by `Selector::from_present` on structs deriving with
+/// `#[serialize_fields(present)]`; implement it for other leaf field types.
+pub trait Present {
+    /// Whether the value holds data.
+    fn is_present(&self) -> bool;
+}
+
+impl<T> Present for Option<T> {
+    fn is_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T: Present + ?Sized> Present for &T {
+    fn is_present(&self) -> bool {
+        (**self).is_present()
+    }
+}
+
+impl<T: Present + ?Sized> Present for Box<T> {
+    fn is_present(&self) -> bool {
+        (**self).is_present()
+    }
+}
+
+impl<T: Present + ?Sized> Present for Rc<T> {
+    fn is_present(&self) -> bool {
+        (**self).is_present()
+    }
+}
+
+impl<T: Present + ?Sized> Present for Arc<T> {
+    fn is_present(&self) -> bool {
+        (**self).is_present()
+    }
+}
+
+impl<T: Present + ToOwned + ?Sized> Present for Cow<'_, T> {
+    fn is_present(&self) -> bool {
+        (**self).is_present()
+    }
+}
+
+impl Present for serde_json::Value {
+    fn is_present(&self) -> bool {
+        !self.is_null()
+    }
+}
+
+macro_rules! present_if_not_empty {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Present for $ty {
+                fn is_present(&self) -> bool {
+                    !self.is_empty()
+                }
+            }
+        )*
+    };
+}
+
+present_if_not_empty!(str, String);
+
+macro_rules! present_collection {
+    ($($ty:ident<$($param:ident),*>),* $(,)?) => {
+        $(
+            impl<$($param),*> Present for $ty<$($param),*> {
+                fn is_present(&self) -> bool {
+                    !self.is_empty()
+                }
+            }
+        )*
+    };
+}
+
+present_collection!(Vec<T>, VecDeque<T>, BTreeSet<T>, HashSet<T, H>, BTreeMap<K, V>, HashMap<K, V, H>);
+
+impl<T> Present for [T] {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<T, const N: usize> Present for [T; N] {
+    fn is_present(&self) -> bool {
+        N > 0
+    }
+}
+
+macro_rules! always_present {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Present for $ty {
+                fn is_present(&self) -> bool {
+                    true
+                }
+            }
+        )*
+    };
+}
+
+always_present!(
+    bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+/// Enabling the fields of a selector of type `S` that hold data, per
+/// [`Present`], recursing into nested structs. Implemented with
+/// `#[serialize_fields(present)]`, and for containers of such structs, whose
+/// elements are merged.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// #[derive(SerializeFields, Serialize)]
+/// #[serialize_fields(present)]
+/// struct UserRow {
+///     id: u32,
+///     name: Option<String>,
+///     email: Option<String>,
+///     tags: Vec<String>,
+/// }
+///
+/// // Only `id` and `name` were loaded
+/// let row = UserRow { id: 1, name: Some("Alice".into()), email: None, tags: Vec::new() };
+/// let selector = UserRowSerializeFieldSelector::from_present(&row);
+/// assert_eq!(selector.enabled_paths(), ["id", "name"]);
+/// ```
+pub trait PresentFields<S> {
+    /// Enable in `selector` the fields of `self` that hold data.
+    fn enable_present(&self, selector: &mut S);
+}
+
+impl<S, T: PresentFields<S>> PresentFields<S> for Option<T> {
+    fn enable_present(&self, selector: &mut S) {
+        if let Some(value) = self {
+            value.enable_present(selector);
+        }
+    }
+}
+
+impl<S, T: PresentFields<S>> PresentFields<S> for [T] {
+    fn enable_present(&self, selector: &mut S) {
+        for value in self {
+            value.enable_present(selector);
+        }
+    }
+}
+
+impl<S, T: PresentFields<S>> PresentFields<S> for Vec<T> {
+    fn enable_present(&self, selector: &mut S) {
+        self.as_slice().enable_present(selector);
+    }
+}
+
+impl<S, T: PresentFields<S>, const N: usize> PresentFields<S> for [T; N] {
+    fn enable_present(&self, selector: &mut S) {
+        self.as_slice().enable_present(selector);
+    }
+}
+
+impl<S, T: PresentFields<S> + ?Sized> PresentFields<S> for Box<T> {
+    fn enable_present(&self, selector: &mut S) {
+        (**self).enable_present(selector);
+    }
+}
@@ -0,0 +1,327 @@
+//! Serialization of a value with every scalar replaced by a placeholder.
+
+use serde::ser::{self, Serialize};
+
+/// Serializes the value with the same shape, but with every string, number,
+/// boolean, byte string and enum variant replaced by the placeholder.
+pub struct Redacted<'a, T: ?Sized>(pub &'a T, pub &'a str);
+
+impl<T: Serialize + ?Sized> Serialize for Redacted<'_, T> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(Redactor {
+            inner: serializer,
+            placeholder: self.1,
+        })
+    }
+}
+
+struct Redactor<'p, S> {
+    inner: S,
+    placeholder: &'p str,
+}
+
+impl<'p, S: ser::Serializer> Redactor<'p, S> {
+    fn placeholder(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(self.placeholder)
+    }
+}
+
+impl<'p, S: ser::Serializer> ser::Serializer for Redactor<'p, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<'p, S::SerializeSeq>;
+    type SerializeTuple = Compound<'p, S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<'p, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<'p, S::SerializeTupleVariant>;
+    type SerializeMap = Compound<'p, S::SerializeMap>;
+    type SerializeStruct = Compound<'p, S::SerializeStruct>;
+    type SerializeStructVariant = Compound<'p, S::SerializeStructVariant>;
+
+    fn serialize_bool(self, _v: bool) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_i128(self, _v: i128) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_u128(self, _v: u128) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_char(self, _v: char) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_some(&Redacted(value, self.placeholder))
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.placeholder()
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_struct(name, &Redacted(value, self.placeholder))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_variant(
+            name,
+            variant_index,
+            variant,
+            &Redacted(value, self.placeholder),
+        )
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        let placeholder = self.placeholder;
+        let inner = self.inner.serialize_seq(len)?;
+        Ok(Compound { inner, placeholder })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        let placeholder = self.placeholder;
+        let inner = self.inner.serialize_tuple(len)?;
+        Ok(Compound { inner, placeholder })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let placeholder = self.placeholder;
+        let inner = self.inner.serialize_tuple_struct(name, len)?;
+        Ok(Compound { inner, placeholder })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let placeholder = self.placeholder;
+        let inner = self.inner.serialize_tuple_variant(name, variant_index, variant, len)?;
+        Ok(Compound { inner, placeholder })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        let placeholder = self.placeholder;
+        let inner = self.inner.serialize_map(len)?;
+        Ok(Compound { inner, placeholder })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        let placeholder = self.placeholder;
+        let inner = self.inner.serialize_struct(name, len)?;
+        Ok(Compound { inner, placeholder })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let placeholder = self.placeholder;
+        let inner = self.inner.serialize_struct_variant(name, variant_index, variant, len)?;
+        Ok(Compound { inner, placeholder })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// A compound value whose elements, and map values, are redacted in turn.
+/// Map keys and field names are kept.
+struct Compound<'p, C> {
+    inner: C,
+    placeholder: &'p str,
+}
+
+impl<C: ser::SerializeSeq> ser::SerializeSeq for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_element(&Redacted(value, self.placeholder))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: ser::SerializeTuple> ser::SerializeTuple for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_element(&Redacted(value, self.placeholder))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: ser::SerializeTupleStruct> ser::SerializeTupleStruct for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_field(&Redacted(value, self.placeholder))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: ser::SerializeTupleVariant> ser::SerializeTupleVariant for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_field(&Redacted(value, self.placeholder))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: ser::SerializeMap> ser::SerializeMap for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.inner.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner.serialize_value(&Redacted(value, self.placeholder))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: ser::SerializeStruct> ser::SerializeStruct for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        self.inner.serialize_field(key, &Redacted(value, self.placeholder))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: ser::SerializeStructVariant> ser::SerializeStructVariant for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        self.inner.serialize_field(key, &Redacted(value, self.placeholder))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
@@ -0,0 +1,84 @@
+//! Key renaming applied at serialization time.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Key names to emit instead of the serialized field names, chosen at
+/// runtime, e.g. per tenant.
+///
+/// Keys are looked up by serialized name, at every level of nesting; names
+/// without an entry go through the conversion function, if any, and are
+/// otherwise kept. Attach it with [`SerializeFields::with_renames`](crate::SerializeFields::with_renames).
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { user_id: u32, display_name: String, last_login: u64 }
+/// use serialize_fields::RenameMap;
+///
+/// let user = User { user_id: 1, display_name: "Alice".into(), last_login: 0 };
+/// let selector = UserSerializeFieldSelector::all();
+///
+/// let renames = RenameMap::camel_case().rename("user_id", "id");
+/// assert_eq!(
+///     serde_json::to_string(&SerializeFields(&user, &selector).with_renames(&renames)).unwrap(),
+///     r#"{"id":1,"displayName":"Alice","lastLogin":0}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RenameMap {
+    keys: HashMap<String, String>,
+    convert: Option<fn(&str) -> String>,
+}
+
+impl RenameMap {
+    /// A map that keeps every name.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A map converting `snake_case` names to `camelCase`.
+    pub fn camel_case() -> Self {
+        Self::new().convert(to_camel_case)
+    }
+
+    /// Emit `to` instead of the serialized name `from`.
+    pub fn rename(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.keys.insert(from.into(), to.into());
+        self
+    }
+
+    /// Convert the names without an explicit entry with `convert`.
+    pub fn convert(mut self, convert: fn(&str) -> String) -> Self {
+        self.convert = Some(convert);
+        self
+    }
+
+    /// The key emitted for the serialized name `key`.
+    pub fn get<'k>(&'k self, key: &'k str) -> Cow<'k, str> {
+        match (self.keys.get(key), self.convert) {
+            (Some(renamed), _) => Cow::Borrowed(renamed),
+            (None, Some(convert)) => Cow::Owned(convert(key)),
+            (None, None) => Cow::Borrowed(key),
+        }
+    }
+}
+
+fn to_camel_case(name: &str) -> String {
+    let mut converted = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' && !converted.is_empty() {
+            upper = true;
+        } else if upper {
+            converted.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            converted.push(c);
+        }
+    }
+    converted
+}
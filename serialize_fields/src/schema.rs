@@ -0,0 +1,99 @@
+//! Narrowing the JSON schema of a struct to the fields a selector enables.
+
+use std::collections::HashSet;
+
+use schemars::SchemaGenerator;
+use serde_json::{Map, Value};
+
+/// Find the object schemas describing a struct inside `schema` and pass their
+/// `properties` to `filter`.
+///
+/// Field types such as `Option<T>` or `Vec<T>` wrap the struct schema in
+/// `anyOf` or `items`, and nested structs are usually a `$ref` into the
+/// generator's definitions. References are replaced by a copy of their
+/// definition, since the filtered copy only applies to this position. Every
+/// object visited loses its `required` list: any field may be left out.
+pub fn filter_struct_schema(
+    schema: &mut Value,
+    generator: &SchemaGenerator,
+    filter: &mut dyn FnMut(&mut Map<String, Value>),
+) {
+    let Some(object) = schema.as_object_mut() else {
+        return;
+    };
+
+    if let Some(reference) = object.remove("$ref") {
+        let name = reference.as_str().and_then(|path| path.rsplit('/').next());
+        if let Some(Value::Object(definition)) = name.and_then(|name| generator.definitions().get(name)) {
+            for (key, value) in definition {
+                object.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    object.remove("required");
+    if let Some(Value::Object(properties)) = object.get_mut("properties") {
+        filter(properties);
+        return;
+    }
+
+    for key in ["anyOf", "oneOf", "allOf"] {
+        if let Some(Value::Array(variants)) = object.get_mut(key) {
+            for variant in variants {
+                filter_struct_schema(variant, generator, filter);
+            }
+        }
+    }
+    for key in ["items", "additionalProperties"] {
+        if let Some(inner) = object.get_mut(key) {
+            filter_struct_schema(inner, generator, filter);
+        }
+    }
+}
+
+/// Remove the definitions added to `generator` since `known` was taken that
+/// `schema` no longer references, directly or through other definitions.
+///
+/// [`filter_struct_schema`] inlines the definitions of nested structs, so the
+/// ones generated only for them would otherwise still be emitted under `$defs`
+/// with their `required` lists.
+pub fn prune_definitions(generator: &mut SchemaGenerator, known: &HashSet<String>, schema: &Value) {
+    let mut reachable = HashSet::new();
+    let mut pending = Vec::new();
+    collect_references(schema, &mut pending);
+    while let Some(name) = pending.pop() {
+        if !reachable.insert(name.clone()) {
+            continue;
+        }
+        if let Some(definition) = generator.definitions().get(&name) {
+            collect_references(definition, &mut pending);
+        }
+    }
+
+    generator
+        .definitions_mut()
+        .retain(|name, _| known.contains(name) || reachable.contains(name));
+}
+
+fn collect_references(schema: &Value, names: &mut Vec<String>) {
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(path)) => {
+                        if let Some(name) = path.rsplit('/').next() {
+                            names.push(name.to_string());
+                        }
+                    }
+                    _ => collect_references(value, names),
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_references(value, names);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,93 @@
+//! Incremental encoding of filtered items into chunks for streaming responses.
+//!
+//! [`ChunkEncoder`] turns items, one at a time, into the byte chunks of an
+//! NDJSON body or of a JSON array; map a stream of items through it and send
+//! each chunk as it is produced.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use serialize_fields::SerializeFields;
+//! # use serde::Serialize;
+//! # #[derive(SerializeFields, Serialize)]
+//! # struct User { id: u32, name: String }
+//! use serialize_fields::stream::{ChunkEncoder, StreamFormat};
+//!
+//! let users = vec![User { id: 1, name: "Alice".into() }, User { id: 2, name: "Bob".into() }];
+//! let selector: UserSerializeFieldSelector = "id".parse().unwrap();
+//!
+//! let mut encoder = ChunkEncoder::new(&selector, StreamFormat::JsonArray);
+//! let mut body = Vec::new();
+//! for user in &users {
+//!     body.extend(encoder.encode(user).unwrap());
+//! }
+//! body.extend(encoder.finish());
+//! assert_eq!(body, br#"[{"id":1},{"id":2}]"#);
+//! ```
+//!
+//! With an async stream, such as a database cursor, the same calls map its
+//! items into the chunks of a streaming body:
+//!
+//! ```rust,ignore
+//! let mut encoder = ChunkEncoder::new(&selector, StreamFormat::Ndjson);
+//! let body = rows.map(move |row| encoder.encode(&row).map(Bytes::from));
+//! ```
+
+use crate::{SerializeFields, SerializeFieldsTrait};
+
+/// How the chunks produced by a [`ChunkEncoder`] are framed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamFormat {
+    /// Newline-delimited JSON: one object per line.
+    Ndjson,
+    /// The parts of a single JSON array.
+    JsonArray,
+}
+
+/// Encodes items with the fields enabled in a selector into the successive
+/// chunks of a body in a [`StreamFormat`].
+#[derive(Debug)]
+pub struct ChunkEncoder<'a, S> {
+    selector: &'a S,
+    format: StreamFormat,
+    started: bool,
+}
+
+impl<'a, S: crate::FieldSelector> ChunkEncoder<'a, S> {
+    /// An encoder for a body in `format`, with the fields enabled in `selector`.
+    pub fn new(selector: &'a S, format: StreamFormat) -> Self {
+        ChunkEncoder {
+            selector,
+            format,
+            started: false,
+        }
+    }
+
+    /// The chunk for the next item: its JSON, preceded by the opening bracket
+    /// or a comma in a JSON array and followed by a newline in NDJSON.
+    pub fn encode<T>(&mut self, item: &T) -> serde_json::Result<Vec<u8>>
+    where
+        T: SerializeFieldsTrait<FieldSelector = S>,
+    {
+        let mut chunk = Vec::new();
+        if self.format == StreamFormat::JsonArray {
+            chunk.push(if self.started { b',' } else { b'[' });
+        }
+        serde_json::to_writer(&mut chunk, &SerializeFields(item, self.selector))?;
+        if self.format == StreamFormat::Ndjson {
+            chunk.push(b'\n');
+        }
+        self.started = true;
+        Ok(chunk)
+    }
+
+    /// The last chunk, after all items: the end of a JSON array, which is
+    /// `[]` if no item was encoded, and nothing for NDJSON.
+    pub fn finish(self) -> Vec<u8> {
+        match self.format {
+            StreamFormat::Ndjson => Vec::new(),
+            StreamFormat::JsonArray if self.started => b"]".to_vec(),
+            StreamFormat::JsonArray => b"[]".to_vec(),
+        }
+    }
+}
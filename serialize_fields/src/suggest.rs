@@ -0,0 +1,44 @@
+//! "Did you mean" suggestions for unknown field paths.
+
+/// Find the selectable path closest to `path`, by Levenshtein distance.
+///
+/// Nested prefixes of the candidates (`profile` for `profile.bio`) are
+/// considered too, so a typo in a nested struct name is also caught. Returns
+/// `None` when nothing is close enough to be a plausible typo.
+pub(crate) fn closest_path(path: &str, candidates: &[&str]) -> Option<String> {
+    let max_distance = (path.chars().count() / 3).max(2);
+
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in candidates {
+        let prefixes = candidate
+            .match_indices('.')
+            .map(|(end, _)| &candidate[..end])
+            .chain(std::iter::once(*candidate));
+        for prefix in prefixes {
+            let distance = levenshtein(path, prefix);
+            if distance <= max_distance && best.is_none_or(|(best, _)| distance < best) {
+                best = Some((distance, prefix));
+            }
+        }
+    }
+
+    best.map(|(_, path)| path.to_string())
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
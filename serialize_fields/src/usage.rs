@@ -0,0 +1,92 @@
+//! Instrumentation of the fields enabled when serializing.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Receives the fields enabled for each serialization it is attached to with
+/// [`SerializeOptions::record_usage`](crate::SerializeOptions::record_usage),
+/// e.g. for analytics on which API fields clients use.
+///
+/// [`record`](Self::record) is called once per serialized value, before it is
+/// written: once for a whole `Vec` or iterator, and not for nested structs.
+/// Closures taking the same arguments implement it. Recorders are `Sync` so
+/// that options holding one can be shared between threads.
+pub trait FieldUsageRecorder: Sync {
+    /// Record that the fields at `paths`, as listed by
+    /// [`enabled_paths`](crate::FieldSelector::enabled_paths), were enabled
+    /// for a value of the struct named `type_name`, its
+    /// [`TYPE_NAME`](crate::FieldSelector::TYPE_NAME).
+    fn record(&self, type_name: &'static str, paths: &[String]);
+}
+
+impl<F> FieldUsageRecorder for F
+where
+    F: Fn(&'static str, &[String]) + Sync,
+{
+    fn record(&self, type_name: &'static str, paths: &[String]) {
+        self(type_name, paths)
+    }
+}
+
+/// A [`FieldUsageRecorder`] counting how many serializations enabled each
+/// field.
+///
+/// # Examples
+///
+/// ```rust
+/// # use serialize_fields::SerializeFields;
+/// # use serde::Serialize;
+/// # #[derive(SerializeFields, Serialize)]
+/// # struct User { id: u32, name: String, email: String }
+/// use serialize_fields::FieldUsageCounter;
+///
+/// let users = vec![User { id: 1, name: "Alice".into(), email: "alice@example.com".into() }];
+/// let counter = FieldUsageCounter::new();
+/// for fields in ["id,name", "id"] {
+///     let selector: UserSerializeFieldSelector = fields.parse().unwrap();
+///     serde_json::to_string(&SerializeFields(&users, &selector).record_usage(&counter)).unwrap();
+/// }
+///
+/// let counts = counter.counts();
+/// let type_name = counts.keys().next().unwrap().0;
+/// assert_eq!(counts[&(type_name, "id".to_string())], 2);
+/// assert_eq!(counts[&(type_name, "name".to_string())], 1);
+/// assert_eq!(counts.len(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct FieldUsageCounter {
+    counts: Mutex<BTreeMap<(&'static str, String), u64>>,
+}
+
+impl FieldUsageCounter {
+    /// A counter with nothing recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of serializations that enabled each field, keyed by struct
+    /// name and path. Fields never enabled are absent.
+    pub fn counts(&self) -> BTreeMap<(&'static str, String), u64> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Forget everything recorded so far, e.g. after reporting the counts.
+    pub fn reset(&self) {
+        self.counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+}
+
+impl FieldUsageRecorder for FieldUsageCounter {
+    fn record(&self, type_name: &'static str, paths: &[String]) {
+        let mut counts = self.counts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for path in paths {
+            *counts.entry((type_name, path.clone())).or_default() += 1;
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! The struct writer used by the generated serialization code.
+
+use crate::SerializeOptions;
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+use std::borrow::Cow;
+
+/// Writes the fields of a struct, as a struct or, when keys are renamed at
+/// runtime, as a map.
+pub enum FieldWriter<'o, S: Serializer> {
+    Struct(S::SerializeStruct),
+    Map(S::SerializeMap, &'o crate::RenameMap),
+}
+
+impl<'o, S: Serializer> FieldWriter<'o, S> {
+    pub fn new(
+        serializer: S,
+        name: &'static str,
+        len: usize,
+        options: &SerializeOptions<'o>,
+    ) -> Result<Self, S::Error> {
+        Ok(match options.rename {
+            Some(renames) => FieldWriter::Map(serializer.serialize_map(Some(len))?, renames),
+            None => FieldWriter::Struct(serializer.serialize_struct(name, len)?),
+        })
+    }
+
+    pub fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), S::Error> {
+        match self {
+            FieldWriter::Struct(state) => state.serialize_field(key, value),
+            FieldWriter::Map(state, renames) => state.serialize_entry(&*renames.get(key), value),
+        }
+    }
+
+    /// Indices of the fields in the order they are written: `declared`, or
+    /// sorted by emitted key. `sorted` is the order by serialized name, used
+    /// unless keys are renamed.
+    pub fn order(
+        &self,
+        options: &SerializeOptions<'_>,
+        names: &[&'static str],
+        declared: &'static [usize],
+        sorted: &'static [usize],
+    ) -> Cow<'static, [usize]> {
+        match self {
+            _ if !options.sorted => Cow::Borrowed(declared),
+            FieldWriter::Struct(_) => Cow::Borrowed(sorted),
+            FieldWriter::Map(_, renames) => {
+                let mut order = declared.to_vec();
+                order.sort_by_cached_key(|&index| renames.get(names[index]).into_owned());
+                Cow::Owned(order)
+            }
+        }
+    }
+
+    pub fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            FieldWriter::Struct(state) => state.end(),
+            FieldWriter::Map(state, _) => state.end(),
+        }
+    }
+}
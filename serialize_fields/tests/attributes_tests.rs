@@ -0,0 +1,1529 @@
+//! Tests for `#[serialize_fields(...)]` field and container attributes.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use serialize_fields::{SerializeFields, SerializeFieldsTrait};
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct RenamedUser {
+    id: u32,
+    #[serialize_fields(rename = "userName", alias = "username", alias = "login")]
+    user_name: String,
+    #[serialize_fields(alias = "details")]
+    profile: RenamedProfile,
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct RenamedProfile {
+    #[serialize_fields(rename = "biography")]
+    bio: String,
+}
+
+fn create_renamed_user() -> RenamedUser {
+    RenamedUser {
+        id: 1,
+        user_name: "alice".to_string(),
+        profile: RenamedProfile {
+            bio: "Developer".to_string(),
+        },
+    }
+}
+
+fn to_value<T: SerializeFieldsTrait>(data: &T, selector: &T::FieldSelector) -> Value {
+    serde_json::to_value(SerializeFields(data, selector)).unwrap()
+}
+
+#[test]
+fn test_rename_changes_serialized_key() {
+    let user = create_renamed_user();
+    let mut selector = user.serialize_fields();
+    selector.enable_dot_hierarchy("user_name");
+    selector.enable_dot_hierarchy("profile.bio");
+
+    assert_eq!(
+        to_value(&user, &selector),
+        json!({"userName": "alice", "profile": {"biography": "Developer"}})
+    );
+}
+
+#[test]
+fn test_alias_selects_field() {
+    let user = create_renamed_user();
+    let mut selector = user.serialize_fields();
+    selector.enable_dot_hierarchy("login");
+    selector.enable_dot_hierarchy("details.bio");
+
+    let mut expected = user.serialize_fields();
+    expected.enable_dot_hierarchy("user_name");
+    expected.enable_dot_hierarchy("profile.bio");
+
+    assert_eq!(selector, expected);
+
+    // The serialized key is not selectable unless it is also an alias
+    let mut by_key = user.serialize_fields();
+    by_key.enable_dot_hierarchy("userName");
+    assert!(by_key.user_name.is_none());
+}
+
+#[test]
+fn test_alias_in_field_enum_from_str() {
+    assert_eq!(
+        "username".parse::<RenamedUserField>().unwrap(),
+        RenamedUserField::UserName
+    );
+    assert_eq!(
+        "details.bio".parse::<RenamedUserField>().unwrap(),
+        RenamedUserField::Profile(RenamedProfileField::Bio)
+    );
+    // Display uses the canonical field name
+    assert_eq!(RenamedUserField::UserName.to_string(), "user_name");
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct AlwaysStruct {
+    #[serialize_fields(always)]
+    id: u32,
+    name: String,
+    #[serialize_fields(always)]
+    profile: RenamedProfile,
+}
+
+#[test]
+fn test_always_fields_serialized_without_selection() {
+    let data = AlwaysStruct {
+        id: 7,
+        name: "Bob".to_string(),
+        profile: RenamedProfile {
+            bio: "Tester".to_string(),
+        },
+    };
+
+    let selector = data.serialize_fields();
+    assert_eq!(
+        to_value(&data, &selector),
+        json!({"id": 7, "profile": {"bio": "Tester"}})
+    );
+    assert_eq!(selector.active_nested(), ["profile"]);
+
+    let mut selector = data.serialize_fields();
+    selector.enable_dot_hierarchy("name");
+    selector.enable_dot_hierarchy("id");
+    assert_eq!(
+        to_value(&data, &selector),
+        json!({"id": 7, "name": "Bob", "profile": {"bio": "Tester"}})
+    );
+}
+
+#[test]
+fn test_always_nested_field_narrowed_by_selector() {
+    let data = AlwaysStruct {
+        id: 7,
+        name: "Bob".to_string(),
+        profile: RenamedProfile {
+            bio: "Tester".to_string(),
+        },
+    };
+
+    let mut selector = data.serialize_fields();
+    selector.enable_dot_hierarchy("profile.bio");
+    assert_eq!(
+        to_value(&data, &selector),
+        json!({"id": 7, "profile": {"biography": "Tester"}})
+    );
+}
+
+#[test]
+fn test_canonicalize_equates_equivalent_selectors() {
+    let by_alias: RenamedUserSerializeFieldSelector = "login,details.bio".parse().unwrap();
+    let mut by_name: RenamedUserSerializeFieldSelector = "user_name,profile".parse().unwrap();
+    by_name.enable_dot_hierarchy("profile.bio");
+    assert_eq!(by_alias, by_name);
+
+    let mut empty_profile: RenamedUserSerializeFieldSelector = "user_name,profile".parse().unwrap();
+    assert_ne!(empty_profile, "user_name".parse().unwrap());
+    empty_profile.canonicalize();
+    assert_eq!(empty_profile, "user_name".parse().unwrap());
+
+    // `always` leaves are cleared, an empty nested `always` selector is kept
+    let mut with_id = AlwaysStructSerializeFieldSelector::new();
+    with_id.enable_dot_hierarchy("id");
+    with_id.enable_dot_hierarchy("profile");
+    with_id.canonicalize();
+    let mut expected = AlwaysStructSerializeFieldSelector::new();
+    expected.enable_dot_hierarchy("profile");
+    assert_eq!(with_id, expected);
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct Account {
+    id: u32,
+    login: String,
+    #[serialize_fields(skip)]
+    password_hash: String,
+}
+
+#[test]
+fn test_skip_field_cannot_be_enabled() {
+    let account = Account {
+        id: 1,
+        login: "alice".to_string(),
+        password_hash: "secret".to_string(),
+    };
+
+    let mut selector = account.serialize_fields();
+    selector.enable_dot_hierarchy("id");
+    selector.enable_dot_hierarchy("password_hash");
+
+    assert_eq!(to_value(&account, &selector), json!({"id": 1}));
+    assert!("password_hash".parse::<AccountField>().is_err());
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct Customer {
+    id: u32,
+    #[serialize_fields(sensitive)]
+    email: String,
+    #[serialize_fields(sensitive)]
+    billing: RenamedProfile,
+}
+
+fn create_customer() -> Customer {
+    Customer {
+        id: 3,
+        email: "carol@example.com".to_string(),
+        billing: RenamedProfile {
+            bio: "Card on file".to_string(),
+        },
+    }
+}
+
+#[test]
+fn test_sensitive_fields_ignored_by_enable() {
+    let customer = create_customer();
+    let mut selector = customer.serialize_fields();
+    selector.enable_dot_hierarchy("id");
+    selector.enable_dot_hierarchy("email");
+    selector.enable(&["billing", "bio"]);
+    selector.enable_enum(CustomerField::Email);
+
+    assert_eq!(to_value(&customer, &selector), json!({"id": 3}));
+}
+
+#[test]
+fn test_all_and_wildcard_leave_sensitive_fields_disabled() {
+    let customer = create_customer();
+    let selector = CustomerSerializeFieldSelector::all();
+    assert_eq!(to_value(&customer, &selector), json!({"id": 3}));
+
+    let mut wildcard = customer.serialize_fields();
+    wildcard.enable_dot_hierarchy("*");
+    assert_eq!(to_value(&customer, &wildcard), json!({"id": 3}));
+}
+
+#[test]
+fn test_parsing_rejects_sensitive_fields() {
+    assert!("id".parse::<CustomerSerializeFieldSelector>().is_ok());
+    assert!("email".parse::<CustomerSerializeFieldSelector>().is_err());
+    assert!("billing.bio".parse::<CustomerSerializeFieldSelector>().is_err());
+
+    // Sensitive paths are never suggested
+    let mut selector = CustomerSerializeFieldSelector::new();
+    let error = selector.try_enable_dot_hierarchy("emial").unwrap_err();
+    assert_eq!(error.suggestion, None);
+}
+
+#[test]
+fn test_enable_sensitive_opt_in() {
+    let customer = create_customer();
+    let mut selector = customer.serialize_fields();
+    selector.enable_sensitive("email");
+    selector.enable_sensitive("billing.bio");
+
+    assert_eq!(
+        to_value(&customer, &selector),
+        json!({"email": "carol@example.com", "billing": {"biography": "Card on file"}})
+    );
+
+    // Typed methods are written by the developer, so they may enable sensitive fields
+    let mut typed = customer.serialize_fields();
+    typed.enable_email().enable_billing(|billing| {
+        billing.enable_bio();
+    });
+    assert_eq!(typed, selector);
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct GroupedUser {
+    #[serialize_fields(group = "public", group = "admin")]
+    id: u32,
+    #[serialize_fields(group = "public")]
+    name: String,
+    #[serialize_fields(group = "admin")]
+    email: String,
+    profile: GroupedProfile,
+    #[serialize_fields(group = "admin")]
+    settings: GroupedSettings,
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct GroupedProfile {
+    #[serialize_fields(group = "public")]
+    bio: String,
+    internal_notes: String,
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct GroupedSettings {
+    theme: String,
+}
+
+fn create_grouped_user() -> GroupedUser {
+    GroupedUser {
+        id: 1,
+        name: "Alice".to_string(),
+        email: "alice@example.com".to_string(),
+        profile: GroupedProfile {
+            bio: "Developer".to_string(),
+            internal_notes: "VIP".to_string(),
+        },
+        settings: GroupedSettings {
+            theme: "dark".to_string(),
+        },
+    }
+}
+
+#[test]
+fn test_enable_group_propagates_into_nested() {
+    let user = create_grouped_user();
+    let mut selector = user.serialize_fields();
+    selector.enable_group("public");
+
+    assert_eq!(
+        to_value(&user, &selector),
+        json!({"id": 1, "name": "Alice", "profile": {"bio": "Developer"}})
+    );
+}
+
+#[test]
+fn test_enable_group_tagged_nested_field() {
+    let user = create_grouped_user();
+    let mut selector = user.serialize_fields();
+    selector.enable_group("admin");
+
+    // The tagged nested field is enabled whole, though none of its fields are tagged
+    assert_eq!(
+        to_value(&user, &selector),
+        json!({"id": 1, "email": "alice@example.com", "settings": {"theme": "dark"}})
+    );
+
+    let mut unknown = user.serialize_fields();
+    unknown.enable_group("nonexistent");
+    assert_eq!(unknown, user.serialize_fields());
+}
+
+#[derive(SerializeFields, Serialize)]
+struct GroupedContact {
+    #[serialize_fields(group = "contact")]
+    email: String,
+    #[serialize_fields(group = "contact", sensitive)]
+    ssn: String,
+}
+
+#[test]
+fn test_enable_group_leaves_sensitive_fields_disabled() {
+    let contact = GroupedContact {
+        email: "alice@example.com".to_string(),
+        ssn: "123-45-6789".to_string(),
+    };
+    let mut selector = contact.serialize_fields();
+    selector.enable_group("contact");
+    assert_eq!(to_value(&contact, &selector), json!({"email": "alice@example.com"}));
+
+    selector.enable_sensitive("ssn");
+    assert_eq!(
+        to_value(&contact, &selector),
+        json!({"email": "alice@example.com", "ssn": "123-45-6789"})
+    );
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct Member {
+    id: u32,
+    #[serialize_fields(roles("admin", "moderator"))]
+    email: String,
+    #[serialize_fields(sensitive, roles("admin"))]
+    ip_address: String,
+    #[serialize_fields(sensitive)]
+    password_reset_token: String,
+    stats: MemberStats,
+}
+
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+struct MemberStats {
+    posts: u32,
+    #[serialize_fields(roles("admin"))]
+    reports: u32,
+}
+
+fn create_member() -> Member {
+    Member {
+        id: 9,
+        email: "dave@example.com".to_string(),
+        ip_address: "10.0.0.1".to_string(),
+        password_reset_token: "token".to_string(),
+        stats: MemberStats { posts: 4, reports: 1 },
+    }
+}
+
+#[test]
+fn test_enable_for_role() {
+    let member = create_member();
+
+    let mut user_view = member.serialize_fields();
+    user_view.enable_for_role("user");
+    assert_eq!(
+        to_value(&member, &user_view),
+        json!({"id": 9, "stats": {"posts": 4}})
+    );
+
+    let mut admin_view = member.serialize_fields();
+    admin_view.enable_for_role("admin");
+    assert_eq!(
+        to_value(&member, &admin_view),
+        json!({
+            "id": 9,
+            "email": "dave@example.com",
+            "ip_address": "10.0.0.1",
+            "stats": {"posts": 4, "reports": 1}
+        })
+    );
+}
+
+#[test]
+fn test_restrict_to_role() {
+    let member = create_member();
+    let mut selector = member.serialize_fields();
+    selector.enable_dot_hierarchy("id");
+    selector.enable_dot_hierarchy("email");
+    selector.enable_dot_hierarchy("stats.reports");
+    selector.enable_dot_hierarchy("stats.posts");
+
+    selector.restrict_to_role("moderator");
+    assert_eq!(
+        to_value(&member, &selector),
+        json!({"id": 9, "email": "dave@example.com", "stats": {"posts": 4}})
+    );
+}
+
+#[test]
+fn test_restrict_to_role_clears_sensitive_fields_without_roles() {
+    let member = create_member();
+    let mut selector = member.serialize_fields();
+    selector.enable_dot_hierarchy("id");
+    selector.enable_sensitive("ip_address");
+    selector.enable_sensitive("password_reset_token");
+
+    selector.restrict_to_role("admin");
+    assert_eq!(
+        to_value(&member, &selector),
+        json!({"id": 9, "ip_address": "10.0.0.1"})
+    );
+}
+
+mod models {
+    use serde::Serialize;
+    use serialize_fields::SerializeFields;
+
+    #[derive(SerializeFields, Serialize)]
+    #[serialize_fields(vis = "inherit")]
+    struct InternalRecord {
+        id: u32,
+    }
+
+    #[derive(SerializeFields, Serialize)]
+    #[serialize_fields(vis = "pub(super)")]
+    pub(super) struct AuditEntry {
+        pub id: u32,
+    }
+
+    pub fn internal_record_json() -> serde_json::Value {
+        let record = InternalRecord { id: 2 };
+        let mut selector = InternalRecordSerializeFieldSelector::new();
+        selector.enable_enum(InternalRecordField::Id);
+        serde_json::to_value(SerializeFields(&record, &selector)).unwrap()
+    }
+
+    #[derive(SerializeFields, Serialize)]
+    #[serialize_fields(selector = "TeamFields")]
+    pub struct Team {
+        pub id: u32,
+        pub(crate) lead: super::RenamedProfile,
+    }
+}
+
+#[derive(SerializeFields, Serialize)]
+struct Project {
+    id: u32,
+    team: models::Team,
+}
+
+#[test]
+fn test_generated_visibility() {
+    assert_eq!(models::internal_record_json(), json!({"id": 2}));
+
+    let entry = models::AuditEntry { id: 4 };
+    let mut selector = models::AuditEntrySerializeFieldSelector::new();
+    selector.enable_dot_hierarchy("id");
+    assert_eq!(to_value(&entry, &selector), json!({"id": 4}));
+}
+
+#[test]
+fn test_custom_selector_name() {
+    let project = Project {
+        id: 5,
+        team: models::Team {
+            id: 6,
+            lead: RenamedProfile {
+                bio: "Lead".to_string(),
+            },
+        },
+    };
+
+    let mut team_fields = models::TeamFields::new();
+    team_fields.enable_dot_hierarchy("lead.bio");
+
+    let mut selector = ProjectSerializeFieldSelector::new();
+    selector.enable_dot_hierarchy("team.lead.bio");
+    assert_eq!(selector.team, Some(team_fields));
+    assert_eq!(
+        "team.lead.bio".parse::<ProjectField>().unwrap(),
+        ProjectField::Team(models::TeamField::Lead(RenamedProfileField::Bio))
+    );
+
+    assert_eq!(
+        to_value(&project, &selector),
+        json!({"team": {"lead": {"biography": "Lead"}}})
+    );
+}
+
+mod common {
+    pub use serialize_fields;
+}
+
+mod reexported {
+    use crate::common::serialize_fields as sf;
+
+    #[derive(sf::SerializeFields, serde::Serialize)]
+    #[serialize_fields(crate = "crate::common::serialize_fields")]
+    pub struct Invoice {
+        pub id: u32,
+        pub total: u64,
+    }
+}
+
+#[test]
+fn test_crate_path_override() {
+    let invoice = reexported::Invoice { id: 1, total: 250 };
+    let mut selector = invoice.serialize_fields();
+    selector.enable_dot_hierarchy("total");
+
+    assert_eq!(to_value(&invoice, &selector), json!({"total": 250}));
+}
+
+#[derive(SerializeFields, Serialize)]
+#[serialize_fields(selector_derive(Deserialize, Default))]
+struct Post {
+    id: u32,
+    #[serialize_fields(alias = "headline")]
+    title: String,
+    author: PostAuthor,
+}
+
+#[derive(SerializeFields, Serialize)]
+#[serialize_fields(selector_derive(serde::Deserialize, PartialOrd, Ord))]
+struct PostAuthor {
+    name: String,
+    email: String,
+}
+
+#[test]
+fn test_selector_derive_deserialize() {
+    let selector: PostSerializeFieldSelector =
+        serde_json::from_str(r#"{"id": null, "headline": true, "author": {"name": 1, "email": false}}"#)
+            .unwrap();
+
+    let mut expected = PostSerializeFieldSelector::new();
+    expected.enable_dot_hierarchy("id");
+    expected.enable_dot_hierarchy("title");
+    expected.enable_dot_hierarchy("author.name");
+    assert_eq!(selector, expected);
+
+    // Serialized selectors round-trip
+    let json = serde_json::to_string(&expected).unwrap();
+    let parsed: PostSerializeFieldSelector = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, expected);
+
+    let post = Post {
+        id: 1,
+        title: "Hello".to_string(),
+        author: PostAuthor {
+            name: "Alice".to_string(),
+            email: "alice@example.com".to_string(),
+        },
+    };
+    assert_eq!(
+        to_value(&post, &parsed),
+        json!({"id": 1, "title": "Hello", "author": {"name": "Alice"}})
+    );
+}
+
+#[test]
+fn test_selectors_deserialize_by_default() {
+    // Unknown keys are ignored and sensitive fields stay disabled
+    let selector: CustomerSerializeFieldSelector = serde_json::from_str(
+        r#"{"id": true, "email": true, "billing": {"bio": true}, "unknown": true}"#,
+    )
+    .unwrap();
+
+    let mut expected = CustomerSerializeFieldSelector::new();
+    expected.enable_dot_hierarchy("id");
+    assert_eq!(selector, expected);
+}
+
+#[derive(SerializeFields, Serialize)]
+struct Article {
+    #[serialize_fields(default)]
+    id: u32,
+    #[serialize_fields(default)]
+    title: String,
+    body: String,
+    #[serialize_fields(default)]
+    author: ArticleAuthor,
+    editor: ArticleAuthor,
+}
+
+#[derive(SerializeFields, Serialize)]
+struct ArticleAuthor {
+    #[serialize_fields(default)]
+    name: String,
+    email: String,
+}
+
+#[test]
+fn test_with_defaults() {
+    let article = Article {
+        id: 1,
+        title: "Title".to_string(),
+        body: "Body".to_string(),
+        author: ArticleAuthor {
+            name: "Alice".to_string(),
+            email: "alice@example.com".to_string(),
+        },
+        editor: ArticleAuthor {
+            name: "Bob".to_string(),
+            email: "bob@example.com".to_string(),
+        },
+    };
+
+    let selector = ArticleSerializeFieldSelector::with_defaults();
+    assert_eq!(
+        to_value(&article, &selector),
+        json!({"id": 1, "title": "Title", "author": {"name": "Alice"}})
+    );
+
+    let from_trait: ArticleSerializeFieldSelector = serialize_fields::FieldSelector::with_defaults();
+    assert_eq!(from_trait, selector);
+}
+
+#[test]
+fn test_exclusions_agree_between_parsers() {
+    use serialize_fields::utils::{create_selector_from_list, parse_selector};
+
+    for fields in ["-title", "!author.name, -", "id,body,-body", "-, editor", "- body"] {
+        let parsed: ArticleSerializeFieldSelector = parse_selector(fields).unwrap();
+        let created: ArticleSerializeFieldSelector = create_selector_from_list(fields);
+        assert_eq!(parsed, created, "{fields}");
+    }
+
+    // Only exclusions: start from the defaults, not from every field
+    let selector: ArticleSerializeFieldSelector = "-title".parse().unwrap();
+    assert_eq!(selector.to_string(), "id,author.name");
+}
+
+/// Generated items must not trip `missing_docs` in crates that deny it.
+#[deny(missing_docs)]
+pub mod documented {
+    use serde::Serialize;
+    use serialize_fields::SerializeFields;
+
+    /// A struct whose field docs are copied onto the generated items.
+    #[derive(SerializeFields, Serialize)]
+    pub struct Documented {
+        /// Unique identifier.
+        pub id: u32,
+        /// Nested details.
+        pub details: DocumentedDetails,
+    }
+
+    /// Nested struct.
+    #[derive(SerializeFields, Serialize)]
+    pub struct DocumentedDetails {
+        /// Free-form note.
+        pub note: String,
+    }
+}
+
+#[derive(SerializeFields, Serialize)]
+#[serialize_fields(preset(name = "public", fields = "id, profile.bio"))]
+#[serialize_fields(preset(name = "no_profile", fields = "-profile"))]
+#[serialize_fields(preset(name = "profile", fields = "profile.*"))]
+struct PresetUser {
+    id: u32,
+    name: String,
+    profile: PresetProfile,
+}
+
+#[derive(SerializeFields, Serialize)]
+struct PresetProfile {
+    bio: String,
+    #[serialize_fields(sensitive)]
+    phone: String,
+}
+
+#[test]
+fn test_named_presets() {
+    let user = PresetUser {
+        id: 1,
+        name: "Alice".to_string(),
+        profile: PresetProfile {
+            bio: "Developer".to_string(),
+            phone: "555-0100".to_string(),
+        },
+    };
+
+    assert_eq!(PresetUserSerializeFieldSelector::PRESETS, ["public", "no_profile", "profile"]);
+
+    let public = PresetUserSerializeFieldSelector::preset("public").unwrap();
+    assert_eq!(to_value(&user, &public), json!({"id": 1, "profile": {"bio": "Developer"}}));
+
+    let no_profile = PresetUserSerializeFieldSelector::preset("no_profile").unwrap();
+    assert_eq!(to_value(&user, &no_profile), json!({"id": 1, "name": "Alice"}));
+
+    // Wildcards do not reach sensitive fields
+    let profile = PresetUserSerializeFieldSelector::preset("profile").unwrap();
+    assert_eq!(to_value(&user, &profile), json!({"profile": {"bio": "Developer"}}));
+
+    assert!(PresetUserSerializeFieldSelector::preset("admin").is_none());
+}
+
+#[test]
+fn test_selector_from_tree_matches_renamed_fields() {
+    use serialize_fields::utils::selector_from_tree;
+
+    struct Node {
+        name: &'static str,
+        children: Vec<Node>,
+    }
+
+    // Look-ahead nodes carry the serialized names of a GraphQL schema
+    let look_ahead = vec![
+        Node { name: "userName", children: vec![] },
+        Node {
+            name: "details",
+            children: vec![Node { name: "biography", children: vec![] }],
+        },
+    ];
+    let selector: RenamedUserSerializeFieldSelector =
+        selector_from_tree(&look_ahead, |node| node.name, |node| &node.children);
+    assert_eq!(selector.to_string(), "user_name,profile.bio");
+}
+
+#[derive(SerializeFields, Serialize)]
+#[serialize_fields(table = "users")]
+struct UserRow {
+    id: u32,
+    #[serialize_fields(column = "users.login", rename = "userName")]
+    user_name: String,
+    #[serialize_fields(always)]
+    version: u32,
+    profile: ProfileRow,
+}
+
+#[derive(SerializeFields, Serialize)]
+struct ProfileRow {
+    #[serialize_fields(column = "profiles.bio")]
+    bio: String,
+    #[serialize_fields(column = "profiles.bio")]
+    summary: String,
+}
+
+#[test]
+fn test_sql_columns() {
+    let selector: UserRowSerializeFieldSelector = "user_name,profile.bio,profile.summary".parse().unwrap();
+    assert_eq!(selector.to_sql_columns(), ["users.login", "users.version", "profiles.bio"]);
+
+    // The columns fetched are the ones the response serializes
+    let row = UserRow {
+        id: 1,
+        user_name: "alice".to_string(),
+        version: 3,
+        profile: ProfileRow {
+            bio: "Developer".to_string(),
+            summary: "Developer".to_string(),
+        },
+    };
+    assert_eq!(
+        to_value(&row, &selector),
+        json!({"userName": "alice", "version": 3, "profile": {"bio": "Developer", "summary": "Developer"}})
+    );
+
+    let selector = UserRowSerializeFieldSelector::all();
+    assert_eq!(
+        selector.to_sql_columns(),
+        ["users.id", "users.login", "users.version", "profiles.bio"]
+    );
+}
+
+#[test]
+fn test_sql_column_refs() {
+    use serialize_fields::SqlColumn;
+
+    let selector: UserRowSerializeFieldSelector = "id".parse().unwrap();
+    assert_eq!(
+        selector.to_sql_column_refs(),
+        [
+            SqlColumn { table: Some("users"), column: "id" },
+            SqlColumn { table: Some("users"), column: "version" },
+        ]
+    );
+    assert_eq!(SqlColumn::parse("id"), SqlColumn { table: None, column: "id" });
+    assert_eq!(
+        SqlColumn::parse("public.users.id"),
+        SqlColumn { table: Some("public.users"), column: "id" }
+    );
+}
+
+#[derive(SerializeFields, Serialize)]
+#[serialize_fields(diesel_table = schema::accounts)]
+struct AccountRow {
+    id: u32,
+    #[serialize_fields(column = "owners.name")]
+    owner: String,
+}
+
+#[test]
+fn test_diesel_table() {
+    let selector = AccountRowSerializeFieldSelector::all();
+    assert_eq!(selector.to_sql_columns(), ["accounts.id", "owners.name"]);
+
+    let row = AccountRow {
+        id: 7,
+        owner: "alice".to_string(),
+    };
+    assert_eq!(to_value(&row, &selector), json!({"id": 7, "owner": "alice"}));
+}
+
+#[test]
+fn test_es_source_filter() {
+    let selector: RenamedUserSerializeFieldSelector = "user_name".parse().unwrap();
+    assert_eq!(selector.to_es_source_filter(), json!({"includes": ["userName"]}));
+
+    // Mostly-everything selections are written as exclusions
+    let selector = RenamedUserSerializeFieldSelector::all_except(&["id"]);
+    assert_eq!(selector.to_es_source_filter(), json!({"excludes": ["id"]}));
+    let selector: RenamedUserSerializeFieldSelector = "-profile".parse().unwrap();
+    assert_eq!(selector.to_es_source_filter(), json!({"excludes": ["profile"]}));
+    let selector = RenamedUserSerializeFieldSelector::all();
+    assert_eq!(selector.to_es_source_filter(), json!({"excludes": []}));
+
+    assert_eq!(RenamedUserSerializeFieldSelector::new().to_es_source_filter(), json!(false));
+
+    // Always fields are fetched even when not enabled, and sensitive ones are
+    // excluded unless enabled
+    let selector: UserRowSerializeFieldSelector = "profile.bio".parse().unwrap();
+    assert_eq!(
+        selector.to_es_source_filter(),
+        json!({"includes": ["version", "profile.bio"]})
+    );
+    let selector = PresetUserSerializeFieldSelector::all();
+    assert_eq!(selector.to_es_source_filter(), json!({"excludes": ["profile.phone"]}));
+
+    // A nested field enabled with none of its fields is still listed
+    let mut selector = RenamedUserSerializeFieldSelector::new();
+    selector.profile = Some(RenamedProfileSerializeFieldSelector::new());
+    assert_eq!(selector.to_es_source_filter(), json!({"includes": ["profile"]}));
+    selector.enable_dot_hierarchy("id");
+    selector.enable_dot_hierarchy("user_name");
+    assert_eq!(
+        selector.to_es_source_filter(),
+        json!({"excludes": ["profile.biography"]})
+    );
+}
+
+#[derive(SerializeFields, Serialize)]
+struct Thread {
+    #[serialize_fields(rename = "thread-id")]
+    id: u32,
+    #[serialize_fields(always)]
+    title: String,
+    posts: Vec<ThreadPost>,
+}
+
+#[derive(SerializeFields, Serialize)]
+struct ThreadPost {
+    body: String,
+    #[serialize_fields(rename = "authorName")]
+    author: String,
+}
+
+#[test]
+fn test_path_list() {
+    use serialize_fields::{PathListFormat, SequencePaths};
+
+    let selector: ThreadSerializeFieldSelector = "id,posts.*".parse().unwrap();
+    assert_eq!(
+        selector.to_path_list(),
+        ["thread-id", "title", "posts.body", "posts.authorName"]
+    );
+    assert_eq!(
+        selector.to_path_list_with(&PathListFormat::FIRESTORE),
+        ["`thread-id`", "title", "posts"]
+    );
+    let format = PathListFormat {
+        separator: "/",
+        sequences: SequencePaths::Traverse,
+        quote_segments: false,
+    };
+    assert_eq!(
+        selector.to_path_list_with(&format),
+        ["thread-id", "title", "posts/body", "posts/authorName"]
+    );
+
+    let thread = Thread {
+        id: 1,
+        title: "Hello".to_string(),
+        posts: vec![ThreadPost {
+            body: "First".to_string(),
+            author: "alice".to_string(),
+        }],
+    };
+    assert_eq!(to_value(&thread, &ThreadSerializeFieldSelector::new()), json!({"title": "Hello"}));
+}
+
+#[test]
+fn test_envelope_reports_serialized_paths() {
+    use serialize_fields::SerializeFieldsEnvelope;
+
+    let thread = Thread {
+        id: 1,
+        title: "Hello".to_string(),
+        posts: vec![ThreadPost {
+            body: "First".to_string(),
+            author: "alice".to_string(),
+        }],
+    };
+    let selector: ThreadSerializeFieldSelector = "id,posts.author".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeFieldsEnvelope(&thread, &selector)).unwrap(),
+        json!({
+            "data": {"thread-id": 1, "title": "Hello", "posts": [{"authorName": "alice"}]},
+            "_fields": ["thread-id", "title", "posts.authorName"],
+        })
+    );
+}
+
+#[derive(SerializeFields, Serialize, Debug, PartialEq)]
+#[serialize_fields(update)]
+struct Settings {
+    #[serialize_fields(always)]
+    version: u32,
+    theme: String,
+    notifications: NotificationSettings,
+    devices: Vec<Device>,
+}
+
+#[derive(SerializeFields, Serialize, Debug, PartialEq)]
+#[serialize_fields(update)]
+struct NotificationSettings {
+    email: bool,
+    push: bool,
+}
+
+#[derive(SerializeFields, Serialize, Debug, PartialEq, Clone)]
+struct Device {
+    name: String,
+}
+
+fn create_settings(version: u32, theme: &str, flags: bool, device: &str) -> Settings {
+    Settings {
+        version,
+        theme: theme.to_string(),
+        notifications: NotificationSettings {
+            email: flags,
+            push: flags,
+        },
+        devices: vec![Device {
+            name: device.to_string(),
+        }],
+    }
+}
+
+#[test]
+fn test_apply_update() {
+    let patch = create_settings(9, "dark", true, "phone");
+
+    let mut stored = create_settings(1, "light", false, "laptop");
+    let mask: SettingsSerializeFieldSelector = "theme,notifications.push".parse().unwrap();
+    mask.apply_update(&mut stored, &patch);
+    assert_eq!(stored.version, 1);
+    assert_eq!(stored.theme, "dark");
+    assert_eq!(stored.notifications, NotificationSettings { email: false, push: true });
+    assert_eq!(stored.devices[0].name, "laptop");
+
+    // Containers of nested structs are replaced as a whole
+    let mask: SettingsSerializeFieldSelector = "devices.name,version".parse().unwrap();
+    mask.apply_update(&mut stored, &patch);
+    assert_eq!(stored.version, 9);
+    assert_eq!(stored.devices, patch.devices);
+
+    let mut stored = create_settings(1, "light", false, "laptop");
+    SettingsSerializeFieldSelector::all().apply_update(&mut stored, &patch);
+    assert_eq!(stored, patch);
+}
+
+#[test]
+fn test_update_columns() {
+    let selector: UserRowSerializeFieldSelector = "user_name,profile.bio".parse().unwrap();
+    assert_eq!(selector.to_update_columns(), ["users.login", "profiles.bio"]);
+    assert_eq!(
+        selector.to_sql_columns(),
+        ["users.login", "users.version", "profiles.bio"]
+    );
+
+    let selector: UserRowSerializeFieldSelector = "version".parse().unwrap();
+    assert_eq!(selector.to_update_columns(), ["users.version"]);
+}
+
+#[test]
+fn test_json_paths() {
+    let selector: ThreadSerializeFieldSelector = "id,posts.author".parse().unwrap();
+    assert_eq!(
+        selector.to_json_paths(),
+        ["$['thread-id']", "$.title", "$.posts[*].authorName"]
+    );
+
+    let selector: RenamedUserSerializeFieldSelector = "profile.bio".parse().unwrap();
+    assert_eq!(selector.to_json_paths(), ["$.profile.biography"]);
+}
+
+#[test]
+fn test_selector_from_json_shape() {
+    let shape = serde_json::json!({
+        "thread-id": 1,
+        "unknown": true,
+        "posts": [{"authorName": "alice"}, {"body": "", "extra": 0}],
+    });
+    let selector = ThreadSerializeFieldSelector::from_json_shape(&shape);
+    assert_eq!(selector.enabled_paths(), ["id", "posts.body", "posts.author"]);
+
+    // A nested field without an object shape is enabled in full
+    let selector = ThreadSerializeFieldSelector::from_json_shape(&serde_json::json!({"posts": null}));
+    assert_eq!(selector.enabled_paths(), ["posts.body", "posts.author"]);
+
+    assert!(ThreadSerializeFieldSelector::from_json_shape(&serde_json::json!(1)).is_empty());
+}
+
+#[test]
+fn test_redacted_keeps_shape() {
+    use serialize_fields::SerializeFieldsRedacted;
+
+    // Sensitive fields are redacted even when enabled
+    let customer = create_customer();
+    let mut selector = CustomerSerializeFieldSelector::all();
+    selector.enable_sensitive("email");
+    selector.enable_sensitive("billing");
+    assert_eq!(
+        serde_json::to_value(SerializeFieldsRedacted(&customer, &selector, "***")).unwrap(),
+        json!({"id": 3, "email": "***", "billing": {"biography": "***"}})
+    );
+
+    let user = create_renamed_user();
+    let selector: RenamedUserSerializeFieldSelector = "id".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&user, &selector).redacted("[hidden]")).unwrap(),
+        json!({"id": 1, "userName": "[hidden]", "profile": {"biography": "[hidden]"}})
+    );
+
+    // Inside a redacted value, structure is kept and scalars are replaced
+    let thread = Thread {
+        id: 1,
+        title: "Hello".to_string(),
+        posts: vec![ThreadPost {
+            body: "First".to_string(),
+            author: "alice".to_string(),
+        }],
+    };
+    let selector: ThreadSerializeFieldSelector = "posts.body".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&thread, &selector).redacted("-")).unwrap(),
+        json!({"thread-id": "-", "title": "Hello", "posts": [{"body": "First", "authorName": "-"}]})
+    );
+}
+
+fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((user, domain)) => format!("{}***@{}", &user[..1], domain),
+        None => "***".to_string(),
+    }
+}
+
+fn round_score(score: &f64) -> f64 {
+    (score * 10.0).round() / 10.0
+}
+
+#[derive(SerializeFields, Serialize)]
+struct Contact {
+    #[serialize_fields(transform = "mask_email")]
+    email: String,
+    #[serialize_fields(transform = "round_score", always)]
+    score: f64,
+}
+
+#[test]
+fn test_transform() {
+    let contact = Contact {
+        email: "alice@example.com".to_string(),
+        score: 0.8765,
+    };
+    let selector = ContactSerializeFieldSelector::all();
+    assert_eq!(
+        to_value(&contact, &selector),
+        json!({"email": "a***@example.com", "score": 0.9})
+    );
+    assert_eq!(
+        to_value(&contact, &ContactSerializeFieldSelector::new()),
+        json!({"score": 0.9})
+    );
+}
+
+/// A third-party-like type that does not derive `SerializeFields`.
+#[derive(Serialize)]
+struct Timestamp(u64);
+
+#[derive(SerializeFields, Serialize)]
+struct Event {
+    name: String,
+    #[serialize_fields(leaf)]
+    at: Timestamp,
+    #[serialize_fields(leaf)]
+    history: Option<Vec<Timestamp>>,
+}
+
+#[test]
+fn test_leaf_fields_serialize_whole() {
+    let event = Event {
+        name: "deploy".to_string(),
+        at: Timestamp(1700000000),
+        history: Some(vec![Timestamp(1600000000)]),
+    };
+    let selector: EventSerializeFieldSelector = "at,history".parse().unwrap();
+    assert_eq!(
+        to_value(&event, &selector),
+        json!({"at": 1700000000, "history": [1600000000]})
+    );
+    assert!("at.seconds".parse::<EventSerializeFieldSelector>().is_err());
+}
+
+mod billing {
+    use serde::Serialize;
+
+    /// A type from another crate, which cannot derive `SerializeFields`.
+    pub struct Invoice {
+        pub number: u32,
+        pub total: Money,
+        pub notes: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    pub struct Money {
+        pub amount: i64,
+        pub currency: String,
+    }
+}
+
+#[derive(SerializeFields)]
+#[serialize_fields(remote = "billing::Invoice")]
+struct InvoiceDef {
+    #[serialize_fields(rename = "invoiceNumber")]
+    number: u32,
+    #[serialize_fields(leaf)]
+    total: billing::Money,
+    notes: Option<String>,
+}
+
+#[test]
+fn test_remote_serializes_mirrored_fields() {
+    use serialize_fields::SerializeRemoteFields;
+
+    let invoice = billing::Invoice {
+        number: 7,
+        total: billing::Money { amount: 1250, currency: "EUR".to_string() },
+        notes: None,
+    };
+    let selector: InvoiceDefSerializeFieldSelector = "number,total".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeRemoteFields::<InvoiceDef>(&invoice, &selector)).unwrap(),
+        json!({"invoiceNumber": 7, "total": {"amount": 1250, "currency": "EUR"}})
+    );
+    assert_eq!(InvoiceDefSerializeFieldSelector::all().enabled_paths(), ["number", "total", "notes"]);
+}
+
+#[derive(SerializeFields, Serialize, Clone, PartialEq)]
+#[serialize_fields(diff)]
+struct Order {
+    id: u32,
+    status: String,
+    shipping: Address,
+    items: Vec<Address>,
+    #[serialize_fields(sensitive)]
+    card: String,
+}
+
+#[derive(SerializeFields, Serialize, Clone, PartialEq)]
+#[serialize_fields(diff)]
+struct Address {
+    city: String,
+    zip: String,
+}
+
+#[test]
+fn test_diff_enables_changed_fields() {
+    let address = Address { city: "Paris".to_string(), zip: "75001".to_string() };
+    let old = Order {
+        id: 1,
+        status: "pending".to_string(),
+        shipping: address.clone(),
+        items: vec![address],
+        card: "4242".to_string(),
+    };
+    let mut new = old.clone();
+    new.status = "shipped".to_string();
+    new.shipping.zip = "75002".to_string();
+    new.card = "1111".to_string();
+
+    let changed = OrderSerializeFieldSelector::diff(&old, &new);
+    assert_eq!(changed.enabled_paths(), ["status", "shipping.zip"]);
+    assert_eq!(
+        to_value(&new, &changed),
+        json!({"status": "shipped", "shipping": {"zip": "75002"}})
+    );
+
+    new.items[0].city = "Lyon".to_string();
+    assert_eq!(
+        OrderSerializeFieldSelector::diff(&old, &new).enabled_paths(),
+        ["status", "shipping.zip", "items.city", "items.zip"]
+    );
+    assert!(OrderSerializeFieldSelector::diff(&old, &old).is_empty());
+}
+
+#[derive(SerializeFields, Serialize, Clone, Debug, PartialEq)]
+#[serialize_fields(partial)]
+struct Subscriber {
+    id: u32,
+    name: String,
+    address: SubscriberAddress,
+    tags: Vec<String>,
+}
+
+#[derive(SerializeFields, Serialize, Clone, Debug, PartialEq)]
+#[serialize_fields(partial)]
+struct SubscriberAddress {
+    city: String,
+    zip: String,
+}
+
+#[test]
+fn test_partial_from_selected_and_merge_into() {
+    let subscriber = Subscriber {
+        id: 1,
+        name: "Alice".to_string(),
+        address: SubscriberAddress { city: "Paris".to_string(), zip: "75001".to_string() },
+        tags: vec!["admin".to_string()],
+    };
+    let selector: SubscriberSerializeFieldSelector = "name,address.city".parse().unwrap();
+    let partial = PartialSubscriber::from_selected(&subscriber, &selector);
+    assert_eq!(partial.id, None);
+    assert_eq!(partial.name.as_deref(), Some("Alice"));
+    assert_eq!(partial.address.as_ref().unwrap().city.as_deref(), Some("Paris"));
+    assert_eq!(partial.address.as_ref().unwrap().zip, None);
+    assert_eq!(partial.tags, None);
+
+    let mut other = Subscriber {
+        id: 2,
+        name: "Bob".to_string(),
+        address: SubscriberAddress { city: "Lyon".to_string(), zip: "69001".to_string() },
+        tags: vec![],
+    };
+    partial.merge_into(&mut other);
+    assert_eq!(
+        other,
+        Subscriber {
+            id: 2,
+            name: "Alice".to_string(),
+            address: SubscriberAddress { city: "Paris".to_string(), zip: "69001".to_string() },
+            tags: vec![],
+        }
+    );
+
+    let mut unchanged = other.clone();
+    PartialSubscriber::default().merge_into(&mut unchanged);
+    assert_eq!(unchanged, other);
+}
+
+#[derive(SerializeFields, Serialize, Debug, PartialEq)]
+#[serialize_fields(clear)]
+struct Page {
+    #[serialize_fields(always)]
+    slug: String,
+    title: String,
+    author: Option<PageAuthor>,
+    comments: Vec<PageAuthor>,
+    #[serialize_fields(skip)]
+    draft_notes: String,
+}
+
+#[derive(SerializeFields, Serialize, Debug, PartialEq)]
+#[serialize_fields(clear)]
+struct PageAuthor {
+    name: String,
+    email: Option<String>,
+}
+
+#[test]
+fn test_clear_unselected() {
+    let author = |name: &str| PageAuthor {
+        name: name.to_string(),
+        email: Some(format!("{}@example.com", name)),
+    };
+    let mut page = Page {
+        slug: "hello".to_string(),
+        title: "Hello".to_string(),
+        author: Some(author("alice")),
+        comments: vec![author("bob"), author("carol")],
+        draft_notes: "todo".to_string(),
+    };
+    let selector: PageSerializeFieldSelector = "author.name,comments.email".parse().unwrap();
+    page.clear_unselected(&selector);
+    assert_eq!(
+        page,
+        Page {
+            slug: "hello".to_string(),
+            title: String::new(),
+            author: Some(PageAuthor { name: "alice".to_string(), email: None }),
+            comments: vec![
+                PageAuthor { name: String::new(), email: Some("bob@example.com".to_string()) },
+                PageAuthor { name: String::new(), email: Some("carol@example.com".to_string()) },
+            ],
+            draft_notes: String::new(),
+        }
+    );
+
+    page.clear_unselected(&PageSerializeFieldSelector::new());
+    assert_eq!((page.author, page.comments.len()), (None, 0));
+}
+
+#[derive(SerializeFields, Serialize, Clone, PartialEq, Hash)]
+#[serialize_fields(eq, hash)]
+struct Listing {
+    #[serialize_fields(always)]
+    id: u32,
+    title: String,
+    views: u64,
+    tags: Vec<ListingTag>,
+}
+
+#[derive(SerializeFields, Serialize, Clone, PartialEq, Hash)]
+#[serialize_fields(eq, hash)]
+struct ListingTag {
+    name: String,
+    weight: u32,
+}
+
+#[test]
+fn test_eq_and_hash_selected() {
+    fn hash(listing: &Listing, selector: &ListingSerializeFieldSelector) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        listing.hash_selected(selector, &mut hasher);
+        std::hash::Hasher::finish(&hasher)
+    }
+
+    let listing = Listing {
+        id: 1,
+        title: "Lamp".to_string(),
+        views: 10,
+        tags: vec![ListingTag { name: "home".to_string(), weight: 1 }],
+    };
+    let mut changed = listing.clone();
+    changed.views = 20;
+    changed.tags[0].weight = 5;
+
+    let selector: ListingSerializeFieldSelector = "title,tags.name".parse().unwrap();
+    assert!(listing.eq_selected(&changed, &selector));
+    assert_eq!(hash(&listing, &selector), hash(&changed, &selector));
+
+    let selector: ListingSerializeFieldSelector = "tags.weight".parse().unwrap();
+    assert!(!listing.eq_selected(&changed, &selector));
+
+    changed = listing.clone();
+    changed.id = 2;
+    assert!(!listing.eq_selected(&changed, &ListingSerializeFieldSelector::new()));
+    assert_ne!(
+        hash(&listing, &ListingSerializeFieldSelector::new()),
+        hash(&changed, &ListingSerializeFieldSelector::new())
+    );
+}
+
+#[derive(SerializeFields, Serialize, Debug, PartialEq)]
+#[serialize_fields(clone)]
+struct Job {
+    #[serialize_fields(always)]
+    id: u32,
+    payload: String,
+    owner: JobOwner,
+    steps: Vec<JobOwner>,
+    #[serialize_fields(skip)]
+    attempts: u32,
+}
+
+#[derive(SerializeFields, Serialize, Debug, Default, PartialEq)]
+#[serialize_fields(clone)]
+struct JobOwner {
+    name: String,
+    email: String,
+}
+
+#[test]
+fn test_clone_selected() {
+    let owner = |name: &str| JobOwner {
+        name: name.to_string(),
+        email: format!("{}@example.com", name),
+    };
+    let job = Job {
+        id: 7,
+        payload: "data".to_string(),
+        owner: owner("alice"),
+        steps: vec![owner("bob")],
+        attempts: 3,
+    };
+    let selector: JobSerializeFieldSelector = "owner.name,steps.email".parse().unwrap();
+    assert_eq!(
+        job.clone_selected(&selector),
+        Job {
+            id: 7,
+            payload: String::new(),
+            owner: JobOwner { name: "alice".to_string(), email: String::new() },
+            steps: vec![JobOwner { name: String::new(), email: "bob@example.com".to_string() }],
+            attempts: 0,
+        }
+    );
+
+    let selector = JobSerializeFieldSelector::new();
+    let trimmed = job.clone_selected(&selector);
+    assert_eq!((trimmed.id, trimmed.owner.name.as_str(), trimmed.steps.len()), (7, "", 0));
+}
+
+#[derive(SerializeFields, Serialize)]
+#[serialize_fields(present)]
+struct LoadedRow {
+    id: u64,
+    display_name: Option<String>,
+    bio: String,
+    #[serialize_fields(sensitive)]
+    password_hash: Option<String>,
+    owner: Option<LoadedOwner>,
+    members: Vec<LoadedOwner>,
+}
+
+#[derive(SerializeFields, Serialize)]
+#[serialize_fields(present)]
+struct LoadedOwner {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+#[test]
+fn test_selector_from_present() {
+    let owner = |name: Option<&str>, email: Option<&str>| LoadedOwner {
+        name: name.map(str::to_string),
+        email: email.map(str::to_string),
+    };
+    let row = LoadedRow {
+        id: 1,
+        display_name: Some("Alice".to_string()),
+        bio: String::new(),
+        password_hash: Some("hash".to_string()),
+        owner: Some(owner(None, None)),
+        members: vec![owner(Some("bob"), None), owner(None, Some("carol@example.com"))],
+    };
+    let selector = LoadedRowSerializeFieldSelector::from_present(&row);
+    assert_eq!(
+        selector.enabled_paths(),
+        ["id", "display_name", "members.name", "members.email"]
+    );
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&row, &selector)).unwrap(),
+        json!({
+            "id": 1,
+            "display_name": "Alice",
+            "members": [{"name": "bob", "email": null}, {"name": null, "email": "carol@example.com"}],
+        })
+    );
+}
+
+#[derive(SerializeFields, Serialize)]
+struct Notification {
+    #[serialize_fields(required)]
+    id: u64,
+    #[serialize_fields(required, always)]
+    kind: String,
+    message: String,
+    #[serialize_fields(required)]
+    target: NotificationTarget,
+}
+
+#[derive(SerializeFields, Serialize)]
+struct NotificationTarget {
+    #[serialize_fields(required)]
+    url: String,
+    label: String,
+}
+
+#[test]
+fn test_validate_required() {
+    let selector: NotificationSerializeFieldSelector = "id,target.url".parse().unwrap();
+    assert_eq!(selector.validate_required(), Ok(()));
+    let notification = Notification {
+        id: 1,
+        kind: "mention".to_string(),
+        message: "Hi".to_string(),
+        target: NotificationTarget {
+            url: "/posts/1".to_string(),
+            label: "Post".to_string(),
+        },
+    };
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&notification, &selector)).unwrap(),
+        json!({"id": 1, "kind": "mention", "target": {"url": "/posts/1"}})
+    );
+
+    let selector: NotificationSerializeFieldSelector = "message,target.label".parse().unwrap();
+    let error = selector.validate_required().unwrap_err();
+    assert_eq!(error.paths, ["id", "target.url"]);
+    assert_eq!(
+        error.to_string(),
+        "required fields are not selected: `id`, `target.url`"
+    );
+
+    let error = NotificationSerializeFieldSelector::new().validate_required().unwrap_err();
+    assert_eq!(error.paths, ["id", "target"]);
+}
@@ -14,14 +14,14 @@ struct SimpleStruct {
     optional_field: Option<String>,
 }
 
-#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq, Default)]
 struct NestedStruct {
     id: u32,
     inner: InnerStruct,
     optional_inner: Option<InnerStruct>,
 }
 
-#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq)]
+#[derive(SerializeFields, Serialize, Deserialize, Debug, PartialEq, Default)]
 struct InnerStruct {
     value: String,
     number: u32,
@@ -330,6 +330,36 @@ fn test_utility_functions() {
     assert!(selector.optional_field.is_none());
 }
 
+#[test]
+fn test_field_list_exclusions() {
+    use serialize_fields::utils::{self, FieldSpec};
+
+    assert_eq!(
+        utils::parse_field_specs("id,-inner, !optional_inner.value,-"),
+        vec![
+            FieldSpec::Include("id"),
+            FieldSpec::Exclude("inner"),
+            FieldSpec::Exclude("optional_inner.value"),
+        ]
+    );
+
+    // Only exclusions: start from every field
+    let selector: NestedStructSerializeFieldSelector =
+        utils::create_selector_from_list("-inner,!optional_inner.value");
+    assert_eq!(
+        selector,
+        NestedStructSerializeFieldSelector::all_except(&["inner", "optional_inner.value"])
+    );
+
+    // Mixed: inclusions first, then exclusions
+    let selector: NestedStructSerializeFieldSelector =
+        utils::create_selector_from_list("id,inner.value,inner.number,-inner.number");
+    assert_eq!(
+        selector,
+        serialize_fields::select!(NestedStruct { id, inner: { value } })
+    );
+}
+
 #[test]
 fn test_empty_field_hierarchy() {
     let mut selector = SimpleStructSerializeFieldSelector::new();
@@ -548,4 +578,1657 @@ fn test_field_enum_json_schema() {
     assert!(nested_json.contains("\"inner.number\""));
     assert!(nested_json.contains("\"optional_inner.value\""));
     assert!(nested_json.contains("\"optional_inner.number\""));
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn test_selector_json_schema() {
+    let schema = schemars::schema_for!(NestedStructSerializeFieldSelector);
+    let json = serde_json::to_value(&schema).unwrap();
+
+    assert_eq!(json["type"], "object");
+    assert_eq!(json["properties"]["id"], serde_json::json!({"not": {"const": false}}));
+    // Nested selectors are referenced through definitions
+    assert!(json["properties"]["inner"]["$ref"]
+        .as_str()
+        .unwrap()
+        .ends_with("InnerStructSerializeFieldSelector"));
+    assert!(json.to_string().contains("optional_inner"));
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn test_filtered_json_schema() {
+    use serialize_fields::FieldSelector;
+
+    #[derive(SerializeFields, Serialize, schemars::JsonSchema)]
+    struct Order {
+        id: u32,
+        note: String,
+        customer: Option<Customer>,
+    }
+
+    #[derive(SerializeFields, Serialize, schemars::JsonSchema)]
+    struct Customer {
+        name: String,
+        email: String,
+    }
+
+    let selector: OrderSerializeFieldSelector = "id,customer.name".parse().unwrap();
+    let mut generator = schemars::SchemaGenerator::default();
+    let schema = serde_json::to_value(selector.filtered_schema::<Order>(&mut generator)).unwrap();
+
+    let properties = schema["properties"].as_object().unwrap();
+    assert_eq!(properties.keys().collect::<Vec<_>>(), ["customer", "id"]);
+    assert!(schema.get("required").is_none());
+
+    // The nested struct is inlined with only its selected fields
+    let customer = schema.to_string();
+    assert!(customer.contains("\"name\""));
+    assert!(!customer.contains("\"email\""));
+    assert!(!customer.contains("\"note\""));
+    assert!(generator.definitions().get("Customer").is_none());
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn test_serialize_fields_schema_has_no_required_fields() {
+    #[derive(SerializeFields, Serialize, schemars::JsonSchema)]
+    struct Invoice {
+        id: u32,
+        lines: Vec<InvoiceLine>,
+    }
+
+    #[derive(SerializeFields, Serialize, schemars::JsonSchema)]
+    struct InvoiceLine {
+        amount: u64,
+    }
+
+    let schema = schemars::schema_for!(SerializeFields<'static, Invoice, InvoiceSerializeFieldSelector>);
+    let json = serde_json::to_value(&schema).unwrap();
+    assert_eq!(json["title"], "InvoiceFields");
+    assert!(!json.to_string().contains("\"required\""));
+    assert!(json["properties"]["lines"]["items"]["properties"]["amount"].is_object());
+
+    // The plain schema of the struct keeps its required fields
+    let plain = serde_json::to_value(schemars::schema_for!(Invoice)).unwrap();
+    assert!(plain["required"].is_array());
+}
+
+#[test]
+fn test_field_enum_as_str_and_all() {
+    assert_eq!(SimpleStructField::ALL, &["id", "name", "optional_field"]);
+    assert_eq!(
+        NestedStructField::ALL,
+        &[
+            "id",
+            "inner.value",
+            "inner.number",
+            "optional_inner.value",
+            "optional_inner.number",
+        ]
+    );
+
+    let field = NestedStructField::OptionalInner(InnerStructField::Number);
+    assert_eq!(field.as_str(), "optional_inner.number");
+    assert_eq!(field.as_str(), field.as_dot_path());
+    assert_eq!(NestedStructField::ALL[field.index()], field.as_str());
+
+    // Every catalog entry parses back to the variant it came from
+    for path in NestedStructField::ALL {
+        assert_eq!(path.parse::<NestedStructField>().unwrap().as_str(), *path);
+    }
+}
+
+#[test]
+fn test_field_paths_catalog() {
+    assert_eq!(
+        NestedStructSerializeFieldSelector::FIELD_PATHS,
+        NestedStructField::ALL
+    );
+
+    fn unknown_fields<S: serialize_fields::FieldSelector>(requested: &str) -> Vec<&str> {
+        serialize_fields::utils::parse_field_list(requested)
+            .into_iter()
+            .filter(|field| !S::FIELD_PATHS.contains(field))
+            .collect()
+    }
+
+    assert_eq!(
+        unknown_fields::<NestedStructSerializeFieldSelector>("id,inner.value,inner.missing"),
+        vec!["inner.missing"]
+    );
+}
+
+#[test]
+fn test_typed_enable_methods() {
+    let data = create_nested_struct();
+
+    let mut selector = NestedStructSerializeFieldSelector::new();
+    selector
+        .enable_id()
+        .enable_inner(|inner| {
+            inner.enable_number();
+        })
+        .enable_optional_inner(|inner| {
+            inner.enable_value();
+        });
+
+    let mut expected = NestedStructSerializeFieldSelector::new();
+    expected.enable_dot_hierarchy("id");
+    expected.enable_dot_hierarchy("inner.number");
+    expected.enable_dot_hierarchy("optional_inner.value");
+    assert_eq!(selector, expected);
+
+    // Calling the nested method again extends the existing nested selector
+    selector.enable_inner(|inner| {
+        inner.enable_value();
+    });
+    assert!(contains!(selector, inner.value));
+    assert!(contains!(selector, inner.number));
+
+    let json = serde_json::to_value(SerializeFields(&data, &selector)).unwrap();
+    assert_eq!(json["inner"].as_object().unwrap().len(), 2);
+}
+
+#[test]
+fn test_selector_builder() {
+    let selector = NestedStructSerializeFieldSelector::builder()
+        .id()
+        .inner(|inner| inner.value().number())
+        .optional_inner(|inner| inner.number())
+        .build();
+
+    let mut expected = NestedStructSerializeFieldSelector::new();
+    expected.enable_dot_hierarchy("id");
+    expected.enable_dot_hierarchy("inner.value");
+    expected.enable_dot_hierarchy("inner.number");
+    expected.enable_dot_hierarchy("optional_inner.number");
+    assert_eq!(selector, expected);
+
+    // A builder can continue from an existing selector
+    let extended: NestedStructSerializeFieldSelector =
+        NestedStructSerializeFieldSelectorBuilder::from(selector)
+            .optional_inner(|inner| inner.value())
+            .into();
+    assert!(contains!(extended, optional_inner.value));
+    assert!(contains!(extended, optional_inner.number));
+
+    fn empty<S: serialize_fields::BuildSelector>() -> S {
+        S::builder().into()
+    }
+    assert_eq!(
+        empty::<NestedStructSerializeFieldSelector>(),
+        NestedStructSerializeFieldSelector::new()
+    );
+}
+
+#[test]
+fn test_select_macro() {
+    let selector = serialize_fields::select!(NestedStruct {
+        id,
+        inner: { value },
+        optional_inner: { value, number },
+    });
+
+    let expected = NestedStructSerializeFieldSelector::builder()
+        .id()
+        .inner(|inner| inner.value())
+        .optional_inner(|inner| inner.value().number())
+        .build();
+    assert_eq!(selector, expected);
+
+    let empty = serialize_fields::select!(SimpleStruct {});
+    assert_eq!(empty, SimpleStructSerializeFieldSelector::new());
+}
+
+#[test]
+fn test_all_enables_every_field() {
+    let data = create_nested_struct();
+    let selector = NestedStructSerializeFieldSelector::all();
+
+    assert!(contains!(selector, id));
+    assert!(contains!(selector, inner.value));
+    assert!(contains!(selector, optional_inner.number));
+
+    let json = serde_json::to_value(SerializeFields(&data, &selector)).unwrap();
+    assert_eq!(json, serde_json::to_value(&data).unwrap());
+
+    let mut enabled = NestedStructSerializeFieldSelector::new();
+    enabled.enable_dot_hierarchy("inner.value");
+    enabled.enable_all();
+    assert_eq!(enabled, selector);
+}
+
+#[test]
+fn test_disable_fields() {
+    let mut selector = NestedStructSerializeFieldSelector::all();
+    selector.disable_dot_hierarchy("id");
+    selector.disable(&["inner", "value"]);
+    selector.disable_dot_hierarchy("optional_inner");
+    selector.disable_dot_hierarchy("unknown.path");
+
+    let expected = serialize_fields::select!(NestedStruct { inner: { number } });
+    assert_eq!(selector, expected);
+
+    // Disabling the last nested field removes the nested selector
+    selector.disable_enum(NestedStructField::Inner(InnerStructField::Number));
+    assert!(selector.inner.is_none());
+    assert_eq!(selector, NestedStructSerializeFieldSelector::new());
+}
+
+#[test]
+fn test_all_except() {
+    let data = create_nested_struct();
+    let selector = NestedStructSerializeFieldSelector::all_except(&["inner", "optional_inner.value"]);
+
+    let json = serde_json::to_value(SerializeFields(&data, &selector)).unwrap();
+    assert!(json.get("id").is_some());
+    assert!(json.get("inner").is_none());
+    assert_eq!(json["optional_inner"].as_object().unwrap().len(), 1);
+    assert!(json["optional_inner"].get("number").is_some());
+}
+
+#[test]
+fn test_wildcard_enables_one_level() {
+    let mut selector = NestedStructSerializeFieldSelector::new();
+    selector.enable_dot_hierarchy("inner.*");
+    assert_eq!(
+        selector,
+        serialize_fields::select!(NestedStruct { inner: { value, number } })
+    );
+
+    // At the top level only plain fields are enabled
+    let mut top = NestedStructSerializeFieldSelector::new();
+    top.enable_dot_hierarchy("*");
+    assert_eq!(top, serialize_fields::select!(NestedStruct { id }));
+}
+
+#[test]
+fn test_deep_wildcard_enables_subtree() {
+    let mut selector = NestedStructSerializeFieldSelector::new();
+    selector.enable_dot_hierarchy("optional_inner.**");
+    assert_eq!(
+        selector,
+        serialize_fields::select!(NestedStruct { optional_inner: { value, number } })
+    );
+
+    let mut everything = NestedStructSerializeFieldSelector::new();
+    everything.enable_dot_hierarchy("**");
+    assert_eq!(everything, NestedStructSerializeFieldSelector::all());
+}
+
+#[test]
+fn test_merge_selectors() {
+    let base = serialize_fields::select!(NestedStruct { id, inner: { value } });
+    let requested = serialize_fields::select!(NestedStruct {
+        inner: { number },
+        optional_inner: { value },
+    });
+    let expected = serialize_fields::select!(NestedStruct {
+        id,
+        inner: { value, number },
+        optional_inner: { value },
+    });
+
+    let mut merged = base.clone();
+    merged.merge(&requested);
+    assert_eq!(merged, expected);
+    assert_eq!(&base | &requested, expected);
+    assert_eq!(requested.clone() | base.clone(), expected);
+
+    let mut assigned = base;
+    assigned |= requested;
+    assert_eq!(assigned, expected);
+}
+
+#[test]
+fn test_intersect_selectors() {
+    let requested = serialize_fields::select!(NestedStruct {
+        id,
+        inner: { value, number },
+        optional_inner: { number },
+    });
+    let allowed = serialize_fields::select!(NestedStruct {
+        inner: { value },
+        optional_inner: { value },
+    });
+    let expected = serialize_fields::select!(NestedStruct { inner: { value } });
+
+    let mut clamped = requested.clone();
+    clamped.intersect(&allowed);
+    assert_eq!(clamped, expected);
+    assert_eq!(&requested & &allowed, expected);
+
+    let mut assigned = requested;
+    assigned &= allowed;
+    assert_eq!(assigned, expected);
+}
+
+#[test]
+fn test_subtract_selectors() {
+    let requested = serialize_fields::select!(NestedStruct {
+        id,
+        inner: { value, number },
+        optional_inner: { number },
+    });
+    let allowed = serialize_fields::select!(NestedStruct {
+        id,
+        inner: { value },
+        optional_inner: { number },
+    });
+
+    // Fields the client asked for but may not see
+    let denied = &requested - &allowed;
+    assert_eq!(denied, serialize_fields::select!(NestedStruct { inner: { number } }));
+
+    let mut remaining = requested.clone();
+    remaining.subtract(&requested);
+    assert_eq!(remaining, NestedStructSerializeFieldSelector::new());
+
+    let mut assigned = requested;
+    assigned -= denied;
+    assert_eq!(assigned, allowed);
+}
+
+#[test]
+fn test_subset_and_superset() {
+    let allowed = serialize_fields::select!(NestedStruct { id, inner: { value, number } });
+    let narrow = serialize_fields::select!(NestedStruct { inner: { value } });
+    let broad = serialize_fields::select!(NestedStruct { inner: { value }, optional_inner: { value } });
+
+    assert!(narrow.is_subset_of(&allowed));
+    assert!(allowed.is_superset_of(&narrow));
+    assert!(!broad.is_subset_of(&allowed));
+    assert!(!allowed.is_superset_of(&broad));
+
+    // Every selector is a subset of itself, and the empty one of everything
+    assert!(allowed.is_subset_of(&allowed));
+    assert!(NestedStructSerializeFieldSelector::new().is_subset_of(&narrow));
+    assert!(NestedStructSerializeFieldSelector::all().is_superset_of(&broad));
+}
+
+#[test]
+fn test_is_empty_and_enabled_leaf_count() {
+    let empty = NestedStructSerializeFieldSelector::new();
+    assert!(empty.is_empty());
+    assert_eq!(empty.enabled_leaf_count(), 0);
+
+    let selector = serialize_fields::select!(NestedStruct { id, inner: { value, number } });
+    assert!(!selector.is_empty());
+    assert_eq!(selector.enabled_leaf_count(), 3);
+
+    assert_eq!(
+        NestedStructSerializeFieldSelector::all().enabled_leaf_count(),
+        NestedStructSerializeFieldSelector::FIELD_PATHS.len()
+    );
+}
+
+#[test]
+fn test_enabled_paths() {
+    let selector = serialize_fields::select!(NestedStruct { id, optional_inner: { number, value } });
+    assert_eq!(
+        selector.enabled_paths(),
+        vec!["id", "optional_inner.value", "optional_inner.number"]
+    );
+    assert_eq!(
+        selector.enabled_paths_with_parents(),
+        vec!["id", "optional_inner", "optional_inner.value", "optional_inner.number"]
+    );
+
+    // Re-enabling every path rebuilds the selector
+    let mut rebuilt = NestedStructSerializeFieldSelector::new();
+    for path in selector.enabled_paths() {
+        rebuilt.enable_dot_hierarchy(&path);
+    }
+    assert_eq!(rebuilt, selector);
+
+    let mut empty_nested = NestedStructSerializeFieldSelector::new();
+    empty_nested.enable_dot_hierarchy("inner");
+    assert_eq!(empty_nested.enabled_paths(), vec!["inner"]);
+}
+
+#[test]
+fn test_selector_display_round_trip() {
+    let selector = serialize_fields::select!(NestedStruct { inner: { number }, id });
+    assert_eq!(selector.to_string(), "id,inner.number");
+
+    let parsed: NestedStructSerializeFieldSelector =
+        serialize_fields::utils::create_selector_from_list(&selector.to_string());
+    assert_eq!(parsed, selector);
+
+    assert_eq!(NestedStructSerializeFieldSelector::new().to_string(), "");
+}
+
+#[test]
+fn test_selector_from_str() {
+    use serialize_fields::{InvalidPath, InvalidPathReason};
+
+    let selector: NestedStructSerializeFieldSelector = "id, inner.*, -inner.value".parse().unwrap();
+    assert_eq!(selector, serialize_fields::select!(NestedStruct { id, inner: { number } }));
+
+    let error = "id,nme,inner..value,id.nested,-inner.bogus"
+        .parse::<NestedStructSerializeFieldSelector>()
+        .unwrap_err();
+    assert_eq!(
+        error.errors,
+        vec![
+            InvalidPath {
+                path: "nme".to_string(),
+                position: 3,
+                reason: InvalidPathReason::Unknown { at_segment: 0, suggestion: None },
+            },
+            InvalidPath {
+                path: "inner..value".to_string(),
+                position: 7,
+                reason: InvalidPathReason::Malformed,
+            },
+            InvalidPath {
+                path: "id.nested".to_string(),
+                position: 20,
+                reason: InvalidPathReason::Unknown { at_segment: 1, suggestion: None },
+            },
+            InvalidPath {
+                path: "inner.bogus".to_string(),
+                position: 31,
+                reason: InvalidPathReason::Unknown { at_segment: 1, suggestion: None },
+            },
+        ]
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid field selection: unknown field `nme` in `nme` at position 3; \
+         malformed path `inner..value` at position 7; \
+         unknown field `nested` in `id.nested` at position 20; \
+         unknown field `bogus` in `inner.bogus` at position 31"
+    );
+}
+
+#[test]
+fn test_try_enable() {
+    use serialize_fields::UnknownField;
+
+    let mut selector = NestedStructSerializeFieldSelector::new();
+    selector.try_enable_dot_hierarchy("id").unwrap();
+    selector.try_enable(&["inner", "value"]).unwrap();
+    selector.try_enable_dot_hierarchy("optional_inner.**").unwrap();
+
+    let error = selector.try_enable_dot_hierarchy("id.invalid_nested").unwrap_err();
+    assert_eq!(
+        error,
+        UnknownField {
+            path: "id.invalid_nested".to_string(),
+            at_segment: 1,
+            suggestion: None,
+        }
+    );
+    assert_eq!(error.segment(), "invalid_nested");
+    assert_eq!(error.to_string(), "unknown field `invalid_nested` in `id.invalid_nested`");
+
+    let error = selector.try_enable(&["inner", "missing"]).unwrap_err();
+    assert_eq!(error.at_segment, 1);
+    assert_eq!(selector.try_enable(&["nope"]).unwrap_err().at_segment, 0);
+
+    // Failed calls leave the selector untouched
+    assert_eq!(
+        selector,
+        serialize_fields::select!(NestedStruct { id, inner: { value }, optional_inner: { value, number } })
+    );
+}
+
+#[test]
+fn test_unknown_field_suggestions() {
+    let mut selector = NestedStructSerializeFieldSelector::new();
+
+    let error = selector.try_enable_dot_hierarchy("inner.valeu").unwrap_err();
+    assert_eq!(error.suggestion.as_deref(), Some("inner.value"));
+    assert_eq!(
+        error.to_string(),
+        "unknown field `valeu` in `inner.valeu`, did you mean `inner.value`?"
+    );
+
+    // Nested struct names are suggested on their own
+    let error = selector.try_enable_dot_hierarchy("optinal_inner").unwrap_err();
+    assert_eq!(error.suggestion.as_deref(), Some("optional_inner"));
+
+    let error = "id,inner.numbr"
+        .parse::<NestedStructSerializeFieldSelector>()
+        .unwrap_err();
+    assert!(error.to_string().ends_with("did you mean `inner.number`?"));
+
+    // Nothing plausible
+    let error = selector.try_enable_dot_hierarchy("completely_unrelated").unwrap_err();
+    assert_eq!(error.suggestion, None);
+}
+
+#[test]
+fn test_selector_deserialize_round_trip() {
+    let selector = serialize_fields::select!(NestedStruct { id, optional_inner: { number } });
+    let json = serde_json::to_value(&selector).unwrap();
+    assert_eq!(json, serde_json::json!({"id": null, "optional_inner": {"number": null}}));
+
+    let parsed: NestedStructSerializeFieldSelector = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, selector);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct FieldsQuery {
+    #[serde(with = "serialize_fields::comma_string")]
+    fields: NestedStructSerializeFieldSelector,
+}
+
+#[test]
+fn test_comma_string_serde_helper() {
+    let query: FieldsQuery = serde_json::from_str(r#"{"fields": "id,inner.value"}"#).unwrap();
+    assert_eq!(
+        query.fields,
+        serialize_fields::select!(NestedStruct { id, inner: { value } })
+    );
+    assert_eq!(
+        serde_json::to_string(&query).unwrap(),
+        r#"{"fields":"id,inner.value"}"#
+    );
+
+    let error = serde_json::from_str::<FieldsQuery>(r#"{"fields": "id,inner.valeu"}"#).unwrap_err();
+    assert!(error.to_string().contains("did you mean `inner.value`?"));
+}
+
+#[test]
+fn test_selector_from_iterator_and_extend() {
+    let requested: Vec<String> = vec!["id".to_string(), "inner.value".to_string()];
+    let mut selector: NestedStructSerializeFieldSelector = requested.iter().collect();
+    assert_eq!(
+        selector,
+        serialize_fields::select!(NestedStruct { id, inner: { value } })
+    );
+
+    selector.extend(["optional_inner.number", "unknown"]);
+    assert_eq!(
+        selector,
+        serialize_fields::select!(NestedStruct { id, inner: { value }, optional_inner: { number } })
+    );
+}
+
+#[test]
+fn test_contains_path() {
+    let selector = serialize_fields::select!(NestedStruct { id, inner: { value } });
+
+    assert!(selector.contains_path("id"));
+    assert!(selector.contains_path("inner"));
+    assert!(selector.contains_path("inner.value"));
+    assert!(selector.contains(&["inner", "value"]));
+    assert!(!selector.contains_path("inner.number"));
+    assert!(!selector.contains_path("optional_inner"));
+    assert!(!selector.contains_path("optional_inner.value"));
+    assert!(!selector.contains_path("id.nested"));
+    assert!(!selector.contains_path("unknown"));
+    assert!(!selector.contains(&[]));
+}
+
+#[test]
+fn test_at_path_navigation() {
+    let mut selector = serialize_fields::select!(NestedStruct { id, inner: { value } });
+
+    let inner = selector.at_path("inner").unwrap();
+    assert_eq!(inner.enabled_paths(), vec!["value"]);
+    assert_eq!(inner.field_paths(), &["value", "number"]);
+    assert!(selector.at_path("optional_inner").is_none());
+    assert!(selector.at_path("id").is_none());
+    assert!(selector.at_path("unknown").is_none());
+    assert_eq!(selector.at_path("").unwrap().enabled_paths(), vec!["id", "inner.value"]);
+
+    let inner = selector.at_path_mut("inner").unwrap();
+    inner.enable_dot_hierarchy("number");
+    inner
+        .as_any_mut()
+        .downcast_mut::<InnerStructSerializeFieldSelector>()
+        .unwrap()
+        .value = None;
+
+    assert_eq!(
+        selector,
+        serialize_fields::select!(NestedStruct { id, inner: { number } })
+    );
+}
+
+#[test]
+fn test_prune_empty_nested_selectors() {
+    let data = create_nested_struct();
+    let mut selector = NestedStructSerializeFieldSelector::new();
+    selector.enable_dot_hierarchy("id");
+    selector.enable_dot_hierarchy("inner");
+    selector.enable_dot_hierarchy("optional_inner.value");
+    selector.optional_inner.as_mut().unwrap().value = None;
+
+    let json = serde_json::to_value(SerializeFields(&data, &selector)).unwrap();
+    assert_eq!(json["inner"], serde_json::json!({}));
+
+    selector.prune();
+    assert_eq!(selector, serialize_fields::select!(NestedStruct { id }));
+
+    // Operations that empty a nested selector drop it on their own
+    let mut intersected = serialize_fields::select!(NestedStruct { inner: { value } });
+    intersected.intersect(&serialize_fields::select!(NestedStruct { inner: { number } }));
+    assert!(intersected.inner.is_none());
+}
+
+#[test]
+fn test_selector_depth_and_limits() {
+    use serialize_fields::{SelectorLimitError, SelectorLimits};
+
+    assert_eq!(NestedStructSerializeFieldSelector::new().depth(), 0);
+    assert_eq!(serialize_fields::select!(NestedStruct { id }).depth(), 1);
+    assert_eq!(serialize_fields::select!(NestedStruct { id, inner: { value } }).depth(), 2);
+
+    let limits = SelectorLimits { max_depth: 2, max_fields: 3 };
+    assert!(limits.parse::<NestedStructSerializeFieldSelector>("id,inner.value").is_ok());
+    assert_eq!(
+        limits.parse::<NestedStructSerializeFieldSelector>("a.b.c"),
+        Err(SelectorLimitError::TooDeep { depth: 3, max_depth: 2 })
+    );
+    assert_eq!(
+        limits.parse::<NestedStructSerializeFieldSelector>("id,id,id,id"),
+        Err(SelectorLimitError::TooManyFields { count: 4, max_fields: 3 })
+    );
+
+    // Wildcards are checked after expansion
+    assert_eq!(
+        limits.parse::<NestedStructSerializeFieldSelector>("**"),
+        Err(SelectorLimitError::TooManyFields { count: 5, max_fields: 3 })
+    );
+    assert!(matches!(
+        limits.parse::<NestedStructSerializeFieldSelector>("nope"),
+        Err(SelectorLimitError::Invalid(_))
+    ));
+
+    assert!(SelectorLimits::default().check(&NestedStructSerializeFieldSelector::all()).is_ok());
+}
+
+#[test]
+fn test_selector_cache_key() {
+    use serialize_fields::FieldSelector;
+
+    assert_eq!(
+        NestedStructSerializeFieldSelector::TYPE_NAME,
+        "integration_tests::NestedStruct"
+    );
+
+    let a: NestedStructSerializeFieldSelector = "id,inner.value".parse().unwrap();
+    let b: NestedStructSerializeFieldSelector = "inner.value,id".parse().unwrap();
+    assert_eq!(a.cache_key(), b.cache_key());
+
+    let c: NestedStructSerializeFieldSelector = "id,inner.number".parse().unwrap();
+    assert_ne!(a.cache_key(), c.cache_key());
+
+    // Same paths on another type give another key
+    let simple: SimpleStructSerializeFieldSelector = "id".parse().unwrap();
+    let nested: NestedStructSerializeFieldSelector = "id".parse().unwrap();
+    assert_ne!(simple.cache_key(), nested.cache_key());
+
+    // Pinned so an accidental change to the key format is noticed
+    assert_eq!(NestedStructSerializeFieldSelector::new().cache_key(), 0xb71f_e4d3_3b50_de65);
+}
+
+#[test]
+fn test_selector_binary_encoding() {
+    use serialize_fields::SelectorDecodeError;
+
+    // id, inner (value, number), optional_inner
+    let selector = serialize_fields::select!(NestedStruct { id, inner: { number } });
+    let bytes = selector.to_bytes();
+    assert_eq!(bytes, vec![0b1101_0000]);
+    assert_eq!(NestedStructSerializeFieldSelector::from_bytes(&bytes).unwrap(), selector);
+
+    let all = NestedStructSerializeFieldSelector::all();
+    assert_eq!(all.to_bytes(), vec![0b1111_1110]);
+    assert_eq!(NestedStructSerializeFieldSelector::from_bytes(&all.to_bytes()).unwrap(), all);
+
+    assert_eq!(NestedStructSerializeFieldSelector::new().to_bytes(), vec![0b0000_0000]);
+
+    assert_eq!(
+        NestedStructSerializeFieldSelector::from_bytes(&[]),
+        Err(SelectorDecodeError::UnexpectedEnd)
+    );
+    // Every field enabled takes 7 bits, so the last padding bit must be zero
+    assert_eq!(
+        NestedStructSerializeFieldSelector::from_bytes(&[0b1111_1111]),
+        Err(SelectorDecodeError::TrailingData)
+    );
+    assert_eq!(
+        NestedStructSerializeFieldSelector::from_bytes(&[0b0000_0001]),
+        Err(SelectorDecodeError::TrailingData)
+    );
+    assert_eq!(
+        NestedStructSerializeFieldSelector::from_bytes(&[0, 0]),
+        Err(SelectorDecodeError::TrailingData)
+    );
+}
+
+static ID_ONLY: SimpleStructSerializeFieldSelector =
+    SimpleStructSerializeFieldSelector::builder().id().build();
+
+static NESTED_PRESET: NestedStructSerializeFieldSelector = NestedStructSerializeFieldSelector {
+    id: Some(()),
+    inner: Some(InnerStructSerializeFieldSelector {
+        number: Some(()),
+        ..InnerStructSerializeFieldSelector::new()
+    }),
+    ..NestedStructSerializeFieldSelector::new()
+};
+
+#[test]
+fn test_static_selector_presets() {
+    let mut simple = SimpleStructSerializeFieldSelector::new();
+    simple.enable_dot_hierarchy("id");
+    assert_eq!(ID_ONLY, simple);
+
+    let data = create_nested_struct();
+    let json = serde_json::to_value(SerializeFields(&data, &NESTED_PRESET)).unwrap();
+    assert_eq!(json, serde_json::json!({ "id": 456, "inner": { "number": 42 } }));
+}
+
+#[test]
+fn test_frozen_selector_sharing() {
+    let frozen = serialize_fields::select!(NestedStruct { id, inner: { value } }).freeze();
+    let data = create_nested_struct();
+
+    let outputs: Vec<String> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let selector = frozen.clone();
+                let data = &data;
+                scope.spawn(move || {
+                    serde_json::to_string(&SerializeFields(data, &*selector)).unwrap()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+    assert!(outputs.iter().all(|json| json == r#"{"id":456,"inner":{"value":"Inner Value"}}"#));
+
+    let shared = frozen.clone();
+    assert!(shared.ptr_eq(&frozen));
+    assert_eq!(shared.to_string(), "id,inner.value");
+
+    drop(shared);
+    let mut thawed = frozen.thaw();
+    thawed.enable_dot_hierarchy("inner.number");
+    assert!(thawed.contains_path("inner.number"));
+}
+
+#[test]
+fn test_selector_from_entropy() {
+    assert_eq!(
+        NestedStructSerializeFieldSelector::from_entropy(&[]),
+        NestedStructSerializeFieldSelector::new()
+    );
+    assert_eq!(
+        NestedStructSerializeFieldSelector::from_entropy(&[0xff, 0xff, 0xff]),
+        NestedStructSerializeFieldSelector::all()
+    );
+
+    // Every combination of the seven presence bits round trips through to_bytes
+    for byte in 0..=u8::MAX {
+        let selector = NestedStructSerializeFieldSelector::from_entropy(&[byte]);
+        let decoded = NestedStructSerializeFieldSelector::from_bytes(&selector.to_bytes()).unwrap();
+        assert_eq!(decoded, selector);
+    }
+}
+
+#[test]
+fn test_selectors_as_btreemap_keys() {
+    use std::collections::BTreeMap;
+
+    let id: NestedStructSerializeFieldSelector = "id".parse().unwrap();
+    let inner: NestedStructSerializeFieldSelector = "inner.value".parse().unwrap();
+    let both: NestedStructSerializeFieldSelector = "id,inner.value".parse().unwrap();
+
+    // Fields compare in declaration order, disabled before enabled
+    assert!(NestedStructSerializeFieldSelector::new() < inner);
+    assert!(inner < id);
+    assert!(id < both);
+
+    let mut projections = BTreeMap::new();
+    projections.insert(both.clone(), "both");
+    projections.insert(id.clone(), "id");
+    projections.insert(inner.clone(), "inner");
+    assert_eq!(projections.values().copied().collect::<Vec<_>>(), ["inner", "id", "both"]);
+    assert_eq!(projections[&"inner.value,id".parse().unwrap()], "both");
+}
+
+#[test]
+fn test_openapi_fields_parameter() {
+    let parameter =
+        serialize_fields::openapi::fields_parameter::<NestedStructSerializeFieldSelector>("fields");
+    assert_eq!(parameter["name"], "fields");
+    assert_eq!(parameter["required"], false);
+    assert_eq!(parameter["explode"], false);
+    assert!(parameter["description"].as_str().unwrap().ends_with(
+        "Fields: `id`, `inner.value`, `inner.number`, `optional_inner.value`, `optional_inner.number`."
+    ));
+    assert!(parameter["schema"]["items"].get("enum").is_none());
+    assert!(parameter["schema"]["items"]["pattern"].is_string());
+}
+
+#[test]
+fn test_fields_from_query_string() {
+    use serialize_fields::SelectorLimits;
+    use serialize_fields::http::Fields;
+
+    type Selector = NestedStructSerializeFieldSelector;
+
+    let fields = Fields::<Selector>::from_query("page=2&fields=id,inner.value").unwrap();
+    assert_eq!(fields.to_string(), "id,inner.value");
+
+    // Escapes are decoded and repeated parameters combined
+    let fields = Fields::<Selector>::from_query("fields=id%2C+inner.number&fields=optional_inner.*").unwrap();
+    assert_eq!(
+        fields.into_inner().to_string(),
+        "id,inner.number,optional_inner.value,optional_inner.number"
+    );
+
+    // No parameter means the default selection
+    assert_eq!(Fields::<Selector>::from_query("page=2").unwrap().0, Selector::with_defaults());
+
+    let rejection = Fields::<Selector>::from_query("fields=id,inner.vlaue").unwrap_err();
+    assert_eq!(rejection.status(), 400);
+    assert_eq!(rejection.source(), "fields");
+    assert_eq!(
+        rejection.to_string(),
+        "invalid `fields`: invalid field selection: unknown field `vlaue` in `inner.vlaue` at position 3, did you mean `inner.value`?"
+    );
+
+    let limits = SelectorLimits { max_depth: 1, max_fields: 8 };
+    let rejection = Fields::<Selector>::from_query_param("select=inner.value", "select", &limits).unwrap_err();
+    assert_eq!(rejection.to_string(), "invalid `select`: field selection is 2 levels deep, at most 1 are allowed");
+}
+
+#[test]
+fn test_selected_response_body() {
+    use serialize_fields::http::{JSON_CONTENT_TYPE, Selected};
+
+    let selector: NestedStructSerializeFieldSelector = "id".parse().unwrap();
+    let body = Selected(create_nested_struct(), selector.clone()).to_json().unwrap();
+    assert_eq!(body, br#"{"id":456}"#);
+
+    let body = Selected(vec![create_nested_struct()], selector.clone()).to_json().unwrap();
+    assert_eq!(body, br#"[{"id":456}]"#);
+
+    let body = Selected(None::<NestedStruct>, selector).to_json().unwrap();
+    assert_eq!(body, b"null");
+    assert_eq!(JSON_CONTENT_TYPE, "application/json");
+}
+
+#[test]
+fn test_fields_config_header_and_query() {
+    use serialize_fields::SelectorLimits;
+    use serialize_fields::http::FieldsConfig;
+
+    type Selector = NestedStructSerializeFieldSelector;
+
+    let headers = [("x-fields", "inner.number")];
+    let lookup = |name: &str| {
+        headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    };
+
+    let config = FieldsConfig::new().header("X-Fields");
+    let fields = config.extract::<Selector>(Some("fields=id"), lookup).unwrap();
+    assert_eq!(fields.to_string(), "inner.number");
+
+    // Without the header, the query string is used
+    let fields = config.extract::<Selector>(Some("fields=id"), |_| None).unwrap();
+    assert_eq!(fields.to_string(), "id");
+
+    let config = FieldsConfig::new().without_query().query_param("select");
+    let fields = config.extract::<Selector>(Some("fields=id&select=inner.value"), |_| None).unwrap();
+    assert_eq!(fields.to_string(), "inner.value");
+
+    let config = FieldsConfig::new()
+        .without_query()
+        .header("X-Fields")
+        .limits(SelectorLimits { max_depth: 1, max_fields: 8 });
+    assert_eq!(config.extract::<Selector>(Some("fields=id"), |_| None).unwrap().0, Selector::with_defaults());
+    let rejection = config.extract::<Selector>(None, lookup).unwrap_err();
+    assert_eq!(rejection.source(), "X-Fields");
+}
+
+#[test]
+fn test_parse_odata() {
+    use serialize_fields::utils::parse_odata;
+    use serialize_fields::{InvalidPathReason, SelectorParseError};
+
+    type Selector = NestedStructSerializeFieldSelector;
+
+    let selector: Selector = parse_odata("$select=id,inner/number").unwrap();
+    assert_eq!(selector.to_string(), "id,inner.number");
+
+    let selector: Selector =
+        parse_odata("$filter=contains(id,'a;b')&$select=id&$expand=inner($select=value;$top=1),optional_inner").unwrap();
+    assert_eq!(
+        selector.to_string(),
+        "id,inner.value,optional_inner.value,optional_inner.number"
+    );
+
+    // No $select means every plain field of the level
+    let selector: Selector = parse_odata("").unwrap();
+    assert_eq!(selector.to_string(), "id");
+
+    let error: SelectorParseError = parse_odata::<Selector>("$select=id,inner/nmuber").unwrap_err();
+    assert_eq!(error.errors[0].path, "inner.nmuber");
+    assert_eq!(error.errors[0].position, 11);
+    assert!(matches!(error.errors[0].reason, InvalidPathReason::Unknown { at_segment: 1, .. }));
+
+    let error = parse_odata::<Selector>("$expand=inner($select=value").unwrap_err();
+    assert_eq!(error.to_string(), "invalid field selection: expected `)` at position 27");
+
+    let error = parse_odata::<Selector>("$select=id,").unwrap_err();
+    assert_eq!(error.errors[0].reason, InvalidPathReason::Syntax { expected: "a property name" });
+}
+
+#[test]
+fn test_parse_partial_response() {
+    use serialize_fields::InvalidPathReason;
+    use serialize_fields::utils::parse_partial_response;
+
+    type Selector = NestedStructSerializeFieldSelector;
+
+    let selector: Selector = parse_partial_response("id,inner(value),optional_inner/number").unwrap();
+    assert_eq!(selector.to_string(), "id,inner.value,optional_inner.number");
+
+    let selector: Selector = parse_partial_response("inner(*), optional_inner.value").unwrap();
+    assert_eq!(selector.to_string(), "inner.value,inner.number,optional_inner.value");
+
+    // A nested field without subfields is selected whole
+    let selector: Selector = parse_partial_response("id,inner").unwrap();
+    assert_eq!(selector.to_string(), "id,inner.value,inner.number");
+
+    let error = parse_partial_response::<Selector>("id,inner(valeu)").unwrap_err();
+    assert_eq!(error.errors[0].path, "inner.valeu");
+    assert_eq!(error.errors[0].position, 9);
+    assert!(error.to_string().ends_with("did you mean `inner.value`?"));
+
+    for (input, position, expected) in [
+        ("id,", 3, "a field name"),
+        ("inner()", 6, "a field name"),
+        ("id)", 2, "`,` or end of input"),
+        ("inner(value", 11, "`)`"),
+    ] {
+        let error = parse_partial_response::<Selector>(input).unwrap_err();
+        assert_eq!(error.errors[0].position, position, "{input}");
+        assert_eq!(error.errors[0].reason, InvalidPathReason::Syntax { expected }, "{input}");
+    }
+}
+
+#[test]
+fn test_parse_graphql() {
+    use serialize_fields::InvalidPathReason;
+    use serialize_fields::utils::parse_graphql;
+
+    type Selector = NestedStructSerializeFieldSelector;
+
+    let selector: Selector = parse_graphql(
+        "{
+            id
+            # the main record
+            main: inner(version: \"(2)\") { value, __typename }
+            optional_inner { number }
+        }",
+    )
+    .unwrap();
+    assert_eq!(selector.to_string(), "id,inner.value,optional_inner.number");
+
+    let error = parse_graphql::<Selector>("{ id inner { nubmer } }").unwrap_err();
+    assert_eq!(error.errors[0].path, "inner.nubmer");
+    assert_eq!(error.errors[0].position, 13);
+
+    for (input, position, expected) in [
+        ("id", 0, "`{`"),
+        ("{ id", 4, "`}`"),
+        ("{ inner { } }", 10, "a field name"),
+        ("{ ...Fields }", 2, "a field name"),
+        ("{ id } extra", 7, "end of input"),
+    ] {
+        let error = parse_graphql::<Selector>(input).unwrap_err();
+        assert_eq!(error.errors[0].position, position, "{input}");
+        assert_eq!(error.errors[0].reason, InvalidPathReason::Syntax { expected }, "{input}");
+    }
+}
+
+#[test]
+fn test_selector_from_tree() {
+    use serialize_fields::utils::selector_from_tree;
+
+    struct Node {
+        name: &'static str,
+        children: Vec<Node>,
+    }
+
+    fn leaf(name: &'static str) -> Node {
+        Node { name, children: Vec::new() }
+    }
+
+    let look_ahead = vec![
+        leaf("id"),
+        leaf("__typename"),
+        Node { name: "inner", children: vec![leaf("number"), leaf("computed")] },
+    ];
+    let selector: NestedStructSerializeFieldSelector =
+        selector_from_tree(&look_ahead, |node| node.name, |node| &node.children);
+    assert_eq!(selector.to_string(), "id,inner.number");
+
+    // A nested field with only unknown subfields is still selected
+    let look_ahead = vec![leaf("id"), Node { name: "inner", children: vec![leaf("__typename")] }];
+    let selector: NestedStructSerializeFieldSelector =
+        selector_from_tree(&look_ahead, |node| node.name, |node| &node.children);
+    assert_eq!(selector.to_string(), "id,inner");
+}
+
+#[test]
+fn test_selector_from_field_mask() {
+    use serialize_fields::utils::selector_from_field_mask;
+
+    type Selector = NestedStructSerializeFieldSelector;
+
+    let allowed: Selector = "id,inner.*".parse().unwrap();
+
+    let selector = selector_from_field_mask(&["id", "inner.value", "optional_inner.number"], &allowed).unwrap();
+    assert_eq!(selector.to_string(), "id,inner.value");
+
+    // A nested field selects its whole subtree, clamped to `allowed`
+    let selector = selector_from_field_mask(&["inner"], &Selector::all()).unwrap();
+    assert_eq!(selector.to_string(), "inner.value,inner.number");
+    let clamp: Selector = "id,inner.value".parse().unwrap();
+    let selector = selector_from_field_mask(&["id", "inner"], &clamp).unwrap();
+    assert_eq!(selector.to_string(), "id,inner.value");
+
+    let empty: [&str; 0] = [];
+    assert_eq!(selector_from_field_mask(&empty, &allowed).unwrap(), allowed);
+
+    let error = selector_from_field_mask(&["id".to_string(), "inner.nubmer".to_string()], &allowed).unwrap_err();
+    assert_eq!(error.errors.len(), 1);
+    assert_eq!(error.errors[0].path, "inner.nubmer");
+    assert_eq!(error.errors[0].position, 1);
+}
+
+#[test]
+fn test_active_nested() {
+    type Selector = NestedStructSerializeFieldSelector;
+
+    let selector: Selector = "id,optional_inner.value".parse().unwrap();
+    assert_eq!(selector.active_nested(), ["optional_inner"]);
+    assert_eq!(Selector::all().active_nested(), ["inner", "optional_inner"]);
+    assert!(Selector::new().active_nested().is_empty());
+
+    // Through the dynamic interface, for relations further down
+    let nested = Selector::all().at_path("inner").unwrap().active_nested();
+    assert!(nested.is_empty());
+
+    // A nested field enabled without any of its fields serializes as `{}`
+    let mut selector = Selector::new();
+    selector.enable_inner(|_| {});
+    assert!(selector.active_nested().is_empty());
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn test_csv_write_records() {
+    let mut other = create_nested_struct();
+    other.id = 457;
+    other.inner.value = "Line\nbreak".to_string();
+    other.optional_inner = None;
+    let items = vec![create_nested_struct(), other];
+
+    let selector: NestedStructSerializeFieldSelector = "id,inner.value,optional_inner.number".parse().unwrap();
+    let mut out = Vec::new();
+    serialize_fields::csv::write_records(&mut out, &items, &selector).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "id,inner.value,optional_inner.number\n456,Inner Value,99\n457,\"Line\nbreak\",\n"
+    );
+
+    // No fields still writes one (empty) record per item
+    let mut out = Vec::new();
+    serialize_fields::csv::write_records(&mut out, &items, &NestedStructSerializeFieldSelector::new()).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "\n\n\n");
+}
+
+#[test]
+fn test_serialize_fields_flat() {
+    use serialize_fields::SerializeFieldsFlat;
+
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id,inner.value,optional_inner.*".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeFieldsFlat(&data, &selector)).unwrap(),
+        serde_json::json!({
+            "id": 456,
+            "inner.value": "Inner Value",
+            "optional_inner.value": "Optional Inner",
+            "optional_inner.number": 99,
+        })
+    );
+
+    // A missing optional struct is a single null entry
+    let mut data = create_nested_struct();
+    data.optional_inner = None;
+    assert_eq!(
+        serde_json::to_value(SerializeFieldsFlat(&data, &selector)).unwrap(),
+        serde_json::json!({"id": 456, "inner.value": "Inner Value", "optional_inner": null})
+    );
+}
+
+#[test]
+fn test_serialize_with_nulls() {
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id,optional_inner.value".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&data, &selector).with_nulls()).unwrap(),
+        serde_json::json!({
+            "id": 456,
+            "inner": null,
+            "optional_inner": {"value": "Optional Inner", "number": null},
+        })
+    );
+
+    // Every record has the same keys, whatever is enabled
+    let empty = NestedStructSerializeFieldSelector::new();
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&data, &empty).with_nulls()).unwrap(),
+        serde_json::json!({"id": null, "inner": null, "optional_inner": null})
+    );
+
+    let items = vec![data];
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&items, &empty).with_nulls()).unwrap(),
+        serde_json::json!([{"id": null, "inner": null, "optional_inner": null}])
+    );
+}
+
+#[test]
+fn test_redacted_collections() {
+    #[derive(SerializeFields, Serialize)]
+    struct Tagged {
+        id: u32,
+        tags: Vec<String>,
+        scores: std::collections::BTreeMap<String, f64>,
+        note: Option<String>,
+    }
+
+    let data = Tagged {
+        id: 1,
+        tags: vec!["a".to_string(), "b".to_string()],
+        scores: [("math".to_string(), 0.5)].into_iter().collect(),
+        note: None,
+    };
+    let selector: TaggedSerializeFieldSelector = "id".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&data, &selector).redacted("***")).unwrap(),
+        serde_json::json!({"id": 1, "tags": ["***", "***"], "scores": {"math": "***"}, "note": null})
+    );
+}
+
+#[test]
+fn test_serialize_fields_envelope() {
+    use serialize_fields::SerializeFieldsEnvelope;
+
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id,inner.number".parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(SerializeFieldsEnvelope(&data, &selector)).unwrap(),
+        serde_json::json!({
+            "data": {"id": 456, "inner": {"number": 42}},
+            "_fields": ["id", "inner.number"],
+        })
+    );
+
+    let selector = NestedStructSerializeFieldSelector::new();
+    assert_eq!(
+        serde_json::to_value(SerializeFieldsEnvelope(&data, &selector)).unwrap(),
+        serde_json::json!({"data": {}, "_fields": []})
+    );
+}
+
+#[test]
+fn test_serialize_with_renames() {
+    use serialize_fields::RenameMap;
+
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id,optional_inner.value".parse().unwrap();
+    let renames = RenameMap::camel_case().rename("value", "label");
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&data, &selector).with_renames(&renames)).unwrap(),
+        serde_json::json!({"id": 456, "optionalInner": {"label": "Optional Inner"}})
+    );
+
+    // Combined with other options, and through collections
+    let items = vec![data];
+    let renames = RenameMap::new().rename("inner", "details");
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&items, &selector).with_renames(&renames).with_nulls()).unwrap(),
+        serde_json::json!([{
+            "id": 456,
+            "details": null,
+            "optional_inner": {"value": "Optional Inner", "number": null},
+        }])
+    );
+
+    assert_eq!(renames.get("id"), "id");
+    assert_eq!(RenameMap::camel_case().get("last_login_at"), "lastLoginAt");
+    assert_eq!(RenameMap::camel_case().get("_private"), "_private");
+}
+
+#[test]
+fn test_serialize_sorted() {
+    use serialize_fields::RenameMap;
+
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id,inner.*,optional_inner.value".parse().unwrap();
+    assert_eq!(
+        serde_json::to_string(&SerializeFields(&data, &selector).sorted()).unwrap(),
+        r#"{"id":456,"inner":{"number":42,"value":"Inner Value"},"optional_inner":{"value":"Optional Inner"}}"#
+    );
+
+    // Sorted by the emitted keys when they are renamed
+    let renames = RenameMap::new().rename("id", "zid").rename("value", "a_value");
+    assert_eq!(
+        serde_json::to_string(&SerializeFields(&data, &selector).with_renames(&renames).sorted()).unwrap(),
+        r#"{"inner":{"a_value":"Inner Value","number":42},"optional_inner":{"a_value":"Optional Inner"},"zid":456}"#
+    );
+}
+
+#[test]
+fn test_to_filtered_value_and_string() {
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id,inner.value".parse().unwrap();
+    assert_eq!(
+        data.to_filtered_value(&selector).unwrap(),
+        serde_json::json!({"id": 456, "inner": {"value": "Inner Value"}})
+    );
+    assert_eq!(
+        data.to_filtered_string(&selector).unwrap(),
+        r#"{"id":456,"inner":{"value":"Inner Value"}}"#
+    );
+}
+
+#[test]
+fn test_serialize_fields_iter() {
+    use serialize_fields::{SerializeFieldsIter, SerializeOptions};
+
+    let items = vec![create_nested_struct(), create_nested_struct()];
+    let selector: NestedStructSerializeFieldSelector = "id,inner.number".parse().unwrap();
+    let iter = SerializeFieldsIter::new(items.iter().take(1), &selector);
+    assert_eq!(
+        serde_json::to_value(&iter).unwrap(),
+        serde_json::json!([{"id": 456, "inner": {"number": 42}}])
+    );
+    // The iterator has been consumed
+    assert!(serde_json::to_value(&iter).is_err());
+
+    let iter = SerializeFieldsIter::new(&items, &selector).with_options(SerializeOptions::new().with_nulls());
+    assert_eq!(
+        serde_json::to_value(&iter).unwrap(),
+        serde_json::json!([
+            {"id": 456, "inner": {"value": null, "number": 42}, "optional_inner": null},
+            {"id": 456, "inner": {"value": null, "number": 42}, "optional_inner": null},
+        ])
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_to_vec() {
+    let items: Vec<NestedStruct> = (0..100)
+        .map(|id| {
+            let mut item = create_nested_struct();
+            item.id = id;
+            item
+        })
+        .collect();
+    let selector: NestedStructSerializeFieldSelector = "id,optional_inner.value".parse().unwrap();
+    assert_eq!(
+        serialize_fields::parallel::to_vec(&items, &selector).unwrap(),
+        serde_json::to_vec(&SerializeFields(&items, &selector)).unwrap()
+    );
+    assert_eq!(serialize_fields::parallel::to_vec(&items[..0], &selector).unwrap(), b"[]");
+}
+
+#[test]
+fn test_stream_chunk_encoder() {
+    use serialize_fields::stream::{ChunkEncoder, StreamFormat};
+
+    let items = [create_nested_struct(), create_nested_struct()];
+    let selector: NestedStructSerializeFieldSelector = "id".parse().unwrap();
+
+    let mut encoder = ChunkEncoder::new(&selector, StreamFormat::Ndjson);
+    let chunks: Vec<Vec<u8>> = items.iter().map(|item| encoder.encode(item).unwrap()).collect();
+    assert_eq!(chunks, [b"{\"id\":456}\n".to_vec(), b"{\"id\":456}\n".to_vec()]);
+    assert!(encoder.finish().is_empty());
+
+    let mut encoder = ChunkEncoder::new(&selector, StreamFormat::JsonArray);
+    assert_eq!(encoder.encode(&items[0]).unwrap(), br#"[{"id":456}"#);
+    assert_eq!(encoder.encode(&items[1]).unwrap(), br#",{"id":456}"#);
+    assert_eq!(encoder.finish(), b"]");
+    assert_eq!(ChunkEncoder::new(&selector, StreamFormat::JsonArray).finish(), b"[]");
+}
+
+#[test]
+fn test_erased_serialize_fields() {
+    use serialize_fields::ErasedSerializeFields;
+
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id".parse().unwrap();
+    let inner_selector: InnerStructSerializeFieldSelector = "number".parse().unwrap();
+    let parts: Vec<Box<dyn ErasedSerializeFields + Send + Sync>> = vec![
+        Box::new(SerializeFields(&data, &selector)),
+        Box::new(SerializeFields(&data.inner, &inner_selector).with_nulls()),
+    ];
+    assert_eq!(
+        serde_json::to_value(&parts).unwrap(),
+        serde_json::json!([{"id": 456}, {"value": null, "number": 42}])
+    );
+    assert_eq!(parts[0].to_json_value().unwrap(), serde_json::json!({"id": 456}));
+}
+
+#[test]
+fn test_erased_serialize_fields_forwards_to_serializer() {
+    use std::collections::BTreeMap;
+
+    use serialize_fields::ErasedSerializeFields;
+
+    #[derive(SerializeFields, Serialize)]
+    struct Ledger {
+        zeta: u32,
+        total: u128,
+        alpha: Vec<u8>,
+        by_year: BTreeMap<u16, u32>,
+    }
+
+    let ledger = Ledger {
+        zeta: 1,
+        total: u128::MAX,
+        alpha: vec![1, 2],
+        by_year: BTreeMap::from([(2024, 3)]),
+    };
+    let selector = LedgerSerializeFieldSelector::all();
+    let erased: Box<dyn ErasedSerializeFields> = Box::new(SerializeFields(&ledger, &selector));
+
+    // Declaration order and values JSON trees cannot hold are kept
+    assert_eq!(
+        serde_json::to_string(&erased).unwrap(),
+        format!(r#"{{"zeta":1,"total":{},"alpha":[1,2],"by_year":{{"2024":3}}}}"#, u128::MAX)
+    );
+}
+
+#[test]
+fn test_serialize_through_pointers() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let data = create_nested_struct();
+    let selector: NestedStructSerializeFieldSelector = "id,inner.number".parse().unwrap();
+    let expected = serde_json::json!({"id": 456, "inner": {"number": 42}});
+
+    assert_eq!(serde_json::to_value(SerializeFields(&&data, &selector)).unwrap(), expected);
+    assert_eq!(serde_json::to_value(SerializeFields(&Box::new(create_nested_struct()), &selector)).unwrap(), expected);
+    assert_eq!(serde_json::to_value(SerializeFields(&Rc::new(create_nested_struct()), &selector)).unwrap(), expected);
+
+    let shared = vec![Arc::new(data)];
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&shared, &selector).with_nulls()).unwrap(),
+        serde_json::json!([{"id": 456, "inner": {"value": null, "number": 42}, "optional_inner": null}])
+    );
+    assert_eq!(shared[0].to_filtered_value(&selector).unwrap(), expected);
+}
+
+#[test]
+fn test_deserialize_fields() {
+    use serialize_fields::DeserializeFields;
+
+    let selector: NestedStructSerializeFieldSelector = "inner.value,optional_inner.*".parse().unwrap();
+    let body = serde_json::json!({
+        "id": 7,
+        "inner": {"value": "Patched", "number": 1},
+        "optional_inner": {"value": "New", "number": 2},
+    });
+    let patched: NestedStruct = DeserializeFields::new(&selector).from_value(body.clone()).unwrap();
+    assert_eq!(
+        patched,
+        NestedStruct {
+            id: 0,
+            inner: InnerStruct { value: "Patched".to_string(), number: 0 },
+            optional_inner: Some(InnerStruct { value: "New".to_string(), number: 2 }),
+        }
+    );
+
+    let error = DeserializeFields::<NestedStruct, _>::new(&selector)
+        .deny_disallowed()
+        .from_value(serde_json::json!({"inner": {"number": 1}}))
+        .unwrap_err();
+    assert_eq!(error.to_string(), "field `inner.number` is not allowed");
+    assert!(DeserializeFields::<NestedStruct, _>::new(&selector).deny_disallowed().from_str(r#"{"optional_inner": null}"#).is_ok());
+}
+
+#[test]
+fn test_patch_apply() {
+    use serialize_fields::patch;
+
+    let selector: NestedStructSerializeFieldSelector = "inner.*,optional_inner.value".parse().unwrap();
+    let mut data = create_nested_struct();
+    patch::apply(
+        &mut data,
+        &serde_json::json!({"inner": {"number": 7}, "optional_inner": {"value": "Patched"}}),
+        &selector,
+    )
+    .unwrap();
+    assert_eq!(data.inner.number, 7);
+    assert_eq!(data.inner.value, "Inner Value");
+    assert_eq!(data.optional_inner.as_ref().unwrap().value, "Patched");
+
+    // Every failing field is reported, and nothing is written
+    let error = patch::apply(
+        &mut data,
+        &serde_json::json!({"id": 1, "inner": {"value": "Ok", "number": "seven"}, "optional_inner": {"number": 1}}),
+        &selector,
+    )
+    .unwrap_err();
+    let paths: Vec<&str> = error.errors.iter().map(|error| error.path.as_str()).collect();
+    assert_eq!(paths, ["id", "optional_inner.number", "inner.number"]);
+    assert_eq!(error.errors[0].to_string(), "`id`: field is not writable");
+
+    let error = patch::apply(&mut data, &serde_json::json!({"inner": {"number": "seven"}}), &selector).unwrap_err();
+    assert_eq!(error.errors.len(), 1);
+    assert_eq!(error.errors[0].path, "inner.number");
+    assert_eq!(data.inner.value, "Inner Value");
+    assert_eq!(data.inner.number, 7);
+}
+
+#[derive(SerializeFields, Serialize)]
+struct ObservedStruct {
+    id: u32,
+    #[serialize_fields(sensitive)]
+    secret: String,
+    inner: InnerStruct,
+}
+
+#[test]
+fn test_unknown_field_hook() {
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    // Other tests may enable unknown fields while the hook is installed
+    serialize_fields::set_unknown_field_hook(move |type_name, field| {
+        if type_name.ends_with("::ObservedStruct") {
+            sink.lock().unwrap().push((field.path.clone(), field.at_segment, field.suggestion.clone()));
+        }
+    });
+
+    let mut selector = ObservedStructSerializeFieldSelector::new();
+    for path in ["id", "inner.*", "**", "idd", "inner.vlaue", "id.nested", "secret"] {
+        selector.enable_dot_hierarchy(path);
+    }
+    serialize_fields::clear_unknown_field_hook();
+    selector.enable_dot_hierarchy("missing");
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            ("idd".to_string(), 0, Some("id".to_string())),
+            ("inner.vlaue".to_string(), 1, Some("inner.value".to_string())),
+            ("id.nested".to_string(), 1, None),
+            ("secret".to_string(), 0, None),
+        ]
+    );
+
+    // The known paths were still enabled
+    let data = ObservedStruct {
+        id: 1,
+        secret: "hidden".to_string(),
+        inner: InnerStruct { value: "Inner".to_string(), number: 2 },
+    };
+    assert_eq!(
+        serde_json::to_value(SerializeFields(&data, &selector)).unwrap(),
+        serde_json::json!({"id": 1, "inner": {"value": "Inner", "number": 2}})
+    );
+}
+
+#[test]
+fn test_field_usage_recorder() {
+    use serialize_fields::{FieldSelector, FieldUsageCounter, SerializeFieldsIter};
+    use std::sync::Mutex;
+
+    let items = vec![
+        NestedStruct {
+            id: 1,
+            inner: InnerStruct { value: "a".to_string(), number: 1 },
+            optional_inner: None,
+        },
+        NestedStruct {
+            id: 2,
+            inner: InnerStruct { value: "b".to_string(), number: 2 },
+            optional_inner: None,
+        },
+    ];
+    let selector: NestedStructSerializeFieldSelector = "id,inner.value".parse().unwrap();
+
+    let recorded = Mutex::new(Vec::new());
+    let recorder = |type_name: &'static str, paths: &[String]| {
+        recorded.lock().unwrap().push((type_name, paths.to_vec()));
+    };
+    serde_json::to_string(&SerializeFields(&items, &selector).record_usage(&recorder)).unwrap();
+    // Once for the whole list, and not again for the nested structs
+    assert_eq!(
+        *recorded.lock().unwrap(),
+        [(
+            NestedStructSerializeFieldSelector::TYPE_NAME,
+            vec!["id".to_string(), "inner.value".to_string()]
+        )]
+    );
+
+    let counter = FieldUsageCounter::new();
+    serde_json::to_string(&SerializeFields(&items[0], &selector).record_usage(&counter)).unwrap();
+    let iter = SerializeFieldsIter::new(items.iter(), &selector)
+        .with_options(serialize_fields::SerializeOptions::new().record_usage(&counter));
+    serde_json::to_string(&iter).unwrap();
+    let type_name = NestedStructSerializeFieldSelector::TYPE_NAME;
+    assert_eq!(
+        counter.counts(),
+        [((type_name, "id".to_string()), 2), ((type_name, "inner.value".to_string()), 2)].into()
+    );
+
+    counter.reset();
+    assert!(counter.counts().is_empty());
+}
+
+#[test]
+fn test_sensitive_access_hook() {
+    use serialize_fields::FieldSelector;
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    // Other tests may serialize sensitive fields while the hook is installed
+    serialize_fields::set_sensitive_access_hook(move |access| {
+        if access.type_name == ObservedStructSerializeFieldSelector::TYPE_NAME {
+            sink.lock().unwrap().push((access.path, access.context.map(str::to_string)));
+        }
+    });
+
+    let data = ObservedStruct {
+        id: 1,
+        secret: "hidden".to_string(),
+        inner: InnerStruct { value: "Inner".to_string(), number: 2 },
+    };
+    let mut selector = ObservedStructSerializeFieldSelector::all();
+    serde_json::to_string(&SerializeFields(&data, &selector)).unwrap();
+    selector.enable_sensitive("secret");
+    serde_json::to_string(&SerializeFields(&data, &selector).audit_context("export by 7")).unwrap();
+    serde_json::to_string(&SerializeFields(&data, &selector)).unwrap();
+    // Redacted values are not reported
+    serde_json::to_string(&SerializeFields(&data, &selector).redacted("***")).unwrap();
+    serialize_fields::clear_sensitive_access_hook();
+    serde_json::to_string(&SerializeFields(&data, &selector)).unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [("secret", Some("export by 7".to_string())), ("secret", None)]
+    );
+}
+
+#[test]
+fn test_enable_with_policy() {
+    use serialize_fields::{FieldAccessPolicy, FieldSelector};
+
+    struct DenyPrefix(&'static str);
+
+    impl FieldAccessPolicy for DenyPrefix {
+        fn allows(&self, type_name: &str, path: &str) -> bool {
+            assert_eq!(type_name, NestedStructSerializeFieldSelector::TYPE_NAME);
+            !path.starts_with(self.0)
+        }
+    }
+
+    let policy = DenyPrefix("inner.");
+    let mut selector = NestedStructSerializeFieldSelector::new();
+    assert!(selector.enable_with_policy("id", &policy).is_empty());
+    assert_eq!(
+        selector.enable_with_policy("**", &policy),
+        ["inner.value", "inner.number"]
+    );
+    assert_eq!(
+        selector.enabled_paths(),
+        ["id", "optional_inner.value", "optional_inner.number"]
+    );
+
+    // Unknown paths enable nothing and veto nothing
+    let mut selector = NestedStructSerializeFieldSelector::new();
+    assert!(selector.enable_with_policy("missing", &policy).is_empty());
+    assert!(selector.is_empty());
+
+    // Through a trait object
+    let policy: &dyn FieldAccessPolicy = &|_: &str, path: &str| path != "id";
+    assert_eq!(selector.enable_with_policy("id", policy), ["id"]);
+    assert!(selector.is_empty());
+}
+
+#[test]
+fn test_enable_with_policy_on_field_named_with_policy() {
+    use serialize_fields::FieldAccessPolicy;
+
+    #[derive(SerializeFields, Serialize)]
+    struct Rule {
+        with_policy: bool,
+    }
+
+    // No typed `enable_with_policy` clashes with the policy-checked one
+    let policy: &dyn FieldAccessPolicy = &|_: &str, _: &str| true;
+    let mut selector = RuleSerializeFieldSelector::new();
+    assert!(selector.enable_with_policy("with_policy", policy).is_empty());
+    assert_eq!(
+        serde_json::to_string(&SerializeFields(&Rule { with_policy: true }, &selector)).unwrap(),
+        r#"{"with_policy":true}"#
+    );
+}
@@ -0,0 +1,308 @@
+//! Parsing of `#[serialize_fields(...)]` attributes.
+
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Ident, LitStr, Path, Result, Token, Visibility, parenthesized};
+
+/// Options parsed from `#[serialize_fields(...)]` on the struct itself.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// Name of the generated selector struct.
+    pub selector: Option<Ident>,
+    /// Visibility of the generated items.
+    pub vis: Option<GeneratedVis>,
+    /// Path to the `serialize_fields` crate.
+    pub krate: Option<Path>,
+    /// Extra derives for the selector struct.
+    pub selector_derives: Vec<Path>,
+    /// Named field lists returned by the selector's `preset()`.
+    pub presets: Vec<Preset>,
+    /// Table prefixing the SQL columns of fields without `column`.
+    pub table: Option<String>,
+    /// Generate `UpdateFields`, copying selected fields between values.
+    pub update: bool,
+    /// Generate `Selector::diff`, enabling the fields that differ.
+    pub diff: bool,
+    /// Generate a `Partial{Struct}` of the selected fields.
+    pub partial: bool,
+    /// Implement `ClearUnselected`, resetting fields that are not enabled.
+    pub clear: bool,
+    /// Implement `SelectedEq`, comparing the serialized fields.
+    pub eq: bool,
+    /// Implement `SelectedHash`, hashing the serialized fields.
+    pub hash: bool,
+    /// Implement `CloneSelected`, cloning only the enabled fields.
+    pub clone: bool,
+    /// Implement `PresentFields`, enabling the fields holding data.
+    pub present: bool,
+    /// Type from another crate whose fields the struct mirrors.
+    pub remote: Option<Path>,
+}
+
+/// A preset declared with `#[serialize_fields(preset(name = "...", fields = "..."))]`.
+pub struct Preset {
+    pub name: LitStr,
+    pub fields: LitStr,
+}
+
+/// Visibility requested through `#[serialize_fields(vis = "...")]`.
+pub enum GeneratedVis {
+    /// Reuse the visibility of the annotated struct.
+    Inherit,
+    Explicit(Visibility),
+}
+
+impl ContainerAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut container_attrs = ContainerAttrs::default();
+        // Which of `table` and `diesel_table` set the table, if any
+        let mut table_attr: Option<&str> = None;
+
+        for attr in attrs {
+            if !attr.path().is_ident("serialize_fields") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("selector") {
+                    if container_attrs.selector.is_some() {
+                        return Err(meta.error("duplicate `selector` attribute"));
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    container_attrs.selector = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("crate") {
+                    if container_attrs.krate.is_some() {
+                        return Err(meta.error("duplicate `crate` attribute"));
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    container_attrs.krate = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("selector_derive") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let derives = Punctuated::<Path, Token![,]>::parse_terminated(&content)?;
+                    container_attrs.selector_derives.extend(derives);
+                    Ok(())
+                } else if meta.path.is_ident("table") {
+                    match table_attr {
+                        Some("table") => return Err(meta.error("duplicate `table` attribute")),
+                        Some(_) => return Err(meta.error("`table` conflicts with `diesel_table`")),
+                        None => table_attr = Some("table"),
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    container_attrs.table = Some(value.value());
+                    Ok(())
+                } else if meta.path.is_ident("diesel_table") {
+                    match table_attr {
+                        Some("diesel_table") => return Err(meta.error("duplicate `diesel_table` attribute")),
+                        Some(_) => return Err(meta.error("`diesel_table` conflicts with `table`")),
+                        None => table_attr = Some("diesel_table"),
+                    }
+                    // The module generated by `diesel::table!` is named after the table
+                    let value: Path = meta.value()?.parse()?;
+                    let table = value.segments.last().expect("paths have a segment");
+                    container_attrs.table = Some(table.ident.to_string());
+                    Ok(())
+                } else if meta.path.is_ident("remote") {
+                    if container_attrs.remote.is_some() {
+                        return Err(meta.error("duplicate `remote` attribute"));
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    container_attrs.remote = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("update") {
+                    container_attrs.update = true;
+                    Ok(())
+                } else if meta.path.is_ident("diff") {
+                    container_attrs.diff = true;
+                    Ok(())
+                } else if meta.path.is_ident("partial") {
+                    container_attrs.partial = true;
+                    Ok(())
+                } else if meta.path.is_ident("clear") {
+                    container_attrs.clear = true;
+                    Ok(())
+                } else if meta.path.is_ident("eq") {
+                    container_attrs.eq = true;
+                    Ok(())
+                } else if meta.path.is_ident("hash") {
+                    container_attrs.hash = true;
+                    Ok(())
+                } else if meta.path.is_ident("clone") {
+                    container_attrs.clone = true;
+                    Ok(())
+                } else if meta.path.is_ident("present") {
+                    container_attrs.present = true;
+                    Ok(())
+                } else if meta.path.is_ident("preset") {
+                    let mut name: Option<LitStr> = None;
+                    let mut fields: Option<LitStr> = None;
+                    meta.parse_nested_meta(|preset_meta| {
+                        if preset_meta.path.is_ident("name") {
+                            name = Some(preset_meta.value()?.parse()?);
+                        } else if preset_meta.path.is_ident("fields") {
+                            fields = Some(preset_meta.value()?.parse()?);
+                        } else {
+                            return Err(preset_meta.error("expected `name` or `fields`"));
+                        }
+                        Ok(())
+                    })?;
+                    let (Some(name), Some(fields)) = (name, fields) else {
+                        return Err(meta.error("`preset` requires both `name` and `fields`"));
+                    };
+                    if container_attrs
+                        .presets
+                        .iter()
+                        .any(|preset| preset.name.value() == name.value())
+                    {
+                        return Err(syn::Error::new_spanned(&name, "duplicate preset name"));
+                    }
+                    container_attrs.presets.push(Preset { name, fields });
+                    Ok(())
+                } else if meta.path.is_ident("vis") {
+                    if container_attrs.vis.is_some() {
+                        return Err(meta.error("duplicate `vis` attribute"));
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    container_attrs.vis = Some(if value.value() == "inherit" {
+                        GeneratedVis::Inherit
+                    } else {
+                        GeneratedVis::Explicit(value.parse()?)
+                    });
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported serialize_fields container attribute"))
+                }
+            })?;
+        }
+
+        Ok(container_attrs)
+    }
+}
+
+/// Options parsed from `#[serialize_fields(...)]` on a struct field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// Key used in the serialized output instead of the field name.
+    pub rename: Option<String>,
+    /// Additional names accepted when enabling the field.
+    pub aliases: Vec<String>,
+    /// Serialize the field regardless of the selector.
+    pub always: bool,
+    /// Exclude the field from the selector entirely.
+    pub skip: bool,
+    /// Only enable the field through the explicit sensitive API.
+    pub sensitive: bool,
+    /// Groups enabled together through `enable_group`.
+    pub groups: Vec<String>,
+    /// Roles allowed to see the field; empty means every role.
+    pub roles: Vec<String>,
+    /// Enabled by `with_defaults()`.
+    pub default: bool,
+    /// SQL column returned by `to_sql_columns()`, e.g. `users.user_name`.
+    pub column: Option<LitStr>,
+    /// Function applied to the value before it is serialized.
+    pub transform: Option<Path>,
+    /// Serialize the value as a whole, even if its type looks like a struct
+    /// deriving `SerializeFields`.
+    pub leaf: bool,
+    /// Reported by `validate_required` when not enabled.
+    pub required: bool,
+}
+
+impl FieldAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut field_attrs = FieldAttrs::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident("serialize_fields") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if field_attrs.rename.is_some() {
+                        return Err(meta.error("duplicate `rename` attribute"));
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    field_attrs.rename = Some(value.value());
+                    Ok(())
+                } else if meta.path.is_ident("column") {
+                    if field_attrs.column.is_some() {
+                        return Err(meta.error("duplicate `column` attribute"));
+                    }
+                    field_attrs.column = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("transform") {
+                    if field_attrs.transform.is_some() {
+                        return Err(meta.error("duplicate `transform` attribute"));
+                    }
+                    let value: LitStr = meta.value()?.parse()?;
+                    field_attrs.transform = Some(value.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("always") {
+                    field_attrs.always = true;
+                    Ok(())
+                } else if meta.path.is_ident("leaf") {
+                    field_attrs.leaf = true;
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    field_attrs.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("sensitive") {
+                    field_attrs.sensitive = true;
+                    Ok(())
+                } else if meta.path.is_ident("group") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    field_attrs.groups.push(value.value());
+                    Ok(())
+                } else if meta.path.is_ident("roles") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let roles = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                    field_attrs.roles.extend(roles.iter().map(LitStr::value));
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    field_attrs.default = true;
+                    Ok(())
+                } else if meta.path.is_ident("required") {
+                    field_attrs.required = true;
+                    Ok(())
+                } else if meta.path.is_ident("alias") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    field_attrs.aliases.push(value.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported serialize_fields field attribute"))
+                }
+            })?;
+
+            if field_attrs.skip && field_attrs.always {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`skip` and `always` cannot be combined",
+                ));
+            }
+            if field_attrs.sensitive && field_attrs.always {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`sensitive` and `always` cannot be combined",
+                ));
+            }
+            if field_attrs.sensitive && field_attrs.default {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`sensitive` and `default` cannot be combined",
+                ));
+            }
+            if field_attrs.skip && field_attrs.required {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`skip` and `required` cannot be combined",
+                ));
+            }
+        }
+
+        Ok(field_attrs)
+    }
+}
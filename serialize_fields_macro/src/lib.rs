@@ -4,8 +4,14 @@
 //! field selectors and serialization logic for dynamic field selection.
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type, parse_macro_input};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    Data, DeriveInput, Fields, GenericArgument, PathArguments, Type, TypePath, parse_macro_input,
+};
+
+mod attrs;
+
+use attrs::{ContainerAttrs, FieldAttrs};
 
 /// Derive macro for generating field selectors and dynamic serialization.
 ///
@@ -19,7 +25,7 @@ use syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type, parse
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```rust,ignore
 /// use serialize_fields::SerializeFields;
 /// use serde::{Serialize, Deserialize};
 ///
@@ -33,348 +39,2582 @@ use syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type, parse
 /// ```
 ///
 /// This generates:
-/// - `UserSerializeFieldSelector` struct
-/// - Methods: `new()`, `enable_dot_hierarchy()`, `enable()`
+/// - `UserSerializeFieldSelector` struct, implementing `Debug`, `Clone`, `Eq`,
+///   `Ord` and `Hash`. The order compares fields in declaration order with a
+///   disabled field before an enabled one, so selectors can key a `BTreeMap`.
+/// - Methods: `new()`, `all()`, `all_except()`, `enable_dot_hierarchy()`, `enable()`, `enable_all()`,
+///   `disable_dot_hierarchy()`, `disable()`
+/// - `{Selector}Builder`, returned by `builder()`:
+///   `UserSerializeFieldSelector::builder().id().profile(|p| p.bio()).build()`.
+///   A field named `build` gets no builder method. `new()`, `builder()`,
+///   `build()` and the leaf builder methods are `const fn`, so presets can be
+///   `static`; nested presets use a struct literal with `..Selector::new()`.
+/// - Typed methods per field: `enable_id()`, `enable_name()`, and for nested
+///   structs `enable_profile(|p| { p.enable_bio(); })`. These are checked at
+///   compile time and also enable sensitive fields, so string paths are only
+///   needed for untrusted input. Fields whose method would clash with a
+///   built-in one (such as `group`) get no typed method.
 /// - `SerializeFieldsTrait` impl with `serialize_fields()` and `serialize()` methods
-#[proc_macro_derive(SerializeFields)]
+///
+/// # Container attributes
+///
+/// - `#[serialize_fields(selector = "UserFields")]`: name the generated
+///   selector struct `UserFields` instead of `UserSerializeFieldSelector`.
+/// - `#[serialize_fields(vis = "pub(crate)")]`: visibility of the generated
+///   selector and field enum (default `pub`). `vis = "inherit"` reuses the
+///   struct's own visibility. It cannot be narrower than the struct's, since
+///   the selector appears in its `SerializeFieldsTrait` impl.
+/// - `#[serialize_fields(crate = "common::serialize_fields")]`: path to the
+///   `serialize_fields` crate, for when it is only reachable via a re-export.
+/// - `#[serialize_fields(preset(name = "public", fields = "id,profile.bio"))]`:
+///   a named field list returned by `Selector::preset("public")` and listed in
+///   `Selector::PRESETS` (repeatable). Paths use canonical field names and the
+///   syntax of `create_selector_from_list`; unknown or sensitive paths are a
+///   compile error.
+/// - `#[serialize_fields(table = "users")]`: table prefixing the SQL columns
+///   returned by `to_sql_columns()` for fields without a `column` attribute.
+/// - `#[serialize_fields(diesel_table = schema::users)]`: same as `table`, taking
+///   the module generated by Diesel's `table!` instead of a string. Nothing
+///   Diesel-specific is generated; the columns feed a dynamic select.
+/// - `#[serialize_fields(remote = "other_crate::Money")]`: the struct mirrors
+///   the fields of a type from another crate, like serde's remote derive.
+///   `serialize_fields::RemoteFields` is implemented instead of
+///   `SerializeFieldsTrait`, and values of the remote type are serialized with
+///   `SerializeRemoteFields::<Mirror>(&value, &selector)`.
+/// - `#[serialize_fields(update)]`: implement `serialize_fields::UpdateFields`
+///   and generate `Selector::apply_update(&mut target, &source)`, copying the
+///   enabled fields. Plain fields and containers of nested structs must be
+///   `Clone`, and directly nested structs must use `update` too.
+/// - `#[serialize_fields(diff)]`: generate `Selector::diff(&old, &new)`,
+///   enabling the fields whose values differ, e.g. to serialize only what
+///   changed. Fields must be `PartialEq`, and directly nested structs must use
+///   `diff` too; other containers of nested structs are compared as a whole.
+/// - `#[serialize_fields(partial)]`: generate `Partial{Struct}`, with every
+///   field wrapped in `Option`, built from the enabled fields with
+///   `PartialStruct::from_selected(&data, &selector)` and written back with
+///   `partial.merge_into(&mut data)`. Fields must be `Clone`, and directly
+///   nested structs must use `partial` too; they become their own partial
+///   struct.
+/// - `#[serialize_fields(clear)]`: implement `serialize_fields::ClearUnselected`
+///   and generate `data.clear_unselected(&selector)`, resetting the fields not
+///   enabled, and skipped ones, to their `Default`. Nested structs, also in
+///   `Option`s, `Vec`s and arrays, must use `clear` too.
+/// - `#[serialize_fields(eq)]` and `#[serialize_fields(hash)]`: implement
+///   `serialize_fields::SelectedEq` and `SelectedHash`, generating
+///   `data.eq_selected(&other, &selector)` and
+///   `data.hash_selected(&selector, &mut hasher)` over the fields that would be
+///   serialized. Fields must be `PartialEq` or `Hash`, and nested structs must
+///   use the same attribute.
+/// - `#[serialize_fields(clone)]`: implement `serialize_fields::CloneSelected`
+///   and generate `data.clone_selected(&selector)`, a deep copy of the enabled
+///   fields with the others, and skipped ones, set to their `Default`. Nested
+///   structs, also in `Option`s, `Vec`s and arrays, must use `clone` too.
+/// - `#[serialize_fields(present)]`: implement `serialize_fields::PresentFields`
+///   and generate `Selector::from_present(&data)`, enabling the fields that
+///   hold data, such as `Option`s that are `Some` and non-empty strings, e.g.
+///   for partially loaded rows. Leaf fields must implement
+///   `serialize_fields::Present`, and nested structs, also in `Option`s,
+///   `Vec`s and arrays, must use `present` too. Sensitive fields are never
+///   enabled.
+/// - `#[serialize_fields(selector_derive(MyTrait))]`: extra derives for the
+///   selector struct. Selectors always implement `Serialize` and `Deserialize`
+///   through the crate's serde path, so listing those is accepted but has no
+///   effect. Deserialization accepts any value for an enabled leaf, except
+///   `false`, and never enables sensitive fields.
+///
+/// # Field attributes
+///
+/// - `#[serialize_fields(rename = "key")]`: serialize the field under `key`
+///   instead of its name. Selection still uses the field name.
+/// - `#[serialize_fields(alias = "otherName")]`: also accept `otherName` when
+///   enabling the field. May be repeated.
+/// - `#[serialize_fields(always)]`: always serialize the field, whether or not
+///   it is enabled in the selector.
+/// - `#[serialize_fields(skip)]`: exclude the field from selection and
+///   serialization entirely. No selector entry is generated for it.
+/// - `#[serialize_fields(sensitive)]`: the field is ignored by `enable`,
+///   `enable_dot_hierarchy` and `enable_enum`, and can only be turned on with
+///   `enable_sensitive`. Writing its value calls the hook set with
+///   `serialize_fields::set_sensitive_access_hook`.
+/// - `#[serialize_fields(group = "public")]`: tag the field with a group name
+///   so `enable_group("public")` turns it on. May be repeated.
+/// - `#[serialize_fields(roles("admin", "moderator"))]`: restrict the field to
+///   the listed roles for `enable_for_role` and `restrict_to_role`.
+/// - `#[serialize_fields(default)]`: enable the field in the selector returned
+///   by `with_defaults()`. On a nested field, the nested defaults are used.
+///   Sensitive fields cannot be enabled by default.
+/// - `#[serialize_fields(required)]`: report the field from
+///   `validate_required()` when it is not enabled. Required fields of a nested
+///   struct are checked when the nested field is enabled.
+/// - `#[serialize_fields(transform = "mask_email")]`: serialize
+///   `mask_email(&value)` instead of the value when the field is selected, e.g.
+///   to truncate or round it. The function takes a reference to the field and
+///   returns anything `Serialize`. Only for plain fields.
+/// - `#[serialize_fields(leaf)]`: treat the field as a plain value even if
+///   its type is not recognised as one, such as `chrono::NaiveDate` or another
+///   third-party type that does not derive `SerializeFields`. By default, any
+///   type not known to be a plain value is assumed to be a nested struct.
+/// - `#[serialize_fields(column = "users.user_name")]`: SQL column returned by
+///   `to_sql_columns()` when the field is selected. Defaults to the field name,
+///   prefixed by the container's `table`. Nested structs contribute their own
+///   columns.
+#[proc_macro_derive(SerializeFields, attributes(serialize_fields))]
 pub fn serialize_fields_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let struct_name = &input.ident;
-    let selector_name = format!("{}SerializeFieldSelector", struct_name);
-    let selector_ident = syn::Ident::new(&selector_name, struct_name.span());
+    let container_attrs = ContainerAttrs::from_attrs(&input.attrs)?;
+    let selector_ident = container_attrs.selector.clone().unwrap_or_else(|| {
+        let selector_name = format!("{}SerializeFieldSelector", struct_name);
+        syn::Ident::new(&selector_name, struct_name.span())
+    });
+
+    // Paths used by the generated code, overridable for re-exports
+    let krate = container_attrs
+        .krate
+        .clone()
+        .unwrap_or_else(|| syn::parse_quote!(::serialize_fields));
+    let serde_path = quote! { #krate::__private::serde };
+    let serde_crate = serde_path.to_string().replace(' ', "");
+    #[cfg(feature = "schemars")]
+    let schemars_path = quote! { #krate::__private::schemars };
+    let deserialize_enabled = format!("{}::deserialize_enabled", serde_crate.trim_end_matches("::serde"));
+
+    // Extra derives requested for the selector, on top of the built-in ones
+    let mut extra_derives = Vec::new();
+    for derive in &container_attrs.selector_derives {
+        let name = derive.segments.last().unwrap().ident.to_string();
+        match name.as_str() {
+            // Already derived or implemented below
+            "Debug" | "Clone" | "PartialEq" | "Eq" | "PartialOrd" | "Ord" | "Hash" | "Serialize"
+            | "Deserialize" | "Default" => {}
+            _ => extra_derives.push(quote! { #derive }),
+        }
+    }
+
+    // Visibility of the generated selector and field enum
+    let generated_vis = match &container_attrs.vis {
+        Some(attrs::GeneratedVis::Inherit) => input.vis.clone(),
+        Some(attrs::GeneratedVis::Explicit(vis)) => vis.clone(),
+        None => syn::parse_quote!(pub),
+    };
+
+    // Presets, with every path checked against the selectable catalog at compile time
+    let mut preset_names = Vec::new();
+    let mut preset_fields = Vec::new();
+    let mut preset_checks = Vec::new();
+    for preset in &container_attrs.presets {
+        for entry in preset.fields.value().split(',') {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let path = entry.strip_prefix(['-', '!']).unwrap_or(entry).trim_start();
+            if path.split('.').any(str::is_empty) {
+                return Err(syn::Error::new_spanned(
+                    &preset.fields,
+                    format!("malformed path `{}` in preset", path),
+                ));
+            }
+            let message = format!(
+                "unknown or sensitive field `{}` in preset `{}`",
+                path,
+                preset.name.value()
+            );
+            preset_checks.push(quote_spanned! {preset.fields.span()=>
+                const _: () = assert!(
+                    #krate::__private::is_selectable_path(
+                        <#selector_ident as #krate::FieldSelector>::__SELECTABLE_PATHS,
+                        #path,
+                    ),
+                    #message,
+                );
+            });
+        }
+        preset_names.push(&preset.name);
+        preset_fields.push(&preset.fields);
+    }
 
     // Parse fields
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => &fields.named,
-            _ => panic!("SerializeFields only supports structs with named fields"),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "SerializeFields only supports structs with named fields",
+                ));
+            }
         },
-        _ => panic!("SerializeFields only supports structs"),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "SerializeFields only supports structs",
+            ));
+        }
     };
 
+    if container_attrs.update && container_attrs.remote.is_some() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`update` cannot be combined with `remote`",
+        ));
+    }
+
     // Generate field selector struct fields
     let mut selector_fields = Vec::new();
     let mut enable_match_arms = Vec::new();
+    let mut enable_sensitive_match_arms = Vec::new();
+    let mut enable_group_stmts = Vec::new();
+    let mut enable_for_role_stmts = Vec::new();
+    let mut default_stmts = Vec::new();
+    let mut enable_all_stmts = Vec::new();
+    let mut enable_wildcard_stmts = Vec::new();
+    let mut merge_stmts = Vec::new();
+    let mut intersect_stmts = Vec::new();
+    let mut subtract_stmts = Vec::new();
+    let mut subset_checks = Vec::new();
+    let mut leaf_count_terms = Vec::new();
+    let mut selector_field_idents = Vec::new();
+    let mut collect_paths_stmts = Vec::new();
+    let mut validate_path_arms = Vec::new();
+    let mut canonical_path_arms = Vec::new();
+    let mut sql_column_stmts = Vec::new();
+    let mut source_path_stmts = Vec::new();
+    let mut output_path_stmts = Vec::new();
+    let mut json_shape_stmts = Vec::new();
+    let mut update_stmts = Vec::new();
+    let mut diff_stmts = Vec::new();
+    let mut partial_fields = Vec::new();
+    let mut from_selected_fields = Vec::new();
+    let mut merge_into_stmts = Vec::new();
+    let mut clear_stmts = Vec::new();
+    let mut eq_stmts = Vec::new();
+    let mut hash_stmts = Vec::new();
+    let mut clone_field_inits = Vec::new();
+    let mut present_stmts = Vec::new();
+    let mut required_stmts = Vec::new();
+    let mut active_nested_stmts = Vec::new();
+    let mut contains_arms = Vec::new();
+    let mut at_arms = Vec::new();
+    let mut prune_stmts = Vec::new();
+    let mut canonicalize_stmts = Vec::new();
+    let mut depth_stmts = Vec::new();
+    let mut write_bits_stmts = Vec::new();
+    let mut read_bits_stmts = Vec::new();
+    let mut at_mut_arms = Vec::new();
+    let mut restrict_to_role_stmts = Vec::new();
     let mut new_field_inits = Vec::new();
     let mut serialize_fields = Vec::new();
+    let mut count_enabled_fields = Vec::new();
+    let mut serialized_field_names: Vec<String> = Vec::new();
 
     // Generate field enum
     let field_enum_name = format!("{}Field", struct_name);
     let field_enum_ident = syn::Ident::new(&field_enum_name, struct_name.span());
+    let mut typed_enable_methods = Vec::new();
+    let mut builder_methods = Vec::new();
     let mut enum_variants = Vec::new();
     let mut enable_enum_match_arms = Vec::new();
+    let mut disable_match_arms = Vec::new();
+    let mut disable_enum_match_arms = Vec::new();
     let mut as_dot_path_arms = Vec::new();
+    let mut index_arms = Vec::new();
+    let mut catalog_entries = Vec::new();
+    let mut selectable_catalog_entries = Vec::new();
+    let mut path_offset = quote! { 0 };
     let mut deserialize_match_arms = Vec::new();
     #[cfg(feature = "schemars")]
     let mut schema_simple_fields: Vec<String> = Vec::new();
     #[cfg(feature = "schemars")]
-    let mut schema_nested_prefixes: Vec<(String, String)> = Vec::new(); // (prefix, nested_type)
+    let mut schema_nested_prefixes: Vec<(String, syn::Path)> = Vec::new(); // (prefix, nested field enum)
+    #[cfg(feature = "schemars")]
+    let mut selector_schema_properties = Vec::new();
+    #[cfg(feature = "schemars")]
+    let mut schema_keep_arms = Vec::new();
+    #[cfg(feature = "schemars")]
+    let mut schema_nested_filters = Vec::new();
+    #[cfg(feature = "schemars")]
+    let mut schema_nested_relaxes = Vec::new();
 
     for field in fields {
         let field_ident = field.ident.as_ref().unwrap();
-        
+        let field_attrs = FieldAttrs::from_attrs(&field.attrs)?;
+
+        // Skipped fields get no selector entry and are never serialized
+        if field_attrs.skip {
+            clear_stmts.push(quote! {
+                self.#field_ident = ::core::default::Default::default();
+            });
+            clone_field_inits.push(quote! {
+                #field_ident: ::core::default::Default::default()
+            });
+            continue;
+        }
+
         // Handle raw identifiers (r#keyword)
         let field_name_str = strip_raw_prefix(&field_ident.to_string());
-        
-        // Create a safe field name for the selector struct (can't use keywords)
-        let field_ident = field_ident;
+
+        // Key used in the serialized output
+        let serialized_name = field_attrs
+            .rename
+            .clone()
+            .unwrap_or_else(|| field_name_str.clone());
+
+        // Names accepted when selecting the field
+        let selectable_names: Vec<String> = std::iter::once(field_name_str.clone())
+            .chain(field_attrs.aliases.iter().cloned())
+            .collect();
+
+        // Names matched against client field names, which may be output keys
+        let mut output_names = vec![serialized_name.clone()];
+        for name in &selectable_names {
+            if !output_names.contains(name) {
+                output_names.push(name.clone());
+            }
+        }
+
+        let aliases = &field_attrs.aliases;
+
+        // Doc comments are copied onto the selector field and enum variant
+        let docs: Vec<&syn::Attribute> = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .collect();
+
+        // Sensitive fields can't be turned on by a deserialized selector
+        let skip_deserializing = field_attrs
+            .sensitive
+            .then(|| quote! { #[serde(skip_deserializing)] });
+
+        // Typed `enable_<field>` method, unless it would clash with a built-in one
+        let typed_enable_ident = (!RESERVED_ENABLE_SUFFIXES.contains(&field_name_str.as_str()))
+            .then(|| format_ident!("enable_{}", field_name_str, span = field_ident.span()));
+        let typed_enable_doc = format!("Enable the `{}` field.", field_name_str);
+        let has_builder_method = field_name_str != "build";
 
         // Determine if this is a nested struct type that would have SerializeFields
-        let (is_nested, nested_type) = analyze_field_type(&field.ty);
+        let nested_type = if field_attrs.leaf {
+            None
+        } else {
+            analyze_field_type(&field.ty)
+        };
+        selector_field_idents.push(field_ident);
+        let held_directly = nested_type.is_some_and(|nested_type| {
+            matches!(&field.ty, Type::Path(type_path) if std::ptr::eq(type_path, nested_type))
+        });
+
+        // Partial struct field, holding a partial nested struct when held directly
+        let field_ty = &field.ty;
+        match nested_type.filter(|_| held_directly) {
+            Some(nested_type) => {
+                let partial_nested = partial_path(nested_type);
+                partial_fields.push(quote! {
+                    #(#docs)*
+                    #generated_vis #field_ident: Option<#partial_nested>
+                });
+                from_selected_fields.push(quote! {
+                    #field_ident: selector
+                        .#field_ident
+                        .as_ref()
+                        .map(|nested| #partial_nested::from_selected(&data.#field_ident, nested))
+                });
+                merge_into_stmts.push(quote! {
+                    if let Some(partial) = self.#field_ident {
+                        partial.merge_into(&mut target.#field_ident);
+                    }
+                });
+            }
+            None => {
+                partial_fields.push(quote! {
+                    #(#docs)*
+                    #generated_vis #field_ident: Option<#field_ty>
+                });
+                from_selected_fields.push(quote! {
+                    #field_ident: selector
+                        .#field_ident
+                        .is_some()
+                        .then(|| ::core::clone::Clone::clone(&data.#field_ident))
+                });
+                merge_into_stmts.push(quote! {
+                    if let Some(value) = self.#field_ident {
+                        target.#field_ident = value;
+                    }
+                });
+            }
+        }
 
         // Create variant name (PascalCase from snake_case)
         let variant_name = to_pascal_case(&field_name_str);
         let variant_ident = syn::Ident::new(&variant_name, field_ident.span());
 
-        if is_nested {
-            let nested_selector_type = syn::Ident::new(
-                &format!("{}SerializeFieldSelector", nested_type),
-                field_ident.span(),
-            );
-            let nested_field_enum = syn::Ident::new(
-                &format!("{}Field", nested_type),
-                field_ident.span(),
-            );
+        if let Some(nested_type) = nested_type {
+            let nested_selector_type = quote! {
+                <#nested_type as #krate::SerializeFieldsTrait>::FieldSelector
+            };
+            let nested_field_enum = field_enum_path(nested_type);
+
+            if let Some(column) = &field_attrs.column {
+                return Err(syn::Error::new_spanned(
+                    column,
+                    "`column` is only supported on plain fields; nested structs use their own columns",
+                ));
+            }
+            if let Some(transform) = &field_attrs.transform {
+                return Err(syn::Error::new_spanned(
+                    transform,
+                    "`transform` is only supported on plain fields",
+                ));
+            }
+            let always_columns = field_attrs.always.then(|| {
+                quote! {
+                    else if with_always {
+                        #krate::FieldSelector::__collect_sql_columns(
+                            &<#nested_selector_type as #krate::FieldSelector>::all(),
+                            with_always,
+                            columns,
+                        );
+                    }
+                }
+            });
+            sql_column_stmts.push(quote! {
+                if let Some(nested) = &self.#field_ident {
+                    #krate::FieldSelector::__collect_sql_columns(nested, with_always, columns);
+                } #always_columns
+            });
+            // A nested field that is not serialized is excluded as a whole
+            let unselected_source = if field_attrs.always {
+                quote! { includes.push(format!("{}{}", prefix, #serialized_name)) }
+            } else {
+                quote! { excludes.push(format!("{}{}", prefix, #serialized_name)) }
+            };
+            // An always field without a selector is serialized in full
+            let active = if field_attrs.always {
+                quote! { !self.#field_ident.as_ref().is_some_and(#krate::FieldSelector::is_empty) }
+            } else {
+                quote! { self.#field_ident.as_ref().is_some_and(|nested| !#krate::FieldSelector::is_empty(nested)) }
+            };
+            active_nested_stmts.push(quote! {
+                if #active {
+                    names.push(#field_name_str);
+                }
+            });
+            // Nested structs held directly are updated field by field, and
+            // containers of them as a whole
+            if held_directly {
+                update_stmts.push(quote! {
+                    if let Some(nested) = &selector.#field_ident {
+                        #krate::UpdateFields::update_fields(&mut self.#field_ident, &source.#field_ident, nested);
+                    }
+                });
+            } else {
+                update_stmts.push(quote! {
+                    if selector.#field_ident.is_some() {
+                        self.#field_ident = ::core::clone::Clone::clone(&source.#field_ident);
+                    }
+                });
+            }
+            // Likewise compared field by field, or as a whole; sensitive
+            // fields are never reported as changed
+            if !field_attrs.sensitive {
+                diff_stmts.push(if held_directly {
+                    quote! {
+                        let nested = <#nested_selector_type>::diff(&old.#field_ident, &new.#field_ident);
+                        if !#krate::FieldSelector::is_empty(&nested) {
+                            selector.#field_ident = Some(nested);
+                        }
+                    }
+                } else {
+                    quote! {
+                        if old.#field_ident != new.#field_ident {
+                            selector.#field_ident = Some(<#nested_selector_type as #krate::FieldSelector>::all());
+                        }
+                    }
+                });
+            }
+            // An always field without a selector is compared in full
+            let unselected_eq = field_attrs.always.then(|| {
+                quote! {
+                    if self.#field_ident != other.#field_ident {
+                        return false;
+                    }
+                }
+            });
+            eq_stmts.push(quote! {
+                match &selector.#field_ident {
+                    Some(nested) => {
+                        if !#krate::SelectedEq::eq_selected(&self.#field_ident, &other.#field_ident, nested) {
+                            return false;
+                        }
+                    }
+                    None => {
+                        #unselected_eq
+                    }
+                }
+            });
+            let unselected_hash = field_attrs.always.then(|| {
+                quote! { ::core::hash::Hash::hash(&self.#field_ident, state); }
+            });
+            hash_stmts.push(quote! {
+                match &selector.#field_ident {
+                    Some(nested) => #krate::SelectedHash::hash_selected(&self.#field_ident, nested, state),
+                    None => {
+                        #unselected_hash
+                    }
+                }
+            });
+            let unselected_clone = if field_attrs.always {
+                quote! { ::core::clone::Clone::clone(&self.#field_ident) }
+            } else {
+                quote! { ::core::default::Default::default() }
+            };
+            clone_field_inits.push(quote! {
+                #field_ident: match &selector.#field_ident {
+                    Some(nested) => #krate::CloneSelected::clone_selected(&self.#field_ident, nested),
+                    None => #unselected_clone,
+                }
+            });
+            // An always field without a selector is kept in full
+            clear_stmts.push(if field_attrs.always {
+                quote! {
+                    if let Some(nested) = &selector.#field_ident {
+                        #krate::ClearUnselected::clear_unselected(&mut self.#field_ident, nested);
+                    }
+                }
+            } else {
+                quote! {
+                    match &selector.#field_ident {
+                        Some(nested) => #krate::ClearUnselected::clear_unselected(&mut self.#field_ident, nested),
+                        None => self.#field_ident = ::core::default::Default::default(),
+                    }
+                }
+            });
+            let sequence = is_sequence_type(&field.ty);
+            let segment = quote! {
+                #krate::__private::PathSegment { name: #serialized_name, sequence: #sequence }
+            };
+            let unselected_output = field_attrs.always.then(|| {
+                quote! {
+                    if with_always {
+                        path.push(#segment);
+                        visit(path);
+                        path.pop();
+                    }
+                }
+            });
+            let unselected_required = (field_attrs.required && !field_attrs.always).then(|| {
+                quote! { missing.push(format!("{}{}", prefix, #field_name_str)); }
+            });
+            required_stmts.push(quote! {
+                match &self.#field_ident {
+                    Some(nested) => #krate::FieldSelector::__collect_missing_required(
+                        nested,
+                        &format!("{}{}.", prefix, #field_name_str),
+                        missing,
+                    ),
+                    None => {
+                        #unselected_required
+                    }
+                }
+            });
+            if !field_attrs.sensitive {
+                present_stmts.push(quote! {
+                    let nested = selector.#field_ident.get_or_insert_with(<#nested_selector_type as #krate::FieldSelector>::new);
+                    #krate::PresentFields::enable_present(&self.#field_ident, nested);
+                    if #krate::FieldSelector::is_empty(nested) {
+                        selector.#field_ident = None;
+                    }
+                });
+                json_shape_stmts.push(quote! {
+                    if let Some(value) = shape.get(#serialized_name) {
+                        let nested = self.#field_ident.get_or_insert_with(<#nested_selector_type as #krate::FieldSelector>::new);
+                        if !#krate::__private::enable_json_shape(nested, value) {
+                            #krate::FieldSelector::enable_all(nested);
+                        }
+                    }
+                });
+            }
+            output_path_stmts.push(quote! {
+                match &self.#field_ident {
+                    Some(nested) => {
+                        path.push(#segment);
+                        #krate::FieldSelector::__visit_output_paths(nested, with_always, path, visit);
+                        path.pop();
+                    }
+                    None => {
+                        #unselected_output
+                    }
+                }
+            });
+            // A nested field with none of its fields serialized is still
+            // written as an empty object, so it is fetched as a whole
+            source_path_stmts.push(quote! {
+                match &self.#field_ident {
+                    Some(nested) => {
+                        let included = includes.len();
+                        #krate::FieldSelector::__collect_source_paths(
+                            nested,
+                            &format!("{}{}.", prefix, #serialized_name),
+                            includes,
+                            excludes,
+                        );
+                        if includes.len() == included {
+                            includes.push(format!("{}{}", prefix, #serialized_name));
+                        }
+                    }
+                    None => #unselected_source,
+                }
+            });
 
             selector_fields.push(quote! {
-                #[serde(skip_serializing_if = "Option::is_none")]
+                #(#docs)*
+                #[serde(skip_serializing_if = "Option::is_none", default)]
+                #skip_deserializing
+                #(#[serde(alias = #aliases)])*
                 pub #field_ident: Option<#nested_selector_type>
             });
 
-            enable_match_arms.push(quote! {
-                #field_name_str => {
-                    match &mut self.#field_ident {
-                        Some(nested) => nested.enable(&field_hierarchy[1..]),
-                        None => {
-                            let mut new_nested = #nested_selector_type::new();
-                            new_nested.enable(&field_hierarchy[1..]);
-                            self.#field_ident = Some(new_nested);
+            if let Some(method) = &typed_enable_ident {
+                typed_enable_methods.push(quote! {
+                    #[doc = #typed_enable_doc]
+                    ///
+                    /// The closure selects fields of the nested selector, which is
+                    /// created if the field was not enabled yet.
+                    pub fn #method(
+                        &mut self,
+                        select: impl FnOnce(&mut #nested_selector_type),
+                    ) -> &mut Self {
+                        select(self.#field_ident.get_or_insert_with(#nested_selector_type::new));
+                        self
+                    }
+                });
+            }
+
+            if has_builder_method {
+                let nested_builder = quote! {
+                    <#nested_selector_type as #krate::BuildSelector>::Builder
+                };
+                builder_methods.push(quote! {
+                    #[doc = #typed_enable_doc]
+                    ///
+                    /// The closure receives a builder for the nested selector,
+                    /// starting from the fields already enabled on it.
+                    pub fn #field_ident(
+                        mut self,
+                        build: impl FnOnce(#nested_builder) -> #nested_builder,
+                    ) -> Self {
+                        let nested = self
+                            .selector
+                            .#field_ident
+                            .take()
+                            .unwrap_or_else(#nested_selector_type::new);
+                        self.selector.#field_ident = Some(build(nested.into()).into());
+                        self
+                    }
+                });
+            }
+
+            let nested_enable_arm = |method: proc_macro2::TokenStream| {
+                quote! {
+                    #(#selectable_names)|* => {
+                        match &mut self.#field_ident {
+                            Some(nested) => nested.#method(&field_hierarchy[1..]),
+                            None => {
+                                let mut new_nested = #nested_selector_type::new();
+                                new_nested.#method(&field_hierarchy[1..]);
+                                self.#field_ident = Some(new_nested);
+                            }
                         }
                     }
                 }
-            });
+            };
 
-            serialize_fields.push(quote! {
-                if let Some(ref nested_selector) = field_selector.#field_ident {
-                    state.serialize_field(#field_name_str, &SerializeFields(&data.#field_ident, nested_selector))?;
+            if field_attrs.sensitive {
+                enable_match_arms.push(quote! { #(#selectable_names)|* => {} });
+            } else {
+                enable_match_arms.push(nested_enable_arm(quote! { enable }));
+            }
+            enable_sensitive_match_arms.push(nested_enable_arm(quote! { enable_sensitive_hierarchy }));
+
+            // Disabling the last nested field drops the nested selector
+            disable_match_arms.push(quote! {
+                #(#selectable_names)|* => {
+                    if field_hierarchy.len() == 1 {
+                        self.#field_ident = None;
+                    } else if let Some(nested) = &mut self.#field_ident {
+                        nested.disable(&field_hierarchy[1..]);
+                        if *nested == #nested_selector_type::new() {
+                            self.#field_ident = None;
+                        }
+                    }
+                }
+            });
+            disable_enum_match_arms.push(quote! {
+                #field_enum_ident::#variant_ident(nested_field) => {
+                    if let Some(nested) = &mut self.#field_ident {
+                        nested.disable_enum(nested_field);
+                        if *nested == #nested_selector_type::new() {
+                            self.#field_ident = None;
+                        }
+                    }
                 }
             });
 
+            let observe_sensitive = field_attrs.sensitive.then(|| {
+                quote! { #krate::__private::observe_sensitive::<#selector_ident>(#field_name_str, options); }
+            });
+            if field_attrs.always {
+                // Redaction hides sensitive fields, with every field inside redacted
+                let redacted = field_attrs.sensitive.then(|| {
+                    quote! {
+                        if options.redact.is_some() {
+                            let nested_selector = <#nested_selector_type as #krate::FieldSelector>::new();
+                            state.field(#serialized_name, &#krate::SerializeFieldsWith(&data.#field_ident, &nested_selector, *options))?;
+                        } else
+                    }
+                });
+                // Always present: narrowed by the nested selector when one is set
+                serialize_fields.push(quote! {
+                    #redacted {
+                        #observe_sensitive
+                        match field_selector.#field_ident {
+                            Some(ref nested_selector) => state.field(#serialized_name, &#krate::SerializeFieldsWith(&data.#field_ident, nested_selector, *options))?,
+                            None => state.field(#serialized_name, &data.#field_ident)?,
+                        }
+                    }
+                });
+            } else {
+                // Redaction hides sensitive fields even when they are enabled
+                let nested_selector = if field_attrs.sensitive {
+                    quote! { field_selector.#field_ident.as_ref().filter(|_| options.redact.is_none()) }
+                } else {
+                    quote! { field_selector.#field_ident.as_ref() }
+                };
+                serialize_fields.push(quote! {
+                    if let Some(nested_selector) = #nested_selector {
+                        #observe_sensitive
+                        state.field(#serialized_name, &#krate::SerializeFieldsWith(&data.#field_ident, nested_selector, *options))?;
+                    } else if options.redact.is_some() {
+                        // Same shape, with every field inside redacted
+                        let nested_selector = <#nested_selector_type as #krate::FieldSelector>::new();
+                        state.field(#serialized_name, &#krate::SerializeFieldsWith(&data.#field_ident, &nested_selector, *options))?;
+                    } else if options.nulls {
+                        state.field(#serialized_name, &::core::option::Option::<()>::None)?;
+                    }
+                });
+            }
+
             // Enum variant with nested field
             enum_variants.push(quote! {
+                #(#docs)*
                 #variant_ident(#nested_field_enum)
             });
 
-            enable_enum_match_arms.push(quote! {
-                #field_enum_ident::#variant_ident(nested) => {
-                    match &mut self.#field_ident {
-                        Some(selector) => {
-                            selector.enable_enum(nested);
+            if field_attrs.sensitive {
+                enable_enum_match_arms.push(quote! {
+                    #field_enum_ident::#variant_ident(_) => {}
+                });
+            } else {
+                enable_enum_match_arms.push(quote! {
+                    #field_enum_ident::#variant_ident(nested) => {
+                        match &mut self.#field_ident {
+                            Some(selector) => {
+                                selector.enable_enum(nested);
+                            }
+                            None => {
+                                let mut new_nested = #nested_selector_type::new();
+                                new_nested.enable_enum(nested);
+                                self.#field_ident = Some(new_nested);
+                            }
                         }
+                    }
+                });
+            }
+
+            let groups = &field_attrs.groups;
+            if field_attrs.sensitive {
+                // Only `enable_sensitive` turns a sensitive field on, but the
+                // group still reaches into one that already is
+                enable_group_stmts.push(quote! {
+                    if let Some(nested) = &mut self.#field_ident {
+                        nested.enable_group(group);
+                    }
+                });
+            } else if groups.is_empty() {
+                // Untagged nested fields are enabled only if the group reaches into them
+                enable_group_stmts.push(quote! {
+                    match &mut self.#field_ident {
+                        Some(nested) => nested.enable_group(group),
                         None => {
                             let mut new_nested = #nested_selector_type::new();
-                            new_nested.enable_enum(nested);
-                            self.#field_ident = Some(new_nested);
+                            new_nested.enable_group(group);
+                            if new_nested != #nested_selector_type::new() {
+                                self.#field_ident = Some(new_nested);
+                            }
+                        }
+                    }
+                });
+            } else {
+                // Tagged nested fields are enabled with all of their fields
+                enable_group_stmts.push(quote! {
+                    if matches!(group, #(#groups)|*) {
+                        let nested = self.#field_ident.get_or_insert_with(#nested_selector_type::new);
+                        #krate::FieldSelector::enable_all(nested);
+                    }
+                });
+            }
+
+            if field_attrs.default {
+                default_stmts.push(quote! {
+                    selector.#field_ident = Some(#nested_selector_type::with_defaults());
+                });
+            }
+
+            if !field_attrs.sensitive {
+                enable_all_stmts.push(quote! {
+                    self.#field_ident
+                        .get_or_insert_with(#nested_selector_type::new)
+                        .enable_all();
+                });
+            }
+
+            merge_stmts.push(quote! {
+                if let Some(other_nested) = &other.#field_ident {
+                    match &mut self.#field_ident {
+                        Some(nested) => nested.merge(other_nested),
+                        None => self.#field_ident = Some(other_nested.clone()),
+                    }
+                }
+            });
+
+            // A nested selector emptied by the intersection is dropped
+            intersect_stmts.push(quote! {
+                match (&mut self.#field_ident, &other.#field_ident) {
+                    (Some(nested), Some(other_nested)) => {
+                        let was_empty = *nested == #nested_selector_type::new();
+                        nested.intersect(other_nested);
+                        if !was_empty && *nested == #nested_selector_type::new() {
+                            self.#field_ident = None;
+                        }
+                    }
+                    (Some(_), None) => self.#field_ident = None,
+                    (None, _) => {}
+                }
+            });
+
+            leaf_count_terms.push(quote! {
+                self.#field_ident.as_ref().map_or(0, |nested| nested.enabled_leaf_count())
+            });
+            canonical_path_arms.push(quote! {
+                #(#output_names)|* => if rest.is_empty() {
+                    Some(#field_name_str.to_string())
+                } else {
+                    <#nested_selector_type as #krate::FieldSelector>::__canonical_path(rest)
+                        .map(|nested| format!("{}.{}", #field_name_str, nested))
+                }
+            });
+            if !field_attrs.sensitive {
+                validate_path_arms.push(quote! {
+                    #(#selectable_names)|* => {
+                        if rest.is_empty() {
+                            Ok(())
+                        } else {
+                            <#nested_selector_type as #krate::FieldSelector>::__validate_path(rest)
+                                .map_err(|at_segment| at_segment + 1)
+                        }
+                    }
+                });
+            }
+
+            contains_arms.push(quote! {
+                #(#selectable_names)|* => match &self.#field_ident {
+                    Some(nested) => rest.is_empty() || nested.contains(rest),
+                    None => false,
+                }
+            });
+            write_bits_stmts.push(quote! {
+                writer.write(self.#field_ident.is_some());
+                if let Some(nested) = &self.#field_ident {
+                    #krate::FieldSelector::__write_bits(nested, writer);
+                }
+            });
+            read_bits_stmts.push(quote! {
+                if reader.read()? {
+                    selector.#field_ident = Some(
+                        <#nested_selector_type as #krate::FieldSelector>::__read_bits(reader)?,
+                    );
+                }
+            });
+            depth_stmts.push(quote! {
+                if let Some(nested) = &self.#field_ident {
+                    depth = depth.max(1 + nested.depth());
+                }
+            });
+            prune_stmts.push(quote! {
+                if let Some(nested) = &mut self.#field_ident {
+                    nested.prune();
+                    if nested.is_empty() {
+                        self.#field_ident = None;
+                    }
+                }
+            });
+            if field_attrs.always {
+                // An empty nested selector narrows an always field to `{}`, so keep it
+                canonicalize_stmts.push(quote! {
+                    if let Some(nested) = &mut self.#field_ident {
+                        #krate::FieldSelector::canonicalize(nested);
+                    }
+                });
+            } else {
+                canonicalize_stmts.push(quote! {
+                    if let Some(nested) = &mut self.#field_ident {
+                        #krate::FieldSelector::canonicalize(nested);
+                        if #krate::FieldSelector::is_empty(nested) {
+                            self.#field_ident = None;
+                        }
+                    }
+                });
+            }
+            at_arms.push(quote! {
+                #(#selectable_names)|* => self
+                    .#field_ident
+                    .as_ref()
+                    .and_then(|nested| #krate::FieldSelector::at(nested, rest))
+            });
+            at_mut_arms.push(quote! {
+                #(#selectable_names)|* => self
+                    .#field_ident
+                    .as_mut()
+                    .and_then(|nested| #krate::FieldSelector::at_mut(nested, rest))
+            });
+
+            // An empty nested selector is reported as the nested path itself
+            collect_paths_stmts.push(quote! {
+                if let Some(nested) = &self.#field_ident {
+                    let path = format!("{}{}", prefix, #field_name_str);
+                    if nested.is_empty() {
+                        paths.push(path);
+                    } else {
+                        if with_parents {
+                            paths.push(path.clone());
                         }
+                        nested.__collect_enabled_paths(&format!("{}.", path), with_parents, paths);
+                    }
+                }
+            });
+            subset_checks.push(quote! {
+                match (&self.#field_ident, &other.#field_ident) {
+                    (Some(nested), Some(other_nested)) => nested.is_subset_of(other_nested),
+                    (Some(_), None) => false,
+                    (None, _) => true,
+                }
+            });
+            subtract_stmts.push(quote! {
+                if let (Some(nested), Some(other_nested)) =
+                    (&mut self.#field_ident, &other.#field_ident)
+                {
+                    nested.subtract(other_nested);
+                    if *nested == #nested_selector_type::new() {
+                        self.#field_ident = None;
                     }
                 }
             });
 
+            let roles = &field_attrs.roles;
+            if roles.is_empty() {
+                if field_attrs.sensitive {
+                    // Sensitive fields must list the roles that may see them
+                    restrict_to_role_stmts.push(quote! {
+                        self.#field_ident = None;
+                    });
+                } else {
+                    enable_for_role_stmts.push(quote! {
+                        self.#field_ident
+                            .get_or_insert_with(#nested_selector_type::new)
+                            .enable_for_role(role);
+                    });
+                    restrict_to_role_stmts.push(quote! {
+                        if let Some(nested) = &mut self.#field_ident {
+                            nested.restrict_to_role(role);
+                        }
+                    });
+                }
+            } else {
+                enable_for_role_stmts.push(quote! {
+                    if matches!(role, #(#roles)|*) {
+                        self.#field_ident
+                            .get_or_insert_with(#nested_selector_type::new)
+                            .enable_for_role(role);
+                    }
+                });
+                restrict_to_role_stmts.push(quote! {
+                    if !matches!(role, #(#roles)|*) {
+                        self.#field_ident = None;
+                    } else if let Some(nested) = &mut self.#field_ident {
+                        nested.restrict_to_role(role);
+                    }
+                });
+            }
+
             as_dot_path_arms.push(quote! {
                 #field_enum_ident::#variant_ident(nested) => {
                     format!("{}.{}", #field_name_str, nested.as_dot_path())
                 }
             });
 
-            deserialize_match_arms.push(quote! {
-                s if s.starts_with(concat!(#field_name_str, ".")) => {
-                    let rest = &s[#field_name_str.len() + 1..];
-                    Ok(#field_enum_ident::#variant_ident(rest.parse()?))
-                }
+            index_arms.push(quote! {
+                #field_enum_ident::#variant_ident(nested) => #path_offset + nested.index()
             });
+            catalog_entries.push(quote! {
+                #krate::__private::CatalogEntry::Nested(#field_name_str, #nested_field_enum::ALL)
+            });
+            if !field_attrs.sensitive {
+                selectable_catalog_entries.push(quote! {
+                    #krate::__private::CatalogEntry::Nested(
+                        #field_name_str,
+                        #nested_field_enum::__SELECTABLE,
+                    )
+                });
+            }
+            path_offset = quote! { #path_offset + #nested_field_enum::ALL.len() };
+
+            for name in &selectable_names {
+                deserialize_match_arms.push(quote! {
+                    s if s.starts_with(concat!(#name, ".")) => {
+                        let rest = &s[#name.len() + 1..];
+                        Ok(#field_enum_ident::#variant_ident(rest.parse()?))
+                    }
+                });
+            }
 
             #[cfg(feature = "schemars")]
-            schema_nested_prefixes.push((field_name_str.clone(), nested_type.clone()));
+            schema_nested_prefixes.push((field_name_str.clone(), nested_field_enum.clone()));
+            #[cfg(feature = "schemars")]
+            if !field_attrs.sensitive {
+                selector_schema_properties.push(quote! {
+                    properties.insert(
+                        #field_name_str,
+                        generator.subschema_for::<#nested_selector_type>(),
+                    );
+                });
+            }
+            #[cfg(feature = "schemars")]
+            schema_nested_relaxes.push(quote! {
+                if let Some(property) = properties.get_mut(#serialized_name) {
+                    #krate::__private::filter_struct_schema(property, generator, &mut |nested_properties| {
+                        <#nested_selector_type as #krate::FieldSelector>::__relax_schema_properties(
+                            nested_properties,
+                            generator,
+                        )
+                    });
+                }
+            });
+            #[cfg(feature = "schemars")]
+            schema_nested_filters.push(quote! {
+                if let (Some(nested), Some(property)) =
+                    (&self.#field_ident, properties.get_mut(#serialized_name))
+                {
+                    #krate::__private::filter_struct_schema(property, generator, &mut |nested_properties| {
+                        #krate::FieldSelector::__filter_schema_properties(nested, nested_properties, generator)
+                    });
+                }
+            });
         } else {
+            let deserialize_with = (!field_attrs.sensitive).then(|| {
+                quote! { #[serde(deserialize_with = #deserialize_enabled)] }
+            });
             selector_fields.push(quote! {
-                #[serde(skip_serializing_if = "Option::is_none")]
+                #(#docs)*
+                #[serde(skip_serializing_if = "Option::is_none", default)]
+                #deserialize_with
+                #skip_deserializing
+                #(#[serde(alias = #aliases)])*
                 pub #field_ident: Option<()>
             });
 
-            enable_match_arms.push(quote! {
-                #field_name_str => self.#field_ident = Some(())
-            });
+            if has_builder_method {
+                builder_methods.push(quote! {
+                    #[doc = #typed_enable_doc]
+                    pub const fn #field_ident(mut self) -> Self {
+                        self.selector.#field_ident = Some(());
+                        self
+                    }
+                });
+            }
+
+            if let Some(method) = &typed_enable_ident {
+                typed_enable_methods.push(quote! {
+                    #[doc = #typed_enable_doc]
+                    pub const fn #method(&mut self) -> &mut Self {
+                        self.#field_ident = Some(());
+                        self
+                    }
+                });
+            }
+
+            if field_attrs.sensitive {
+                enable_match_arms.push(quote! { #(#selectable_names)|* => {} });
+            } else {
+                enable_match_arms.push(quote! {
+                    #(#selectable_names)|* => self.#field_ident = Some(())
+                });
+            }
+            enable_sensitive_match_arms.push(quote! {
+                #(#selectable_names)|* => self.#field_ident = Some(())
+            });
+            disable_match_arms.push(quote! {
+                #(#selectable_names)|* => self.#field_ident = None
+            });
+            disable_enum_match_arms.push(quote! {
+                #field_enum_ident::#variant_ident => self.#field_ident = None
+            });
+
+            if field_attrs.always {
+                canonicalize_stmts.push(quote! {
+                    self.#field_ident = None;
+                });
+            }
+            // Redaction hides sensitive fields even when they are enabled
+            let shown = match (field_attrs.always, field_attrs.sensitive) {
+                (true, false) => None,
+                (true, true) => Some(quote! { options.redact.is_none() }),
+                (false, false) => Some(quote! { field_selector.#field_ident.is_some() }),
+                (false, true) => Some(quote! {
+                    field_selector.#field_ident.is_some() && options.redact.is_none()
+                }),
+            };
+            let value = match &field_attrs.transform {
+                Some(transform) => quote! { &#transform(&data.#field_ident) },
+                None => quote! { &data.#field_ident },
+            };
+            let observe_sensitive = field_attrs.sensitive.then(|| {
+                quote! { #krate::__private::observe_sensitive::<#selector_ident>(#field_name_str, options); }
+            });
+            match shown {
+                None => serialize_fields.push(quote! {
+                    state.field(#serialized_name, #value)?;
+                }),
+                Some(shown) => serialize_fields.push(quote! {
+                    if #shown {
+                        #observe_sensitive
+                        state.field(#serialized_name, #value)?;
+                    } else if let Some(placeholder) = options.redact {
+                        state.field(#serialized_name, &#krate::__private::Redacted(&data.#field_ident, placeholder))?;
+                    } else if options.nulls {
+                        state.field(#serialized_name, &::core::option::Option::<()>::None)?;
+                    }
+                }),
+            }
+
+            // Simple enum variant
+            enum_variants.push(quote! {
+                #(#docs)*
+                #variant_ident
+            });
+
+            if field_attrs.sensitive {
+                enable_enum_match_arms.push(quote! {
+                    #field_enum_ident::#variant_ident => {}
+                });
+            } else {
+                enable_enum_match_arms.push(quote! {
+                    #field_enum_ident::#variant_ident => self.#field_ident = Some(())
+                });
+            }
+
+            let groups = &field_attrs.groups;
+            if !groups.is_empty() && !field_attrs.sensitive {
+                enable_group_stmts.push(quote! {
+                    if matches!(group, #(#groups)|*) {
+                        self.#field_ident = Some(());
+                    }
+                });
+            }
+
+            if field_attrs.default {
+                default_stmts.push(quote! {
+                    selector.#field_ident = Some(());
+                });
+            }
+
+            if !field_attrs.sensitive {
+                enable_all_stmts.push(quote! {
+                    self.#field_ident = Some(());
+                });
+                enable_wildcard_stmts.push(quote! {
+                    self.#field_ident = Some(());
+                });
+            }
+
+            merge_stmts.push(quote! {
+                if other.#field_ident.is_some() {
+                    self.#field_ident = Some(());
+                }
+            });
+
+            intersect_stmts.push(quote! {
+                if other.#field_ident.is_none() {
+                    self.#field_ident = None;
+                }
+            });
+
+            leaf_count_terms.push(quote! {
+                usize::from(self.#field_ident.is_some())
+            });
+            canonical_path_arms.push(quote! {
+                #(#output_names)|* => rest.is_empty().then(|| #field_name_str.to_string())
+            });
+            let column = match (&field_attrs.column, &container_attrs.table) {
+                (Some(column), _) => column.value(),
+                (None, Some(table)) => format!("{}.{}", table, field_name_str),
+                (None, None) => field_name_str.clone(),
+            };
+            let selected = if field_attrs.always {
+                quote! { true }
+            } else {
+                quote! { self.#field_ident.is_some() }
+            };
+            let column_selected = if field_attrs.always {
+                quote! { with_always || self.#field_ident.is_some() }
+            } else {
+                quote! { self.#field_ident.is_some() }
+            };
+            sql_column_stmts.push(quote! {
+                if #column_selected {
+                    columns.push(#column);
+                }
+            });
+            update_stmts.push(quote! {
+                if selector.#field_ident.is_some() {
+                    self.#field_ident = ::core::clone::Clone::clone(&source.#field_ident);
+                }
+            });
+            if !field_attrs.sensitive {
+                diff_stmts.push(quote! {
+                    if old.#field_ident != new.#field_ident {
+                        selector.#field_ident = Some(());
+                    }
+                });
+            }
+            let selected_in = if field_attrs.always {
+                quote! { true }
+            } else {
+                quote! { selector.#field_ident.is_some() }
+            };
+            eq_stmts.push(quote! {
+                if #selected_in && self.#field_ident != other.#field_ident {
+                    return false;
+                }
+            });
+            hash_stmts.push(quote! {
+                if #selected_in {
+                    ::core::hash::Hash::hash(&self.#field_ident, state);
+                }
+            });
+            if !field_attrs.always {
+                clear_stmts.push(quote! {
+                    if selector.#field_ident.is_none() {
+                        self.#field_ident = ::core::default::Default::default();
+                    }
+                });
+            }
+            clone_field_inits.push(quote! {
+                #field_ident: if #selected_in {
+                    ::core::clone::Clone::clone(&self.#field_ident)
+                } else {
+                    ::core::default::Default::default()
+                }
+            });
+            if field_attrs.required && !field_attrs.always {
+                required_stmts.push(quote! {
+                    if self.#field_ident.is_none() {
+                        missing.push(format!("{}{}", prefix, #field_name_str));
+                    }
+                });
+            }
+            if !field_attrs.sensitive {
+                present_stmts.push(quote! {
+                    if #krate::Present::is_present(&self.#field_ident) {
+                        selector.#field_ident = Some(());
+                    }
+                });
+                json_shape_stmts.push(quote! {
+                    if shape.contains_key(#serialized_name) {
+                        self.#field_ident = Some(());
+                    }
+                });
+            }
+            output_path_stmts.push(quote! {
+                if #column_selected {
+                    path.push(#krate::__private::PathSegment { name: #serialized_name, sequence: false });
+                    visit(path);
+                    path.pop();
+                }
+            });
+            source_path_stmts.push(quote! {
+                if #selected {
+                    includes.push(format!("{}{}", prefix, #serialized_name));
+                } else {
+                    excludes.push(format!("{}{}", prefix, #serialized_name));
+                }
+            });
+            if !field_attrs.sensitive {
+                validate_path_arms.push(quote! {
+                    #(#selectable_names)|* => if rest.is_empty() { Ok(()) } else { Err(1) }
+                });
+            }
+
+            contains_arms.push(quote! {
+                #(#selectable_names)|* => rest.is_empty() && self.#field_ident.is_some()
+            });
+
+            write_bits_stmts.push(quote! {
+                writer.write(self.#field_ident.is_some());
+            });
+            read_bits_stmts.push(quote! {
+                if reader.read()? {
+                    selector.#field_ident = Some(());
+                }
+            });
+            depth_stmts.push(quote! {
+                if self.#field_ident.is_some() {
+                    depth = depth.max(1);
+                }
+            });
+
+            collect_paths_stmts.push(quote! {
+                if self.#field_ident.is_some() {
+                    paths.push(format!("{}{}", prefix, #field_name_str));
+                }
+            });
+            subset_checks.push(quote! {
+                (self.#field_ident.is_none() || other.#field_ident.is_some())
+            });
+            subtract_stmts.push(quote! {
+                if other.#field_ident.is_some() {
+                    self.#field_ident = None;
+                }
+            });
+
+            let roles = &field_attrs.roles;
+            if roles.is_empty() {
+                if field_attrs.sensitive {
+                    // Sensitive fields must list the roles that may see them
+                    restrict_to_role_stmts.push(quote! {
+                        self.#field_ident = None;
+                    });
+                } else {
+                    enable_for_role_stmts.push(quote! {
+                        self.#field_ident = Some(());
+                    });
+                }
+            } else {
+                enable_for_role_stmts.push(quote! {
+                    if matches!(role, #(#roles)|*) {
+                        self.#field_ident = Some(());
+                    }
+                });
+                restrict_to_role_stmts.push(quote! {
+                    if !matches!(role, #(#roles)|*) {
+                        self.#field_ident = None;
+                    }
+                });
+            }
+
+            as_dot_path_arms.push(quote! {
+                #field_enum_ident::#variant_ident => #field_name_str.to_string()
+            });
+
+            index_arms.push(quote! {
+                #field_enum_ident::#variant_ident => #path_offset
+            });
+            catalog_entries.push(quote! {
+                #krate::__private::CatalogEntry::Leaf(#field_name_str)
+            });
+            if !field_attrs.sensitive {
+                selectable_catalog_entries.push(quote! {
+                    #krate::__private::CatalogEntry::Leaf(#field_name_str)
+                });
+            }
+            path_offset = quote! { #path_offset + 1 };
+
+            deserialize_match_arms.push(quote! {
+                #(#selectable_names)|* => Ok(#field_enum_ident::#variant_ident)
+            });
+
+            #[cfg(feature = "schemars")]
+            schema_simple_fields.push(field_name_str.clone());
+            #[cfg(feature = "schemars")]
+            if !field_attrs.sensitive {
+                selector_schema_properties.push(quote! {
+                    properties.insert(#field_name_str, enabled_leaf.clone());
+                });
+            }
+        }
+
+        #[cfg(feature = "schemars")]
+        schema_keep_arms.push(if field_attrs.always {
+            quote! { #serialized_name => true }
+        } else {
+            quote! { #serialized_name => self.#field_ident.is_some() }
+        });
+
+        new_field_inits.push(quote! {
+            #field_ident: None
+        });
+
+        serialized_field_names.push(serialized_name.clone());
+
+        // Count enabled fields for serialization
+        if field_attrs.always {
+            count_enabled_fields.push(quote! { + 1 });
+        } else {
+            count_enabled_fields.push(quote! {
+                + if field_selector.#field_ident.is_some() { 1 } else { 0 }
+            });
+        }
+    }
+
+    // Generate schema nested field tokens (used only with schemars feature)
+    #[cfg(feature = "schemars")]
+    let schema_nested_enum_types: Vec<_> = schema_nested_prefixes
+        .iter()
+        .map(|(_, nested_field_enum)| quote! { #nested_field_enum })
+        .collect();
+    #[cfg(feature = "schemars")]
+    let schema_nested_prefix_strs: Vec<_> = schema_nested_prefixes
+        .iter()
+        .map(|(prefix, _)| prefix.clone())
+        .collect();
+
+    // Generate schemars impl conditionally at macro compile-time
+    #[cfg(feature = "schemars")]
+    let schemars_impl = quote! {
+        impl #schemars_path::JsonSchema for #field_enum_ident {
+            fn schema_name() -> ::std::borrow::Cow<'static, str> {
+                ::std::borrow::Cow::Borrowed(stringify!(#field_enum_ident))
+            }
+
+            fn json_schema(generator: &mut #schemars_path::SchemaGenerator) -> #schemars_path::Schema {
+                // Collect all possible enum values
+                let mut all_values: Vec<String> = Vec::new();
+
+                // Add simple field values
+                #(all_values.push(#schema_simple_fields.to_string());)*
+
+                // For nested fields, get their enum values and prefix them
+                #(
+                    // Call json_schema directly to get the inline schema, not a $ref
+                    let nested_schema = <#schema_nested_enum_types as #schemars_path::JsonSchema>::json_schema(generator);
+                    if let Some(obj) = nested_schema.as_object() {
+                        if let Some(enum_values) = obj.get("enum").and_then(|v| v.as_array()) {
+                            for val in enum_values {
+                                if let Some(s) = val.as_str() {
+                                    all_values.push(format!("{}.{}", #schema_nested_prefix_strs, s));
+                                }
+                            }
+                        }
+                    }
+                )*
+
+                #schemars_path::json_schema!({
+                    "type": "string",
+                    "enum": all_values,
+                    "description": concat!("Field selector for ", stringify!(#struct_name), " - serializes as dot notation (e.g., \"field.nested\")")
+                })
+            }
+        }
+
+        // Schema of the selector itself, as accepted by its `Deserialize` impl
+        impl #schemars_path::JsonSchema for #selector_ident {
+            fn schema_name() -> ::std::borrow::Cow<'static, str> {
+                ::std::borrow::Cow::Borrowed(stringify!(#selector_ident))
+            }
+
+            fn json_schema(generator: &mut #schemars_path::SchemaGenerator) -> #schemars_path::Schema {
+                // Any value except `false` enables a leaf
+                #[allow(unused_variables)]
+                let enabled_leaf = #schemars_path::json_schema!({
+                    "not": { "const": false }
+                });
+                #[allow(unused_mut)]
+                let mut properties = ::std::collections::BTreeMap::<&'static str, #schemars_path::Schema>::new();
+                #(#selector_schema_properties)*
+
+                #schemars_path::json_schema!({
+                    "type": "object",
+                    "properties": properties,
+                    "description": concat!("Fields of ", stringify!(#struct_name), " to serialize, as nested objects")
+                })
+            }
+        }
+    };
+
+    #[cfg(not(feature = "schemars"))]
+    let schemars_impl = quote! {};
+
+    #[cfg(feature = "schemars")]
+    let schemars_filter_impl = quote! {
+        fn __filter_schema_properties(
+            &self,
+            properties: &mut #krate::__private::serde_json::Map<String, #krate::__private::serde_json::Value>,
+            generator: &#schemars_path::SchemaGenerator,
+        ) {
+            properties.retain(|key, _| match key.as_str() {
+                #(#schema_keep_arms,)*
+                _ => false,
+            });
+            #(#schema_nested_filters)*
+        }
+
+        #[allow(unused_variables)]
+        fn __relax_schema_properties(
+            properties: &mut #krate::__private::serde_json::Map<String, #krate::__private::serde_json::Value>,
+            generator: &#schemars_path::SchemaGenerator,
+        ) {
+            #(#schema_nested_relaxes)*
+        }
+    };
+
+    #[cfg(not(feature = "schemars"))]
+    let schemars_filter_impl = quote! {};
+
+    // Type whose values are read, the remote one for mirrors
+    let data_type = match &container_attrs.remote {
+        Some(remote) => quote! { #remote },
+        None => quote! { #struct_name },
+    };
+    let diff_impl = container_attrs.diff.then(|| {
+        quote! {
+            impl #selector_ident {
+                /// A selector enabling exactly the fields whose values differ
+                /// between `old` and `new`, except sensitive fields.
+                pub fn diff(old: &#data_type, new: &#data_type) -> Self {
+                    #[allow(unused_mut)]
+                    let mut selector = Self::new();
+                    #(#diff_stmts)*
+                    selector
+                }
+            }
+        }
+    });
+
+    let partial_impl = container_attrs.partial.then(|| {
+        let partial_ident = format_ident!("Partial{}", struct_name);
+        let partial_doc = format!(
+            "The fields of [`{}`] enabled in a [`{}`], each `None` when it is not.",
+            struct_name, selector_ident
+        );
+        quote! {
+            #[doc = #partial_doc]
+            #[derive(Default)]
+            #generated_vis struct #partial_ident {
+                #(#partial_fields,)*
+            }
+
+            impl #partial_ident {
+                /// Copy the fields of `data` enabled in `selector`.
+                pub fn from_selected(data: &#data_type, selector: &#selector_ident) -> Self {
+                    #partial_ident {
+                        #(#from_selected_fields,)*
+                    }
+                }
+
+                /// Overwrite the fields of `target` that are set in `self`.
+                #[allow(unused_variables)]
+                pub fn merge_into(self, target: &mut #data_type) {
+                    #(#merge_into_stmts)*
+                }
+            }
+        }
+    });
+
+    // Mirrors can't have inherent methods on the remote type
+    let inherent_target = container_attrs.remote.is_none().then_some(struct_name);
+    let clear_impl = container_attrs.clear.then(|| {
+        let forward = inherent_target.map(|struct_name| {
+            quote! {
+                impl #struct_name {
+                    /// Reset the fields not enabled in `selector`; see
+                    /// `serialize_fields::ClearUnselected`.
+                    pub fn clear_unselected(&mut self, selector: &#selector_ident) {
+                        #krate::ClearUnselected::clear_unselected(self, selector)
+                    }
+                }
+            }
+        });
+        quote! {
+            impl #krate::ClearUnselected<#selector_ident> for #data_type {
+                #[allow(unused_variables)]
+                fn clear_unselected(&mut self, selector: &#selector_ident) {
+                    #(#clear_stmts)*
+                }
+            }
+
+            #forward
+        }
+    });
+
+    let eq_impl = container_attrs.eq.then(|| {
+        let forward = inherent_target.map(|struct_name| {
+            quote! {
+                impl #struct_name {
+                    /// Whether the fields serialized with `selector` are equal;
+                    /// see `serialize_fields::SelectedEq`.
+                    pub fn eq_selected(&self, other: &Self, selector: &#selector_ident) -> bool {
+                        #krate::SelectedEq::eq_selected(self, other, selector)
+                    }
+                }
+            }
+        });
+        quote! {
+            impl #krate::SelectedEq<#selector_ident> for #data_type {
+                #[allow(unused_variables)]
+                fn eq_selected(&self, other: &Self, selector: &#selector_ident) -> bool {
+                    #(#eq_stmts)*
+                    true
+                }
+            }
+
+            #forward
+        }
+    });
+    let hash_impl = container_attrs.hash.then(|| {
+        let forward = inherent_target.map(|struct_name| {
+            quote! {
+                impl #struct_name {
+                    /// Hash the fields serialized with `selector`; see
+                    /// `serialize_fields::SelectedHash`.
+                    pub fn hash_selected<H: ::core::hash::Hasher>(&self, selector: &#selector_ident, state: &mut H) {
+                        #krate::SelectedHash::hash_selected(self, selector, state)
+                    }
+                }
+            }
+        });
+        quote! {
+            impl #krate::SelectedHash<#selector_ident> for #data_type {
+                #[allow(unused_variables)]
+                fn hash_selected<H: ::core::hash::Hasher>(&self, selector: &#selector_ident, state: &mut H) {
+                    #(#hash_stmts)*
+                }
+            }
+
+            #forward
+        }
+    });
+
+    let clone_impl = container_attrs.clone.then(|| {
+        let forward = inherent_target.map(|struct_name| {
+            quote! {
+                impl #struct_name {
+                    /// A copy holding only the fields enabled in `selector`;
+                    /// see `serialize_fields::CloneSelected`.
+                    pub fn clone_selected(&self, selector: &#selector_ident) -> Self {
+                        #krate::CloneSelected::clone_selected(self, selector)
+                    }
+                }
+            }
+        });
+        quote! {
+            impl #krate::CloneSelected<#selector_ident> for #data_type {
+                #[allow(unused_variables)]
+                fn clone_selected(&self, selector: &#selector_ident) -> Self {
+                    Self {
+                        #(#clone_field_inits,)*
+                    }
+                }
+            }
+
+            #forward
+        }
+    });
+
+    let present_impl = container_attrs.present.then(|| {
+        quote! {
+            impl #krate::PresentFields<#selector_ident> for #data_type {
+                #[allow(unused_variables)]
+                fn enable_present(&self, selector: &mut #selector_ident) {
+                    #(#present_stmts)*
+                }
+            }
+
+            impl #selector_ident {
+                /// Create a selector enabling the fields of `data` that hold
+                /// data; see `serialize_fields::PresentFields`.
+                pub fn from_present(data: &#data_type) -> Self {
+                    let mut selector = <Self as #krate::FieldSelector>::new();
+                    #krate::PresentFields::enable_present(data, &mut selector);
+                    selector
+                }
+            }
+        }
+    });
+
+    let update_impl = container_attrs.update.then(|| {
+        quote! {
+            impl #krate::UpdateFields for #struct_name {
+                #[allow(unused_variables)]
+                fn update_fields(&mut self, source: &Self, selector: &Self::FieldSelector) {
+                    #(#update_stmts)*
+                }
+            }
+
+            impl #selector_ident {
+                /// Copy the enabled fields of `source` onto `target`; see
+                /// `serialize_fields::UpdateFields`.
+                pub fn apply_update(&self, target: &mut #struct_name, source: &#struct_name) {
+                    #krate::UpdateFields::update_fields(target, source, self)
+                }
+            }
+        }
+    });
+
+    // Serialization of the struct itself, or of the remote type it mirrors
+    let serialize_impl = match &container_attrs.remote {
+        None => {
+            let body = serialize_body(
+                &krate,
+                struct_name,
+                &serialize_fields,
+                &count_enabled_fields,
+                &serialized_field_names,
+            );
+            quote! {
+                impl #krate::SerializeFieldsTrait for #struct_name {
+                    type FieldSelector = #selector_ident;
+
+                    fn serialize_fields(&self) -> Self::FieldSelector {
+                        #selector_ident::new()
+                    }
+
+                    fn __serialize_with<__S>(
+                        &self,
+                        field_selector: &Self::FieldSelector,
+                        options: &#krate::SerializeOptions<'_>,
+                        __serializer: __S,
+                    ) -> Result<__S::Ok, __S::Error>
+                    where
+                        __S: #serde_path::Serializer,
+                    {
+                        let data = self;
+                        #body
+                    }
+                }
+            }
+        }
+        Some(remote) => {
+            let remote_name = &remote.segments.last().expect("paths have a segment").ident;
+            let body = serialize_body(
+                &krate,
+                remote_name,
+                &serialize_fields,
+                &count_enabled_fields,
+                &serialized_field_names,
+            );
+            let mirrored_idents = fields.iter().map(|field| &field.ident);
+            quote! {
+                impl #krate::RemoteFields for #struct_name {
+                    type Remote = #remote;
+                    type FieldSelector = #selector_ident;
+
+                    fn __serialize_remote_with<__S>(
+                        data: &Self::Remote,
+                        field_selector: &Self::FieldSelector,
+                        options: &#krate::SerializeOptions<'_>,
+                        __serializer: __S,
+                    ) -> Result<__S::Ok, __S::Error>
+                    where
+                        __S: #serde_path::Serializer,
+                    {
+                        #body
+                    }
+
+                    fn __assert_fields(&self, remote: &Self::Remote) {
+                        fn same_type<T: ?Sized>(_: &T, _: &T) {}
+                        #(same_type(&self.#mirrored_idents, &remote.#mirrored_idents);)*
+                    }
+                }
+            }
+        }
+    };
+
+    // Generate the complete implementation
+    let catalog = |entries: &[proc_macro2::TokenStream]| {
+        quote! {
+            {
+                const ENTRIES: &[#krate::__private::CatalogEntry] = &[#(#entries,)*];
+                const BYTES_LEN: usize = #krate::__private::catalog_bytes_len(ENTRIES);
+                const LEN: usize = #krate::__private::catalog_len(ENTRIES);
+                const BYTES: [u8; BYTES_LEN] = #krate::__private::catalog_bytes(ENTRIES);
+                const PATHS: [&str; LEN] = #krate::__private::catalog_paths(&BYTES, ENTRIES);
+                &PATHS
+            }
+        }
+    };
+    let all_catalog = catalog(&catalog_entries);
+    let selectable_catalog = catalog(&selectable_catalog_entries);
+
+    let enum_doc = format!(
+        "Enum representing all fields of [`{}`] for type-safe field selection.",
+        struct_name
+    );
+    let selector_doc = format!("Field selector for [`{}`].", struct_name);
+    let builder_ident = format_ident!("{}Builder", selector_ident);
+    let builder_doc = format!(
+        "Typed builder for [`{}`], created with `{}::builder()`.",
+        selector_ident, selector_ident
+    );
+
+    let expanded = quote! {
+        #[doc = #enum_doc]
+        ///
+        /// Serializes to dot notation (e.g., "profile.bio").
+        #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #generated_vis enum #field_enum_ident {
+            #(#enum_variants,)*
+        }
+
+        impl #field_enum_ident {
+            /// Every dot notation path of the struct, in declaration order.
+            pub const ALL: &'static [&'static str] = #all_catalog;
+
+            /// Paths reachable through `enable`, i.e. without sensitive fields.
+            #[doc(hidden)]
+            pub const __SELECTABLE: &'static [&'static str] = #selectable_catalog;
+
+            /// Position of this field's path in [`Self::ALL`].
+            pub const fn index(&self) -> usize {
+                match self {
+                    #(#index_arms,)*
+                }
+            }
+
+            /// Returns the dot notation path for this field, without allocating.
+            pub const fn as_str(&self) -> &'static str {
+                Self::ALL[self.index()]
+            }
+
+            /// Returns the dot notation path for this field.
+            pub fn as_dot_path(&self) -> String {
+                match self {
+                    #(#as_dot_path_arms,)*
+                }
+            }
+        }
+
+        impl ::std::fmt::Debug for #field_enum_ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::std::fmt::Display for #field_enum_ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::std::str::FromStr for #field_enum_ident {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    #(#deserialize_match_arms,)*
+                    _ => Err(format!("Unknown field: {}", s)),
+                }
+            }
+        }
+
+        impl #serde_path::Serialize for #field_enum_ident {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: #serde_path::Serializer,
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> #serde_path::Deserialize<'de> for #field_enum_ident {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: #serde_path::Deserializer<'de>,
+            {
+                let s = <String as #serde_path::Deserialize>::deserialize(deserializer)?;
+                s.parse().map_err(#serde_path::de::Error::custom)
+            }
+        }
+
+        #schemars_impl
+
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            Eq,
+            PartialOrd,
+            Ord,
+            Hash,
+            #serde_path::Serialize,
+            #serde_path::Deserialize
+            #(, #extra_derives)*
+        )]
+        #[doc = #selector_doc]
+        ///
+        /// Each field is `Some` when the corresponding field of the struct is
+        /// selected for serialization.
+        ///
+        /// The selector serializes to and deserializes from nested objects such
+        /// as `{"id": true, "profile": {"bio": true}}`. Unknown keys are ignored,
+        /// and sensitive fields are never enabled by deserialization.
+        #[serde(crate = #serde_crate)]
+        #generated_vis struct #selector_ident {
+            #(#selector_fields,)*
+        }
+
+        impl #selector_ident {
+            /// Every leaf field path accepted by this selector, in dot notation
+            /// and declaration order.
+            ///
+            /// Nested structs are expanded recursively. Sensitive fields are
+            /// listed too, since they are valid paths for `enable_sensitive`.
+            pub const FIELD_PATHS: &'static [&'static str] = #field_enum_ident::ALL;
+
+            /// Start a typed builder with every field disabled.
+            pub const fn builder() -> #builder_ident {
+                #builder_ident { selector: Self::new() }
+            }
+
+            /// Create a selector with every field disabled.
+            ///
+            /// This is a `const fn`, so presets can be built in a `static` with a
+            /// struct literal using `..Self::new()`, or with the builder when the
+            /// struct has no nested fields.
+            pub const fn new() -> Self {
+                #selector_ident {
+                    #(#new_field_inits,)*
+                }
+            }
+
+            /// Create a selector with the fields marked
+            /// `#[serialize_fields(default)]` enabled, recursively.
+            ///
+            /// Useful as the projection to return when a client does not ask
+            /// for specific fields.
+            pub fn with_defaults() -> Self {
+                let mut selector = Self::new();
+                #(#default_stmts)*
+                selector
+            }
+
+            /// Names of the presets declared with
+            /// `#[serialize_fields(preset(name = "...", fields = "..."))]`.
+            pub const PRESETS: &'static [&'static str] = &[#(#preset_names),*];
+
+            /// The selector declared as the preset `name`, or `None` if there is
+            /// no such preset.
+            pub fn preset(name: &str) -> Option<Self> {
+                match name {
+                    #(#preset_names => Some(#krate::utils::create_selector_from_list(#preset_fields)),)*
+                    _ => None,
+                }
+            }
+
+            /// Create a selector with every field enabled, recursively.
+            ///
+            /// Sensitive fields stay disabled.
+            pub fn all() -> Self {
+                let mut selector = Self::new();
+                selector.enable_all();
+                selector
+            }
+
+            /// Create a selector with every field enabled except the given dot
+            /// paths, e.g. `all_except(&["password_hash", "settings"])`.
+            ///
+            /// Sensitive fields stay disabled as with `all()`.
+            pub fn all_except(excluded: &[&str]) -> Self {
+                <Self as #krate::FieldSelector>::all_except(excluded)
+            }
+
+            /// Enable every field, recursing into nested selectors.
+            ///
+            /// Sensitive fields are left as they are; use `enable_sensitive`
+            /// for them.
+            pub fn enable_all(&mut self) {
+                #(#enable_all_stmts)*
+            }
+
+            /// Enable a field using dot notation, e.g. `"profile.bio"`.
+            ///
+            /// A final `*` segment enables every non-nested field at that level,
+            /// so `"profile.*"` selects all plain fields of `profile`. A final `**`
+            /// enables the whole subtree recursively, like `enable_all`. Sensitive
+            /// fields are not matched by either. Unknown paths are ignored, and
+            /// reported to the hook set with `serialize_fields::set_unknown_field_hook`.
+            pub fn enable_dot_hierarchy(&mut self, field: &str) {
+                let split: Vec<&str> = field.split('.').collect();
+                #krate::__private::observe_enable::<Self>(&split);
+                self.enable(&split);
+            }
+
+            /// Enable a field from an already split path, e.g. `&["profile", "bio"]`.
+            pub fn enable(&mut self, field_hierarchy: &[&str]) {
+                if field_hierarchy.is_empty() {
+                    return;
+                }
+
+                match field_hierarchy[0] {
+                    // `*` as the last segment: every plain field at this level
+                    "*" if field_hierarchy.len() == 1 => {
+                        #(#enable_wildcard_stmts)*
+                    }
+                    // `**` as the last segment: the whole subtree
+                    "**" if field_hierarchy.len() == 1 => self.enable_all(),
+                    #(#enable_match_arms,)*
+                    _ => {}
+                }
+            }
+
+            /// Enable a field using dot notation, failing on unknown paths.
+            ///
+            /// The error gives the first unknown segment, including a nested
+            /// suffix on a plain field such as `"id.invalid_nested"`. Sensitive
+            /// fields are reported as unknown.
+            pub fn try_enable_dot_hierarchy(
+                &mut self,
+                field: &str,
+            ) -> Result<(), #krate::UnknownField> {
+                <Self as #krate::FieldSelector>::try_enable_dot_hierarchy(self, field)
+            }
+
+            /// Enable a field from an already split path, failing on unknown paths.
+            pub fn try_enable(&mut self, field_hierarchy: &[&str]) -> Result<(), #krate::UnknownField> {
+                <Self as #krate::FieldSelector>::try_enable(self, field_hierarchy)
+            }
+
+            /// Enable a field using dot notation, except for the leaf fields
+            /// `policy` vetoes, which are returned; see
+            /// `serialize_fields::FieldSelector::enable_with_policy`.
+            pub fn enable_with_policy<P>(&mut self, field: &str, policy: &P) -> Vec<String>
+            where
+                P: #krate::FieldAccessPolicy + ?Sized,
+            {
+                <Self as #krate::FieldSelector>::enable_with_policy(self, field, policy)
+            }
+
+            /// Disable a field using dot notation, e.g. `"profile.bio"`.
+            ///
+            /// Disabling a nested struct field removes its whole selection, and
+            /// disabling the last enabled field of a nested selector removes the
+            /// nested selector. Unknown paths are ignored.
+            pub fn disable_dot_hierarchy(&mut self, field: &str) {
+                let split: Vec<&str> = field.split('.').collect();
+                self.disable(&split);
+            }
+
+            /// Disable a field from an already split path, e.g. `&["profile", "bio"]`.
+            pub fn disable(&mut self, field_hierarchy: &[&str]) {
+                if field_hierarchy.is_empty() {
+                    return;
+                }
+
+                match field_hierarchy[0] {
+                    #(#disable_match_arms,)*
+                    _ => {}
+                }
+            }
+
+            /// Enable a field using dot notation, including fields marked
+            /// `#[serialize_fields(sensitive)]`.
+            ///
+            /// Only call this with trusted paths: sensitive fields are ignored by
+            /// `enable_dot_hierarchy` and `enable` so that selectors built from
+            /// client input can never expose them.
+            pub fn enable_sensitive(&mut self, field: &str) {
+                let split: Vec<&str> = field.split('.').collect();
+                self.enable_sensitive_hierarchy(&split);
+            }
+
+            /// Enable a field from a split path, including sensitive fields.
+            pub fn enable_sensitive_hierarchy(&mut self, field_hierarchy: &[&str]) {
+                if field_hierarchy.is_empty() {
+                    return;
+                }
+
+                match field_hierarchy[0] {
+                    #(#enable_sensitive_match_arms,)*
+                    _ => {}
+                }
+            }
+
+            /// Enable every field tagged with `#[serialize_fields(group = "...")]`
+            /// for the given group.
+            ///
+            /// Nested selectors are searched as well: a tagged nested field is
+            /// enabled with all of its fields, and an untagged one is enabled
+            /// when some of its own fields belong to the group. Sensitive fields
+            /// are left disabled, as with `enable_all`.
+            pub fn enable_group(&mut self, group: &str) {
+                #(#enable_group_stmts)*
+            }
+
+            /// Enable every field the given role may see.
+            ///
+            /// Fields without `#[serialize_fields(roles(...))]` are visible to
+            /// every role, except sensitive ones which must list the role
+            /// explicitly. Nested selectors are filled in recursively.
+            pub fn enable_for_role(&mut self, role: &str) {
+                #(#enable_for_role_stmts)*
+            }
+
+            /// Disable every enabled field the given role may not see.
+            ///
+            /// This clamps an existing selection (for example one parsed from
+            /// client input) to the role, recursing into nested selectors.
+            /// Sensitive fields without `roles(...)` are disabled for every
+            /// role, as `enable_for_role` never enables them.
+            pub fn restrict_to_role(&mut self, role: &str) {
+                #(#restrict_to_role_stmts)*
+            }
+
+            #(#typed_enable_methods)*
+
+            /// Enable a field using the type-safe field enum.
+            ///
+            /// Sensitive fields are ignored; use `enable_sensitive` for them.
+            pub fn enable_enum(&mut self, field: #field_enum_ident) {
+                match field {
+                    #(#enable_enum_match_arms,)*
+                }
+            }
+
+            /// Disable a field using the type-safe field enum.
+            pub fn disable_enum(&mut self, field: #field_enum_ident) {
+                match field {
+                    #(#disable_enum_match_arms,)*
+                }
+            }
+
+            /// Enable every field enabled in `other`, recursively.
+            ///
+            /// This is the union of both selections, also available as `|`.
+            pub fn merge(&mut self, other: &Self) {
+                #(#merge_stmts)*
+            }
+
+            /// Keep only the fields enabled in both `self` and `other`, recursively.
+            ///
+            /// Use it to clamp a client-supplied selector to what the caller may
+            /// see. Also available as `&`.
+            pub fn intersect(&mut self, other: &Self) {
+                #(#intersect_stmts)*
+            }
+
+            /// Remove every field enabled in `other`, recursively, leaving the
+            /// fields enabled in `self` only.
+            ///
+            /// `requested - allowed` gives the fields a client asked for but may
+            /// not see. Nested selectors left empty are dropped.
+            pub fn subtract(&mut self, other: &Self) {
+                #(#subtract_stmts)*
+            }
+
+            /// Remove nested selectors that have nothing enabled, recursively.
+            ///
+            /// Such entries serialize as an empty `{}` object. `disable`,
+            /// `intersect` and `subtract` already drop the nested selectors they
+            /// empty; this also cleans up ones created by `enable("profile")` or
+            /// by editing the fields directly.
+            pub fn prune(&mut self) {
+                #(#prune_stmts)*
+            }
+
+            /// Normalize the selector so that selectors producing the same output
+            /// compare and hash equal.
+            ///
+            /// Aliases are already resolved when fields are enabled; this also
+            /// drops empty nested selectors, as `prune` does, and clears leaf
+            /// fields marked `always`, which are serialized either way.
+            pub fn canonicalize(&mut self) -> &mut Self {
+                #(#canonicalize_stmts)*
+                self
+            }
+
+            /// Whether no field is enabled at all.
+            ///
+            /// A nested field enabled with an empty nested selector still counts
+            /// as enabled, since it serializes as `{}`.
+            pub fn is_empty(&self) -> bool {
+                true #(&& self.#selector_field_idents.is_none())*
+            }
+
+            /// Number of leaf fields enabled, counting nested selectors
+            /// recursively.
+            pub fn enabled_leaf_count(&self) -> usize {
+                0 #(+ #leaf_count_terms)*
+            }
+
+            /// Whether the field at the dot path is enabled, e.g. `"profile.bio"`.
+            ///
+            /// A nested struct path such as `"profile"` is enabled when its
+            /// nested selector is present. Unknown paths return `false`.
+            pub fn contains_path(&self, field: &str) -> bool {
+                let split: Vec<&str> = field.split('.').collect();
+                self.contains(&split)
+            }
+
+            /// Whether the field at an already split path is enabled.
+            pub fn contains(&self, field_hierarchy: &[&str]) -> bool {
+                match field_hierarchy {
+                    [] => false,
+                    [name, rest @ ..] => match *name {
+                        #(#contains_arms,)*
+                        _ => false,
+                    },
+                }
+            }
+
+            /// The nested selector at a dot path, such as `"profile"`, as a
+            /// `DynFieldSelector`.
+            ///
+            /// Returns `None` for unknown paths, plain fields and nested fields
+            /// that are not enabled.
+            pub fn at_path(&self, field: &str) -> Option<&dyn #krate::DynFieldSelector> {
+                <Self as #krate::FieldSelector>::at_path(self, field)
+            }
+
+            /// Mutable counterpart of `at_path`.
+            pub fn at_path_mut(&mut self, field: &str) -> Option<&mut dyn #krate::DynFieldSelector> {
+                <Self as #krate::FieldSelector>::at_path_mut(self, field)
+            }
+
+            /// Build a selector from arbitrary bytes, for property and fuzz
+            /// tests, sensitive fields included; see
+            /// `serialize_fields::FieldSelector::from_entropy`.
+            #[doc(hidden)]
+            pub fn from_entropy(bytes: &[u8]) -> Self {
+                <Self as #krate::FieldSelector>::from_entropy(bytes)
+            }
+
+            /// Check that every `required` field is serialized; see
+            /// `serialize_fields::FieldSelector::validate_required`.
+            pub fn validate_required(&self) -> Result<(), #krate::MissingRequiredFields> {
+                <Self as #krate::FieldSelector>::validate_required(self)
+            }
+
+            /// Build a selector enabling the fields present in an example
+            /// document; see `serialize_fields::FieldSelector::from_json_shape`.
+            pub fn from_json_shape(shape: &#krate::__private::serde_json::Value) -> Self {
+                <Self as #krate::FieldSelector>::from_json_shape(shape)
+            }
+
+            /// SQL columns of the selected fields, in declaration order; see
+            /// `serialize_fields::FieldSelector::to_sql_columns`.
+            pub fn to_sql_columns(&self) -> Vec<&'static str> {
+                <Self as #krate::FieldSelector>::to_sql_columns(self)
+            }
+
+            /// SQL columns of the enabled fields, for an `UPDATE`; see
+            /// `serialize_fields::FieldSelector::to_update_columns`.
+            pub fn to_update_columns(&self) -> Vec<&'static str> {
+                <Self as #krate::FieldSelector>::to_update_columns(self)
+            }
+
+            /// SQL columns of the selected fields, split into table and column.
+            pub fn to_sql_column_refs(&self) -> Vec<#krate::SqlColumn> {
+                <Self as #krate::FieldSelector>::to_sql_column_refs(self)
+            }
+
+            /// Dot paths of the serialized leaf fields, using serialized names;
+            /// see `serialize_fields::FieldSelector::to_path_list`.
+            pub fn to_path_list(&self) -> Vec<String> {
+                <Self as #krate::FieldSelector>::to_path_list(self)
+            }
 
-            serialize_fields.push(quote! {
-                if field_selector.#field_ident.is_some() {
-                    state.serialize_field(#field_name_str, &data.#field_ident)?;
-                }
-            });
+            /// `to_path_list` in another format, such as
+            /// `serialize_fields::PathListFormat::FIRESTORE`.
+            pub fn to_path_list_with(&self, format: &#krate::PathListFormat) -> Vec<String> {
+                <Self as #krate::FieldSelector>::to_path_list_with(self, format)
+            }
 
-            // Simple enum variant
-            enum_variants.push(quote! {
-                #variant_ident
-            });
+            /// JSONPath expressions for the serialized leaf fields, e.g.
+            /// `$.profile.bio`; see `serialize_fields::FieldSelector::to_json_paths`.
+            pub fn to_json_paths(&self) -> Vec<String> {
+                <Self as #krate::FieldSelector>::to_json_paths(self)
+            }
 
-            enable_enum_match_arms.push(quote! {
-                #field_enum_ident::#variant_ident => self.#field_ident = Some(())
-            });
+            /// The Elasticsearch `_source` filter fetching the serialized fields;
+            /// see `serialize_fields::FieldSelector::to_es_source_filter`.
+            pub fn to_es_source_filter(&self) -> #krate::__private::serde_json::Value {
+                <Self as #krate::FieldSelector>::to_es_source_filter(self)
+            }
 
-            as_dot_path_arms.push(quote! {
-                #field_enum_ident::#variant_ident => #field_name_str.to_string()
-            });
+            /// Wrap the selector in a cheaply clonable, immutable
+            /// `serialize_fields::FrozenSelector`.
+            pub fn freeze(self) -> #krate::FrozenSelector<Self> {
+                #krate::FrozenSelector::new(self)
+            }
 
-            deserialize_match_arms.push(quote! {
-                #field_name_str => Ok(#field_enum_ident::#variant_ident)
-            });
+            /// Encode the selector as presence bits in declaration order; see
+            /// `serialize_fields::FieldSelector::to_bytes`.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                <Self as #krate::FieldSelector>::to_bytes(self)
+            }
 
-            #[cfg(feature = "schemars")]
-            schema_simple_fields.push(field_name_str.clone());
-        }
+            /// Decode a selector encoded by `to_bytes`. Only decode trusted
+            /// input, since sensitive fields are restored too.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, #krate::SelectorDecodeError> {
+                <Self as #krate::FieldSelector>::from_bytes(bytes)
+            }
 
-        new_field_inits.push(quote! {
-            #field_ident: None
-        });
-    }
+            /// A key for the struct type and the enabled paths, stable across
+            /// processes and independent of the order fields were enabled in.
+            pub fn cache_key(&self) -> u64 {
+                <Self as #krate::FieldSelector>::cache_key(self)
+            }
 
-    // Generate schema nested field tokens (used only with schemars feature)
-    #[cfg(feature = "schemars")]
-    let schema_nested_enum_types: Vec<_> = schema_nested_prefixes
-        .iter()
-        .map(|(_, nested_type)| {
-            let ident = syn::Ident::new(&format!("{}Field", nested_type), struct_name.span());
-            quote! { #ident }
-        })
-        .collect();
-    #[cfg(feature = "schemars")]
-    let schema_nested_prefix_strs: Vec<_> = schema_nested_prefixes
-        .iter()
-        .map(|(prefix, _)| prefix.clone())
-        .collect();
+            /// Names of the nested fields serialized with some of their fields,
+            /// e.g. `["profile"]` for `id,profile.bio`; `always` nested fields
+            /// are always listed.
+            pub fn active_nested(&self) -> Vec<&'static str> {
+                #[allow(unused_mut)]
+                let mut names = Vec::new();
+                #(#active_nested_stmts)*
+                names
+            }
 
-    // Count enabled fields for serialization
-    let count_enabled_fields = fields
-        .iter()
-        .map(|field: &syn::Field| {
-            let field_ident = field.ident.as_ref().unwrap();
-            quote! {
-                + if field_selector.#field_ident.is_some() { 1 } else { 0 }
+            /// Number of segments in the longest enabled path, e.g. `2` for
+            /// `profile.bio`; `0` when nothing is enabled.
+            pub fn depth(&self) -> usize {
+                #[allow(unused_mut)]
+                let mut depth = 0;
+                #(#depth_stmts)*
+                depth
             }
-        })
-        .collect::<Vec<_>>();
 
-    // Generate schemars impl conditionally at macro compile-time
-    #[cfg(feature = "schemars")]
-    let schemars_impl = quote! {
-        impl ::schemars::JsonSchema for #field_enum_ident {
-            fn schema_name() -> ::std::borrow::Cow<'static, str> {
-                ::std::borrow::Cow::Borrowed(stringify!(#field_enum_ident))
+            /// Dot paths of the enabled leaf fields, in declaration order.
+            ///
+            /// A nested field enabled without any of its own fields is listed
+            /// by its path, so `enable_dot_hierarchy` on every returned path
+            /// rebuilds the same selector.
+            pub fn enabled_paths(&self) -> Vec<String> {
+                let mut paths = Vec::new();
+                self.__collect_enabled_paths("", false, &mut paths);
+                paths
             }
 
-            fn json_schema(generator: &mut ::schemars::SchemaGenerator) -> ::schemars::Schema {
-                // Collect all possible enum values
-                let mut all_values: Vec<String> = Vec::new();
+            /// Like `enabled_paths`, but also lists each enabled nested field
+            /// before its own paths, e.g. `profile` before `profile.bio`.
+            pub fn enabled_paths_with_parents(&self) -> Vec<String> {
+                let mut paths = Vec::new();
+                self.__collect_enabled_paths("", true, &mut paths);
+                paths
+            }
 
-                // Add simple field values
-                #(all_values.push(#schema_simple_fields.to_string());)*
+            #[doc(hidden)]
+            pub fn __collect_enabled_paths(
+                &self,
+                prefix: &str,
+                with_parents: bool,
+                paths: &mut Vec<String>,
+            ) {
+                #(#collect_paths_stmts)*
+            }
 
-                // For nested fields, get their enum values and prefix them
-                #(
-                    // Call json_schema directly to get the inline schema, not a $ref
-                    let nested_schema = <#schema_nested_enum_types as ::schemars::JsonSchema>::json_schema(generator);
-                    if let Some(obj) = nested_schema.as_object() {
-                        if let Some(enum_values) = obj.get("enum").and_then(|v| v.as_array()) {
-                            for val in enum_values {
-                                if let Some(s) = val.as_str() {
-                                    all_values.push(format!("{}.{}", #schema_nested_prefix_strs, s));
-                                }
-                            }
-                        }
-                    }
-                )*
+            /// Whether every field enabled in `self` is also enabled in `other`,
+            /// recursively.
+            pub fn is_subset_of(&self, other: &Self) -> bool {
+                true #(&& #subset_checks)*
+            }
 
-                ::schemars::json_schema!({
-                    "type": "string",
-                    "enum": all_values,
-                    "description": concat!("Field selector for ", stringify!(#struct_name), " - serializes as dot notation (e.g., \"field.nested\")")
-                })
+            /// Whether every field enabled in `other` is also enabled in `self`,
+            /// recursively.
+            pub fn is_superset_of(&self, other: &Self) -> bool {
+                other.is_subset_of(self)
             }
         }
-    };
 
-    #[cfg(not(feature = "schemars"))]
-    let schemars_impl = quote! {};
+        impl Default for #selector_ident {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
 
-    // Generate the complete implementation
-    let expanded = quote! {
-        /// Enum representing all fields of `#struct_name` for type-safe field selection.
-        /// Serializes to dot notation (e.g., "profile.bio").
-        #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-        pub enum #field_enum_ident {
-            #(#enum_variants,)*
+        /// Formats the selection as the canonical comma-separated field list,
+        /// e.g. `id,name,profile.bio`, in declaration order.
+        impl ::std::fmt::Display for #selector_ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(&self.enabled_paths().join(","))
+            }
         }
 
-        impl #field_enum_ident {
-            /// Returns the dot notation path for this field.
-            pub fn as_dot_path(&self) -> String {
-                match self {
-                    #(#as_dot_path_arms,)*
+        /// Parses a comma-separated field list, rejecting unknown and
+        /// malformed paths; see `serialize_fields::utils::parse_selector`.
+        impl ::std::str::FromStr for #selector_ident {
+            type Err = #krate::SelectorParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                #krate::utils::parse_selector(s)
+            }
+        }
+
+        /// Enables every dot path yielded by the iterator; unknown paths are
+        /// ignored, as with `enable_dot_hierarchy`.
+        impl<__P: AsRef<str>> ::std::iter::FromIterator<__P> for #selector_ident {
+            fn from_iter<__I: IntoIterator<Item = __P>>(paths: __I) -> Self {
+                let mut selector = Self::new();
+                selector.extend(paths);
+                selector
+            }
+        }
+
+        impl<__P: AsRef<str>> ::std::iter::Extend<__P> for #selector_ident {
+            fn extend<__I: IntoIterator<Item = __P>>(&mut self, paths: __I) {
+                for path in paths {
+                    self.enable_dot_hierarchy(path.as_ref());
                 }
             }
         }
 
-        impl ::std::fmt::Debug for #field_enum_ident {
-            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-                write!(f, "{}", self.as_dot_path())
+        impl ::std::ops::BitOr for #selector_ident {
+            type Output = Self;
+
+            fn bitor(mut self, other: Self) -> Self {
+                self.merge(&other);
+                self
             }
         }
 
-        impl ::std::fmt::Display for #field_enum_ident {
-            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-                write!(f, "{}", self.as_dot_path())
+        impl ::std::ops::BitOr for &#selector_ident {
+            type Output = #selector_ident;
+
+            fn bitor(self, other: Self) -> #selector_ident {
+                let mut merged = self.clone();
+                merged.merge(other);
+                merged
             }
         }
 
-        impl ::std::str::FromStr for #field_enum_ident {
-            type Err = String;
+        impl ::std::ops::BitOrAssign for #selector_ident {
+            fn bitor_assign(&mut self, other: Self) {
+                self.merge(&other);
+            }
+        }
 
-            fn from_str(s: &str) -> Result<Self, Self::Err> {
-                match s {
-                    #(#deserialize_match_arms,)*
-                    _ => Err(format!("Unknown field: {}", s)),
-                }
+        impl ::std::ops::BitAnd for #selector_ident {
+            type Output = Self;
+
+            fn bitand(mut self, other: Self) -> Self {
+                self.intersect(&other);
+                self
             }
         }
 
-        impl ::serde::Serialize for #field_enum_ident {
-            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: ::serde::Serializer,
-            {
-                serializer.serialize_str(&self.as_dot_path())
+        impl ::std::ops::BitAnd for &#selector_ident {
+            type Output = #selector_ident;
+
+            fn bitand(self, other: Self) -> #selector_ident {
+                let mut intersection = self.clone();
+                intersection.intersect(other);
+                intersection
             }
         }
 
-        impl<'de> ::serde::Deserialize<'de> for #field_enum_ident {
-            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-            where
-                D: ::serde::Deserializer<'de>,
-            {
-                let s = String::deserialize(deserializer)?;
-                s.parse().map_err(::serde::de::Error::custom)
+        impl ::std::ops::BitAndAssign for #selector_ident {
+            fn bitand_assign(&mut self, other: Self) {
+                self.intersect(&other);
             }
         }
 
-        #schemars_impl
+        impl ::std::ops::Sub for #selector_ident {
+            type Output = Self;
 
-        #[derive(Debug, Clone, PartialEq, Eq, Hash, ::serde::Serialize)]
-        pub struct #selector_ident {
-            #(#selector_fields,)*
+            fn sub(mut self, other: Self) -> Self {
+                self.subtract(&other);
+                self
+            }
         }
 
-        impl #selector_ident {
-            pub fn new() -> Self {
-                #selector_ident {
-                    #(#new_field_inits,)*
-                }
+        impl ::std::ops::Sub for &#selector_ident {
+            type Output = #selector_ident;
+
+            fn sub(self, other: Self) -> #selector_ident {
+                let mut difference = self.clone();
+                difference.subtract(other);
+                difference
             }
+        }
 
-            pub fn enable_dot_hierarchy(&mut self, field: &str) {
-                let split: Vec<&str> = field.split('.').collect();
-                self.enable(&split);
+        impl ::std::ops::SubAssign for #selector_ident {
+            fn sub_assign(&mut self, other: Self) {
+                self.subtract(&other);
             }
+        }
 
-            pub fn enable(&mut self, field_hierarchy: &[&str]) {
-                if field_hierarchy.is_empty() {
-                    return;
-                }
+        #(#preset_checks)*
 
-                match field_hierarchy[0] {
-                    #(#enable_match_arms,)*
-                    _ => {}
-                }
+        #[doc = #builder_doc]
+        #[derive(Debug, Clone, PartialEq, Eq, Default)]
+        #[must_use]
+        #generated_vis struct #builder_ident {
+            selector: #selector_ident,
+        }
+
+        impl #builder_ident {
+            #(#builder_methods)*
+
+            /// Finish building and return the selector.
+            pub const fn build(self) -> #selector_ident {
+                self.selector
             }
+        }
 
-            /// Enable a field using the type-safe field enum.
-            pub fn enable_enum(&mut self, field: #field_enum_ident) {
-                match field {
-                    #(#enable_enum_match_arms,)*
-                }
+        impl From<#selector_ident> for #builder_ident {
+            fn from(selector: #selector_ident) -> Self {
+                #builder_ident { selector }
             }
         }
 
-        impl Default for #selector_ident {
-            fn default() -> Self {
-                Self::new()
+        impl From<#builder_ident> for #selector_ident {
+            fn from(builder: #builder_ident) -> Self {
+                builder.selector
             }
         }
 
-        impl ::serialize_fields::FieldSelector for #selector_ident {
+        impl #krate::BuildSelector for #selector_ident {
+            type Builder = #builder_ident;
+        }
+
+        impl #krate::FieldSelector for #selector_ident {
+            const FIELD_PATHS: &'static [&'static str] = Self::FIELD_PATHS;
+
+            const TYPE_NAME: &'static str = concat!(module_path!(), "::", stringify!(#struct_name));
+
+            const __SELECTABLE_PATHS: &'static [&'static str] = #field_enum_ident::__SELECTABLE;
+
             fn new() -> Self {
                 Self::new()
             }
 
+            fn with_defaults() -> Self {
+                Self::with_defaults()
+            }
+
+            fn all() -> Self {
+                Self::all()
+            }
+
+            fn enable_all(&mut self) {
+                self.enable_all()
+            }
+
             fn enable_dot_hierarchy(&mut self, field: &str) {
                 self.enable_dot_hierarchy(field)
             }
@@ -382,50 +2622,244 @@ pub fn serialize_fields_derive(input: TokenStream) -> TokenStream {
             fn enable(&mut self, field_hierarchy: &[&str]) {
                 self.enable(field_hierarchy)
             }
-        }
 
-        impl ::serialize_fields::SerializeFieldsTrait for #struct_name {
-            type FieldSelector = #selector_ident;
+            fn disable_dot_hierarchy(&mut self, field: &str) {
+                self.disable_dot_hierarchy(field)
+            }
+
+            fn disable(&mut self, field_hierarchy: &[&str]) {
+                self.disable(field_hierarchy)
+            }
+
+            fn merge(&mut self, other: &Self) {
+                self.merge(other)
+            }
+
+            fn intersect(&mut self, other: &Self) {
+                self.intersect(other)
+            }
+
+            fn subtract(&mut self, other: &Self) {
+                self.subtract(other)
+            }
+
+            #schemars_filter_impl
 
-            fn serialize_fields(&self) -> Self::FieldSelector {
-                #selector_ident::new()
+            fn __validate_path(field_hierarchy: &[&str]) -> Result<(), usize> {
+                match field_hierarchy {
+                    [] => Err(0),
+                    ["*"] | ["**"] => Ok(()),
+                    [name, rest @ ..] => match *name {
+                        #(#validate_path_arms,)*
+                        _ => Err(0),
+                    },
+                }
+            }
+
+            fn __collect_sql_columns(&self, with_always: bool, columns: &mut Vec<&'static str>) {
+                #(#sql_column_stmts)*
             }
 
-            fn serialize<__S>(
+            fn __visit_output_paths(
                 &self,
-                field_selector: &Self::FieldSelector,
-                __serializer: __S,
-            ) -> Result<__S::Ok, __S::Error>
-            where
-                __S: ::serde::Serializer,
-            {
-                use ::serde::ser::SerializeStruct;
-                use ::serialize_fields::SerializeFields;
+                with_always: bool,
+                path: &mut Vec<#krate::__private::PathSegment>,
+                visit: &mut dyn FnMut(&[#krate::__private::PathSegment]),
+            ) {
+                #(#output_path_stmts)*
+            }
+
+            fn __collect_source_paths(
+                &self,
+                prefix: &str,
+                includes: &mut Vec<String>,
+                excludes: &mut Vec<String>,
+            ) {
+                #(#source_path_stmts)*
+            }
+
+            fn __collect_missing_required(&self, prefix: &str, missing: &mut Vec<String>) {
+                #(#required_stmts)*
+            }
+
+            fn __enable_json_shape(
+                &mut self,
+                shape: &#krate::__private::serde_json::Map<String, #krate::__private::serde_json::Value>,
+            ) {
+                #(#json_shape_stmts)*
+            }
+
+            fn __canonical_path(path: &[&str]) -> Option<String> {
+                match path {
+                    [] => None,
+                    [wildcard @ ("*" | "**")] => Some(wildcard.to_string()),
+                    [name, rest @ ..] => match *name {
+                        #(#canonical_path_arms,)*
+                        _ => None,
+                    },
+                }
+            }
+
+            fn prune(&mut self) {
+                self.prune()
+            }
+
+            fn is_empty(&self) -> bool {
+                self.is_empty()
+            }
+
+            fn enabled_paths(&self) -> Vec<String> {
+                self.enabled_paths()
+            }
+
+            fn contains_path(&self, field: &str) -> bool {
+                self.contains_path(field)
+            }
+
+            fn contains(&self, field_hierarchy: &[&str]) -> bool {
+                self.contains(field_hierarchy)
+            }
+
+            fn at(&self, field_hierarchy: &[&str]) -> Option<&dyn #krate::DynFieldSelector> {
+                match field_hierarchy {
+                    [] => Some(self),
+                    [name, rest @ ..] => match *name {
+                        #(#at_arms,)*
+                        _ => None,
+                    },
+                }
+            }
+
+            fn at_mut(
+                &mut self,
+                field_hierarchy: &[&str],
+            ) -> Option<&mut dyn #krate::DynFieldSelector> {
+                match field_hierarchy {
+                    [] => Some(self),
+                    [name, rest @ ..] => match *name {
+                        #(#at_mut_arms,)*
+                        _ => None,
+                    },
+                }
+            }
+
+            fn enabled_leaf_count(&self) -> usize {
+                self.enabled_leaf_count()
+            }
+
+            fn active_nested(&self) -> Vec<&'static str> {
+                self.active_nested()
+            }
 
-                let data = self;
+            fn depth(&self) -> usize {
+                self.depth()
+            }
 
-                // Count enabled fields
-                let field_count = 0 #(#count_enabled_fields)*;
+            fn canonicalize(&mut self) {
+                self.canonicalize();
+            }
 
-                let mut state = __serializer.serialize_struct(stringify!(#struct_name), field_count)?;
+            fn __write_bits(&self, writer: &mut #krate::__private::BitWriter) {
+                #(#write_bits_stmts)*
+            }
 
-                #(#serialize_fields)*
+            fn __read_bits(
+                reader: &mut #krate::__private::BitReader<'_>,
+            ) -> Result<Self, #krate::SelectorDecodeError> {
+                #[allow(unused_mut)]
+                let mut selector = Self::new();
+                #(#read_bits_stmts)*
+                Ok(selector)
+            }
 
-                state.end()
+            fn is_subset_of(&self, other: &Self) -> bool {
+                self.is_subset_of(other)
             }
         }
+
+        #serialize_impl
+
+        #update_impl
+
+        #diff_impl
+
+        #partial_impl
+
+        #clear_impl
+
+        #eq_impl
+
+        #hash_impl
+
+        #clone_impl
+
+        #present_impl
     };
 
-    TokenStream::from(expanded)
+    Ok(expanded)
+}
+
+/// Body of the generated serialization function, writing the fields of `data`
+/// enabled in `field_selector` to `__serializer`.
+fn serialize_body(
+    krate: &syn::Path,
+    type_name: &syn::Ident,
+    serialize_fields: &[proc_macro2::TokenStream],
+    count_enabled_fields: &[proc_macro2::TokenStream],
+    serialized_field_names: &[String],
+) -> proc_macro2::TokenStream {
+    let serialized_field_count = serialize_fields.len();
+    let field_indices: Vec<usize> = (0..serialized_field_count).collect();
+    let mut sorted_field_indices = field_indices.clone();
+    sorted_field_indices.sort_by(|&a, &b| serialized_field_names[a].cmp(&serialized_field_names[b]));
+
+    quote! {
+        // Count the fields written: all of them when disabled ones are
+        // nulls or redacted
+        let field_count = if options.nulls || options.redact.is_some() {
+            #serialized_field_count
+        } else {
+            0 #(#count_enabled_fields)*
+        };
+
+        let mut state = #krate::__private::FieldWriter::new(
+            __serializer,
+            stringify!(#type_name),
+            field_count,
+            options,
+        )?;
+
+        // Fields are written in declaration order, or sorted by key
+        const NAMES: &[&str] = &[#(#serialized_field_names),*];
+        const DECLARED: &[usize] = &[#(#field_indices),*];
+        const SORTED: &[usize] = &[#(#sorted_field_indices),*];
+        for &index in state.order(options, NAMES, DECLARED, SORTED).iter() {
+            match index {
+                #(#field_indices => { #serialize_fields })*
+                _ => {}
+            }
+        }
+
+        state.end()
+    }
 }
 
+/// Field names whose `enable_<field>` method would clash with a generated
+/// selector method; no typed method is generated for them.
+const RESERVED_ENABLE_SUFFIXES: &[&str] = &[
+    "all",
+    "dot_hierarchy",
+    "sensitive",
+    "sensitive_hierarchy",
+    "group",
+    "for_role",
+    "enum",
+    "with_policy",
+];
+
 /// Strip the r# prefix from raw identifiers
 fn strip_raw_prefix(s: &str) -> String {
-    if s.starts_with("r#") {
-        s[2..].to_string()
-    } else {
-        s.to_string()
-    }
+    s.strip_prefix("r#").unwrap_or(s).to_string()
 }
 
 /// Convert snake_case to PascalCase for enum variant names
@@ -441,8 +2875,11 @@ fn to_pascal_case(s: &str) -> String {
         .collect()
 }
 
-/// Analyze a field type to determine if it's a nested struct and what type it is
-fn analyze_field_type(ty: &Type) -> (bool, String) {
+/// Analyze a field type to find the nested struct it refers to, if any.
+///
+/// Containers such as `Option` and `Vec` are looked through; primitives and
+/// well-known standard library types are treated as plain values.
+fn analyze_field_type(ty: &Type) -> Option<&TypePath> {
     match ty {
         Type::Path(type_path) => {
             let last_segment = type_path.path.segments.last().unwrap();
@@ -451,28 +2888,26 @@ fn analyze_field_type(ty: &Type) -> (bool, String) {
             match type_name.as_str() {
                 // Primitive types
                 "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
-                | "i128" | "isize" | "f32" | "f64" | "bool" | "char" | "String" => {
-                    (false, String::new())
-                }
+                | "i128" | "isize" | "f32" | "f64" | "bool" | "char" | "String" => None,
 
                 // Standard library types that don't derive SerializeFields
-                "PathBuf" | "SystemTime" | "Duration" => (false, String::new()),
+                "PathBuf" | "SystemTime" | "Duration" => None,
 
                 // Container types - check inner type for Vec, Option, etc.
                 "Option" | "Vec" | "HashMap" | "BTreeMap" | "HashSet" | "BTreeSet" => {
-                    if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
-                        if let Some(GenericArgument::Type(inner_ty)) = args.args.first() {
-                            return analyze_field_type(inner_ty);
-                        }
+                    if let PathArguments::AngleBracketed(args) = &last_segment.arguments
+                        && let Some(GenericArgument::Type(inner_ty)) = args.args.first()
+                    {
+                        return analyze_field_type(inner_ty);
                     }
-                    (false, String::new())
+                    None
                 }
 
                 // Result and similar types - usually not serialized
-                "Result" | "Box" | "Rc" | "Arc" => (false, String::new()),
+                "Result" | "Box" | "Rc" | "Arc" => None,
 
                 // Assume any other type is a custom struct that might derive SerializeFields
-                _ => (true, type_name),
+                _ => Some(type_path),
             }
         }
         Type::Array(type_array) => {
@@ -481,8 +2916,56 @@ fn analyze_field_type(ty: &Type) -> (bool, String) {
         }
         Type::Tuple(_type_tuple) => {
             // For tuples, assume they're not custom structs
-            (false, String::new())
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Whether a nested field holds a sequence of structs, such as `Vec<Post>` or
+/// `Option<[Post; 3]>`, rather than a single one.
+fn is_sequence_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => {
+            let last_segment = type_path.path.segments.last().unwrap();
+            match last_segment.ident.to_string().as_str() {
+                "Vec" | "HashSet" | "BTreeSet" => true,
+                "Option" => match &last_segment.arguments {
+                    PathArguments::AngleBracketed(args) => match args.args.first() {
+                        Some(GenericArgument::Type(inner_ty)) => is_sequence_type(inner_ty),
+                        _ => false,
+                    },
+                    _ => false,
+                },
+                _ => false,
+            }
         }
-        _ => (false, String::new()),
+        Type::Array(_) => true,
+        _ => false,
     }
-}
\ No newline at end of file
+}
+
+/// Path of the partial struct generated for a nested struct type.
+///
+/// `crate::models::Profile` becomes `crate::models::PartialProfile`.
+fn partial_path(type_path: &TypePath) -> syn::Path {
+    let mut path = type_path.path.clone();
+    let last_segment = path.segments.last_mut().unwrap();
+    last_segment.ident = format_ident!("Partial{}", last_segment.ident, span = last_segment.ident.span());
+    last_segment.arguments = PathArguments::None;
+    path
+}
+
+/// Path of the field enum generated for a nested struct type.
+///
+/// `crate::models::Profile` becomes `crate::models::ProfileField`.
+fn field_enum_path(type_path: &TypePath) -> syn::Path {
+    let mut path = type_path.path.clone();
+    let last_segment = path.segments.last_mut().unwrap();
+    last_segment.ident = syn::Ident::new(
+        &format!("{}Field", last_segment.ident),
+        last_segment.ident.span(),
+    );
+    last_segment.arguments = PathArguments::None;
+    path
+}